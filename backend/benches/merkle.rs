@@ -0,0 +1,54 @@
+//! Merkle tree construction benchmarks
+//!
+//! Compares the sparse `MerkleTree` builder with the dense reference that
+//! hashes every padded leaf. Run with `cargo bench --bench merkle`.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use shadow_drop_api::common::merkle::{
+    DEFAULT_TREE_DEPTH, MerkleTree, dense_merkle_root, generate_secret,
+};
+
+fn recipients(count: usize) -> Vec<(String, u64, [u8; 32])> {
+    (0..count)
+        .map(|i| {
+            let wallet = bs58::encode((i as u32).to_le_bytes().repeat(8)).into_string();
+            (wallet, 1_000 + i as u64, generate_secret())
+        })
+        .collect()
+}
+
+fn bench_default_depth(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_depth_8");
+
+    for count in [2, 32, 256] {
+        let recipients = recipients(count);
+
+        group.bench_with_input(BenchmarkId::new("sparse", count), &recipients, |b, r| {
+            b.iter(|| MerkleTree::from_recipients(r, DEFAULT_TREE_DEPTH).root())
+        });
+        group.bench_with_input(BenchmarkId::new("dense", count), &recipients, |b, r| {
+            b.iter(|| dense_merkle_root(r, DEFAULT_TREE_DEPTH))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_deep_trees(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_depth_20");
+    group.sample_size(10);
+
+    // Dense construction at depth 20 hashes ~2M nodes, so only sparse runs here
+    for count in [2, 1_000, 10_000] {
+        let recipients = recipients(count);
+
+        group.bench_with_input(BenchmarkId::new("sparse", count), &recipients, |b, r| {
+            b.iter(|| MerkleTree::from_recipients(r, 20).root())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_default_depth, bench_deep_trees);
+criterion_main!(benches);
@@ -0,0 +1,3 @@
+-- Per-recipient claim secrets, generated once at campaign creation and
+-- stored encrypted (hex nonce || AES-256-GCM ciphertext)
+ALTER TABLE recipients ADD COLUMN secret_ciphertext TEXT;
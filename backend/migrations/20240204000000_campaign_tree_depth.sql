@@ -0,0 +1,2 @@
+-- Merkle tree depth per campaign (existing campaigns were built with depth 8)
+ALTER TABLE campaigns ADD COLUMN tree_depth SMALLINT NOT NULL DEFAULT 8;
@@ -0,0 +1,26 @@
+-- Queue of ZK proof generation jobs
+-- Workers claim rows with FOR UPDATE SKIP LOCKED and hold a lease
+-- (`locked_until`); a job whose worker died is picked up again once the
+-- lease expires, so restarts don't lose work.
+CREATE TABLE IF NOT EXISTS proof_jobs (
+    id UUID PRIMARY KEY,
+    campaign_address TEXT NOT NULL REFERENCES campaigns(address) ON DELETE CASCADE,
+    wallet TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    result JSONB,
+    error TEXT,
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- At most one active job per claim
+CREATE UNIQUE INDEX IF NOT EXISTS proof_jobs_active_claim
+    ON proof_jobs (campaign_address, wallet)
+    WHERE status IN ('queued', 'running');
+
+CREATE INDEX IF NOT EXISTS proof_jobs_pending
+    ON proof_jobs (run_after)
+    WHERE status IN ('queued', 'running');
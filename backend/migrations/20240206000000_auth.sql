@@ -0,0 +1,20 @@
+-- Wallet sign-in challenges; a nonce is deleted when it is used
+CREATE TABLE IF NOT EXISTS auth_nonces (
+    nonce TEXT PRIMARY KEY,
+    wallet TEXT NOT NULL,
+    message TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Sessions issued for a verified signature, keyed by SHA-256(token) so a
+-- leaked table does not leak usable tokens
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    token_hash TEXT PRIMARY KEY,
+    wallet TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS auth_nonces_expires ON auth_nonces (expires_at);
+CREATE INDEX IF NOT EXISTS auth_sessions_expires ON auth_sessions (expires_at);
@@ -0,0 +1,32 @@
+-- Claims observed on chain by the indexer (see src/indexer.rs)
+-- One row per claim instruction; the primary key makes replaying a
+-- transaction a no-op, so recipient totals are never counted twice.
+CREATE TABLE IF NOT EXISTS chain_claims (
+    signature TEXT NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    campaign_address TEXT NOT NULL,
+    wallet TEXT NOT NULL,
+    instruction TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    nullifier TEXT,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (signature, instruction_index)
+);
+
+CREATE INDEX IF NOT EXISTS chain_claims_recipient
+    ON chain_claims (campaign_address, wallet);
+
+-- Last transaction each indexer has processed
+CREATE TABLE IF NOT EXISTS indexer_cursors (
+    name TEXT PRIMARY KEY,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Claim status as reconciled from chain
+ALTER TABLE recipients
+    ADD COLUMN IF NOT EXISTS claimed_amount TEXT NOT NULL DEFAULT '0',
+    ADD COLUMN IF NOT EXISTS claim_tx_signature TEXT;
@@ -0,0 +1,10 @@
+-- Set by the indexer when the program closes the campaign (see src/indexer.rs)
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS closed_at TIMESTAMPTZ;
+
+-- Campaign listings page through a creator's campaigns, newest first
+CREATE INDEX IF NOT EXISTS campaigns_creator_created_at
+    ON campaigns (creator_wallet, created_at DESC, address DESC);
+
+-- Eligible campaign listings look recipients up by wallet
+CREATE INDEX IF NOT EXISTS recipients_wallet
+    ON recipients (wallet) WHERE NOT claimed;
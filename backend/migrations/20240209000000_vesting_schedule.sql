@@ -0,0 +1,4 @@
+-- Shape of the vesting curve (src/vesting.rs `VestingSchedule`), copied
+-- from the campaign account on registration
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS vesting_schedule JSONB NOT NULL
+    DEFAULT '{"kind": "linear"}';
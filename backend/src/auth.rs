@@ -0,0 +1,519 @@
+//! Wallet-signature authentication
+//!
+//! A client asks for a challenge (`POST /auth/nonce`), signs the returned
+//! message with the wallet's ed25519 key and trades the signature for a
+//! short-lived bearer token (`POST /auth/verify`). Nonces are single use and
+//! only a SHA-256 hash of each token is stored. Handlers that change state
+//! take an [`AuthSession`] and check it against the wallet they act for.
+//! Nonces and sessions live behind an [`AuthRepository`].
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{StatusCode, header, request::Parts},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use ed25519_dalek::{Signature, VerifyingKey};
+use rand::{RngCore, rngs::OsRng};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::{common::response::ApiErrorResponse, config::AuthConfig, state::AppState};
+
+/// A message for the wallet to sign
+#[derive(Debug, Clone, Serialize)]
+pub struct Challenge {
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A session issued for a verified signature
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    /// Bearer token for the `Authorization` header
+    pub token: String,
+    pub wallet: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Authentication failures
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("Invalid wallet address")]
+    InvalidWallet,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Unknown or expired nonce")]
+    UnknownNonce,
+
+    #[error("Missing bearer token")]
+    MissingToken,
+
+    #[error("Invalid or expired session")]
+    InvalidSession,
+
+    #[error("Signed in as {signer}, but the request is for {wallet}")]
+    WrongWallet { signer: String, wallet: String },
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<AuthError> for ApiErrorResponse {
+    fn from(e: AuthError) -> Self {
+        let code = match e {
+            AuthError::InvalidWallet => StatusCode::BAD_REQUEST,
+            AuthError::WrongWallet { .. } => StatusCode::FORBIDDEN,
+            AuthError::Database(ref err) => {
+                tracing::error!("Auth database error: {}", err);
+                return ApiErrorResponse::default().with_message("Authentication failed");
+            }
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        ApiErrorResponse::default()
+            .with_code(code)
+            .with_message(&e.to_string())
+    }
+}
+
+/// Storage of sign-in nonces and sessions
+#[async_trait]
+pub trait AuthRepository: Send + Sync {
+    /// Store a challenge, dropping expired ones
+    async fn insert_nonce(
+        &self,
+        nonce: &str,
+        wallet: &str,
+        message: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError>;
+
+    /// Remove a challenge, returning its message if it had not expired
+    async fn take_nonce(&self, nonce: &str, wallet: &str) -> Result<Option<String>, AuthError>;
+
+    /// Store a session, dropping expired ones
+    async fn insert_session(
+        &self,
+        token_hash: &str,
+        wallet: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError>;
+
+    /// Wallet of a live session
+    async fn session_wallet(&self, token_hash: &str) -> Result<Option<String>, AuthError>;
+}
+
+/// `AuthRepository` in PostgreSQL
+#[derive(Debug, Clone)]
+pub struct PgAuthRepository {
+    db: PgPool,
+}
+
+impl PgAuthRepository {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AuthRepository for PgAuthRepository {
+    async fn insert_nonce(
+        &self,
+        nonce: &str,
+        wallet: &str,
+        message: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError> {
+        sqlx::query!("DELETE FROM auth_nonces WHERE expires_at < NOW()")
+            .execute(&self.db)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO auth_nonces (nonce, wallet, message, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            nonce,
+            wallet,
+            message,
+            expires_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn take_nonce(&self, nonce: &str, wallet: &str) -> Result<Option<String>, AuthError> {
+        let challenge = sqlx::query!(
+            r#"
+            DELETE FROM auth_nonces
+            WHERE nonce = $1 AND wallet = $2
+            RETURNING message, expires_at
+            "#,
+            nonce,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(challenge
+            .filter(|row| row.expires_at > Utc::now())
+            .map(|row| row.message))
+    }
+
+    async fn insert_session(
+        &self,
+        token_hash: &str,
+        wallet: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError> {
+        sqlx::query!("DELETE FROM auth_sessions WHERE expires_at < NOW()")
+            .execute(&self.db)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO auth_sessions (token_hash, wallet, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+            token_hash,
+            wallet,
+            expires_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn session_wallet(&self, token_hash: &str) -> Result<Option<String>, AuthError> {
+        let wallet = sqlx::query_scalar!(
+            "SELECT wallet FROM auth_sessions WHERE token_hash = $1 AND expires_at > NOW()",
+            token_hash
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(wallet)
+    }
+}
+
+/// `AuthRepository` in memory, for tests and offline development
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryAuthRepository {
+    /// By nonce
+    nonces: Arc<RwLock<HashMap<String, StoredNonce>>>,
+    /// By token hash
+    sessions: Arc<RwLock<HashMap<String, StoredSession>>>,
+}
+
+#[derive(Debug)]
+struct StoredNonce {
+    wallet: String,
+    message: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct StoredSession {
+    wallet: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl InMemoryAuthRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AuthRepository for InMemoryAuthRepository {
+    async fn insert_nonce(
+        &self,
+        nonce: &str,
+        wallet: &str,
+        message: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError> {
+        let mut nonces = self.nonces.write().unwrap();
+        let now = Utc::now();
+        nonces.retain(|_, n| n.expires_at >= now);
+        nonces.insert(
+            nonce.to_string(),
+            StoredNonce {
+                wallet: wallet.to_string(),
+                message: message.to_string(),
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    async fn take_nonce(&self, nonce: &str, wallet: &str) -> Result<Option<String>, AuthError> {
+        let mut nonces = self.nonces.write().unwrap();
+        if nonces.get(nonce).is_none_or(|n| n.wallet != wallet) {
+            return Ok(None);
+        }
+        Ok(nonces
+            .remove(nonce)
+            .filter(|n| n.expires_at > Utc::now())
+            .map(|n| n.message))
+    }
+
+    async fn insert_session(
+        &self,
+        token_hash: &str,
+        wallet: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError> {
+        let mut sessions = self.sessions.write().unwrap();
+        let now = Utc::now();
+        sessions.retain(|_, s| s.expires_at >= now);
+        sessions.insert(
+            token_hash.to_string(),
+            StoredSession {
+                wallet: wallet.to_string(),
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    async fn session_wallet(&self, token_hash: &str) -> Result<Option<String>, AuthError> {
+        Ok(self
+            .sessions
+            .read()
+            .unwrap()
+            .get(token_hash)
+            .filter(|s| s.expires_at > Utc::now())
+            .map(|s| s.wallet.clone()))
+    }
+}
+
+/// Issues challenges and sessions
+#[derive(Clone)]
+pub struct AuthStore {
+    repo: Arc<dyn AuthRepository>,
+    nonce_ttl: Duration,
+    session_ttl: Duration,
+}
+
+impl AuthStore {
+    pub fn new(repo: Arc<dyn AuthRepository>, config: &AuthConfig) -> Self {
+        Self {
+            repo,
+            nonce_ttl: config.nonce_ttl,
+            session_ttl: config.session_ttl,
+        }
+    }
+
+    /// Create a sign-in challenge for `wallet`
+    pub async fn challenge(&self, wallet: &str) -> Result<Challenge, AuthError> {
+        parse_wallet(wallet)?;
+
+        let nonce = random_hex::<16>();
+        let issued_at = Utc::now();
+        let expires_at = issued_at + self.nonce_ttl;
+        let message = sign_in_message(wallet, &nonce, issued_at, expires_at);
+
+        self.repo
+            .insert_nonce(&nonce, wallet, &message, expires_at)
+            .await?;
+
+        Ok(Challenge {
+            nonce,
+            message,
+            expires_at,
+        })
+    }
+
+    /// Check a signed challenge and open a session
+    ///
+    /// The nonce is consumed whether or not the signature is valid.
+    pub async fn sign_in(
+        &self,
+        wallet: &str,
+        nonce: &str,
+        signature: &str,
+    ) -> Result<Session, AuthError> {
+        let message = self
+            .repo
+            .take_nonce(nonce, wallet)
+            .await?
+            .ok_or(AuthError::UnknownNonce)?;
+
+        verify_signature(wallet, message.as_bytes(), signature)?;
+
+        let token = random_hex::<32>();
+        let expires_at = Utc::now() + self.session_ttl;
+        self.repo
+            .insert_session(&token_hash(&token), wallet, expires_at)
+            .await?;
+
+        Ok(Session {
+            token,
+            wallet: wallet.to_string(),
+            expires_at,
+        })
+    }
+
+    /// Wallet of a live session
+    pub async fn session_wallet(&self, token: &str) -> Result<Option<String>, AuthError> {
+        self.repo.session_wallet(&token_hash(token)).await
+    }
+}
+
+/// The wallet that signed the request, from `Authorization: Bearer <token>`
+#[derive(Debug, Clone)]
+pub struct AuthSession {
+    pub wallet: String,
+}
+
+impl AuthSession {
+    /// Reject the request unless it acts for the signed-in wallet
+    pub fn require_wallet(&self, wallet: &str) -> Result<(), AuthError> {
+        if self.wallet != wallet {
+            return Err(AuthError::WrongWallet {
+                signer: self.wallet.clone(),
+                wallet: wallet.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl FromRequestParts<AppState> for AuthSession {
+    type Rejection = ApiErrorResponse;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(AuthError::MissingToken)?;
+
+        match state.auth.session_wallet(token.trim()).await? {
+            Some(wallet) => Ok(Self { wallet }),
+            None => Err(AuthError::InvalidSession.into()),
+        }
+    }
+}
+
+/// Message a wallet signs to sign in
+fn sign_in_message(
+    wallet: &str,
+    nonce: &str,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> String {
+    format!(
+        "Shadow Drop wants you to sign in with your Solana account:\n{}\n\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+        wallet,
+        nonce,
+        issued_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+/// A base58 Solana address as an ed25519 public key
+fn parse_wallet(wallet: &str) -> Result<VerifyingKey, AuthError> {
+    let bytes: [u8; 32] = bs58::decode(wallet)
+        .into_vec()
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or(AuthError::InvalidWallet)?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| AuthError::InvalidWallet)
+}
+
+/// Verify a base58 ed25519 signature of `message` by `wallet`
+pub fn verify_signature(wallet: &str, message: &[u8], signature: &str) -> Result<(), AuthError> {
+    let key = parse_wallet(wallet)?;
+    let bytes: [u8; 64] = bs58::decode(signature)
+        .into_vec()
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or(AuthError::InvalidSignature)?;
+
+    key.verify_strict(message, &Signature::from_bytes(&bytes))
+        .map_err(|_| AuthError::InvalidSignature)
+}
+
+fn random_hex<const N: usize>() -> String {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn keypair() -> (SigningKey, String) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let wallet = bs58::encode(key.verifying_key().as_bytes()).into_string();
+        (key, wallet)
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let (key, wallet) = keypair();
+        let message = sign_in_message(&wallet, "abc", Utc::now(), Utc::now());
+        let signature = bs58::encode(key.sign(message.as_bytes()).to_bytes()).into_string();
+
+        assert!(verify_signature(&wallet, message.as_bytes(), &signature).is_ok());
+        assert!(matches!(
+            verify_signature(&wallet, b"something else", &signature),
+            Err(AuthError::InvalidSignature)
+        ));
+
+        // Signed by another wallet
+        let other = SigningKey::from_bytes(&[8u8; 32]);
+        let forged = bs58::encode(other.sign(message.as_bytes()).to_bytes()).into_string();
+        assert!(matches!(
+            verify_signature(&wallet, message.as_bytes(), &forged),
+            Err(AuthError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let (_, wallet) = keypair();
+        assert!(matches!(
+            verify_signature("not-base58!", b"m", "sig"),
+            Err(AuthError::InvalidWallet)
+        ));
+        assert!(matches!(
+            verify_signature(&bs58::encode([1u8; 31]).into_string(), b"m", "sig"),
+            Err(AuthError::InvalidWallet)
+        ));
+        assert!(matches!(
+            verify_signature(&wallet, b"m", &bs58::encode([1u8; 63]).into_string()),
+            Err(AuthError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_session_must_match_wallet() {
+        let session = AuthSession {
+            wallet: "alice".to_string(),
+        };
+        assert!(session.require_wallet("alice").is_ok());
+
+        let err = session.require_wallet("bob").unwrap_err();
+        assert_eq!(ApiErrorResponse::from(err).errors.code, 403);
+    }
+}
@@ -0,0 +1,397 @@
+//! The program's `Campaign` account
+//!
+//! Borsh layout after the 8-byte Anchor discriminator, as declared in
+//! `contracts/programs/contracts/src/lib.rs`. Only layout
+//! `wire::CAMPAIGN_VERSION` is read.
+
+use sha2::{Digest, Sha256};
+use shadow_drop_wire::{self as wire, vesting::VestingSchedule};
+
+use super::{ChainError, ChainRpc};
+use crate::common::merkle::Hash;
+
+/// A decoded `Campaign` account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChainCampaign {
+    pub authority: [u8; 32],
+    pub merkle_root: Hash,
+    /// Depth of the `merkle_root` tree, which picks the ZK verifier
+    pub tree_depth: u8,
+    /// Root of the allocation tree `claim` and `claim_token` check against
+    pub claim_root: Hash,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub total_claims: u64,
+    pub is_active: bool,
+    pub bump: u8,
+    pub vault_bump: u8,
+    pub campaign_id: String,
+    pub vesting_start: i64,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub vesting_schedule: VestingSchedule,
+    pub token_mint: Option<[u8; 32]>,
+    pub token_vault: Option<[u8; 32]>,
+}
+
+/// Size of the first deployment's unversioned accounts
+const LEGACY_ACCOUNT_SIZE: usize = 222;
+
+/// Anchor account discriminator: `sha256("account:Campaign")[..8]`
+fn discriminator() -> [u8; 8] {
+    let hash = Sha256::digest(b"account:Campaign");
+    hash[..8].try_into().unwrap()
+}
+
+impl OnChainCampaign {
+    /// Decode account data
+    pub fn decode(data: &[u8]) -> Result<Self, ChainError> {
+        let mut r = Reader(data);
+        if r.array::<8>()? != discriminator() {
+            return Err(ChainError::InvalidAccount(
+                "not a Campaign account".to_string(),
+            ));
+        }
+
+        if data.len() == LEGACY_ACCOUNT_SIZE {
+            return Err(ChainError::InvalidAccount(
+                "campaign predates versioned accounts and must be recreated".to_string(),
+            ));
+        }
+        let version = r.u8()?;
+        if version != wire::CAMPAIGN_VERSION {
+            return Err(ChainError::InvalidAccount(format!(
+                "unsupported campaign layout version {}",
+                version
+            )));
+        }
+
+        let authority = r.array()?;
+        let merkle_root = r.array()?;
+        let tree_depth = r.u8()?;
+        let claim_root = r.array()?;
+        let total_amount = r.u64()?;
+        let claimed_amount = r.u64()?;
+        let total_claims = r.u64()?;
+        let is_active = r.bool()?;
+        let bump = r.u8()?;
+        let vault_bump = r.u8()?;
+        let id_bytes: [u8; 32] = r.array()?;
+        let id_len = (r.u8()? as usize).min(id_bytes.len());
+        let campaign_id = String::from_utf8_lossy(&id_bytes[..id_len]).into_owned();
+
+        Ok(Self {
+            authority,
+            merkle_root,
+            tree_depth,
+            claim_root,
+            total_amount,
+            claimed_amount,
+            total_claims,
+            is_active,
+            bump,
+            vault_bump,
+            campaign_id,
+            vesting_start: r.u64()? as i64,
+            vesting_cliff: r.u64()? as i64,
+            vesting_duration: r.u64()? as i64,
+            vesting_schedule: r.schedule()?,
+            token_mint: r.option()?,
+            token_vault: r.option()?,
+        })
+    }
+
+    /// Encode as account data (the inverse of `decode`), e.g. to seed an
+    /// `InMemoryRpc`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut id = [0u8; 32];
+        let id_len = self.campaign_id.len().min(32);
+        id[..id_len].copy_from_slice(&self.campaign_id.as_bytes()[..id_len]);
+
+        let mut out = discriminator().to_vec();
+        out.push(wire::CAMPAIGN_VERSION);
+        out.extend_from_slice(&self.authority);
+        out.extend_from_slice(&self.merkle_root);
+        out.push(self.tree_depth);
+        out.extend_from_slice(&self.claim_root);
+        out.extend_from_slice(&self.total_amount.to_le_bytes());
+        out.extend_from_slice(&self.claimed_amount.to_le_bytes());
+        out.extend_from_slice(&self.total_claims.to_le_bytes());
+        out.extend_from_slice(&[self.is_active as u8, self.bump, self.vault_bump]);
+        out.extend_from_slice(&id);
+        out.push(id_len as u8);
+        out.extend_from_slice(&self.vesting_start.to_le_bytes());
+        out.extend_from_slice(&self.vesting_cliff.to_le_bytes());
+        out.extend_from_slice(&self.vesting_duration.to_le_bytes());
+        out.extend_from_slice(&self.vesting_schedule.to_bytes());
+        for key in [&self.token_mint, &self.token_vault] {
+            match key {
+                Some(key) => {
+                    out.push(1);
+                    out.extend_from_slice(key);
+                }
+                None => out.push(0),
+            }
+        }
+        out
+    }
+}
+
+/// Fetch and decode the campaign at `address`, checking it belongs to
+/// `program_id`
+pub async fn fetch_campaign(
+    rpc: &dyn ChainRpc,
+    program_id: &str,
+    address: &str,
+) -> Result<OnChainCampaign, ChainError> {
+    let account = rpc
+        .get_account(address)
+        .await?
+        .ok_or_else(|| ChainError::AccountNotFound(address.to_string()))?;
+
+    if account.owner != program_id {
+        return Err(ChainError::WrongOwner {
+            address: address.to_string(),
+            owner: account.owner,
+        });
+    }
+    OnChainCampaign::decode(&account.data)
+}
+
+/// What a campaign submission says was registered on chain
+#[derive(Debug, Clone)]
+pub struct Registration<'a> {
+    /// Creator wallet (base58)
+    pub authority: &'a str,
+    pub merkle_root: &'a Hash,
+    pub tree_depth: u8,
+    /// Root of the recipients' allocation tree (`MerkleScheme::Sha256`)
+    pub claim_root: &'a Hash,
+    /// Total in base units (lamports or raw token amount)
+    pub total_amount: u64,
+    /// Token mint (base58), `None` for SOL campaigns
+    pub token_mint: Option<&'a str>,
+}
+
+impl Registration<'_> {
+    /// Reject the submission unless it matches `onchain`
+    pub fn check(&self, onchain: &OnChainCampaign) -> Result<(), ChainError> {
+        let mut mismatches = Vec::new();
+
+        let authority = bs58::encode(onchain.authority).into_string();
+        if self.authority != authority {
+            mismatches.push(format!(
+                "authority is {}, not {}",
+                authority, self.authority
+            ));
+        }
+        if *self.merkle_root != onchain.merkle_root {
+            mismatches.push(format!(
+                "merkle_root is {}, not {}",
+                hex::encode(onchain.merkle_root),
+                hex::encode(self.merkle_root)
+            ));
+        }
+        if self.tree_depth != onchain.tree_depth {
+            mismatches.push(format!(
+                "tree_depth is {}, not {}",
+                onchain.tree_depth, self.tree_depth
+            ));
+        }
+        if *self.claim_root != onchain.claim_root {
+            mismatches.push(format!(
+                "claim_root is {}, not {}",
+                hex::encode(onchain.claim_root),
+                hex::encode(self.claim_root)
+            ));
+        }
+        if self.total_amount != onchain.total_amount {
+            mismatches.push(format!(
+                "total_amount is {}, not {}",
+                onchain.total_amount, self.total_amount
+            ));
+        }
+        let token_mint = onchain.token_mint.map(|m| bs58::encode(m).into_string());
+        if self.token_mint != token_mint.as_deref() {
+            mismatches.push(format!(
+                "token_mint is {}, not {}",
+                token_mint.as_deref().unwrap_or("none (SOL)"),
+                self.token_mint.unwrap_or("none (SOL)")
+            ));
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ChainError::Mismatch(mismatches))
+        }
+    }
+}
+
+/// Cursor over Borsh data
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ChainError> {
+        if self.0.len() < N {
+            return Err(ChainError::InvalidAccount("account data too short".to_string()));
+        }
+        let (head, tail) = self.0.split_at(N);
+        self.0 = tail;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, ChainError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, ChainError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn bool(&mut self) -> Result<bool, ChainError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(ChainError::InvalidAccount(format!("invalid bool {}", b))),
+        }
+    }
+
+    fn schedule(&mut self) -> Result<VestingSchedule, ChainError> {
+        VestingSchedule::from_bytes(&self.array()?)
+            .map_err(|e| ChainError::InvalidAccount(e.to_string()))
+    }
+
+    fn option(&mut self) -> Result<Option<[u8; 32]>, ChainError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.array()?)),
+            b => Err(ChainError::InvalidAccount(format!("invalid option tag {}", b))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::chain::{Account, InMemoryRpc};
+
+    pub const PROGRAM_ID: &str = "7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v";
+
+    pub fn sample_campaign() -> OnChainCampaign {
+        OnChainCampaign {
+            authority: [1u8; 32],
+            merkle_root: [2u8; 32],
+            tree_depth: 8,
+            claim_root: [5u8; 32],
+            total_amount: 5_000_000_000,
+            claimed_amount: 0,
+            total_claims: 0,
+            is_active: true,
+            bump: 254,
+            vault_bump: 253,
+            campaign_id: "abc123".to_string(),
+            vesting_start: 1_700_000_000,
+            vesting_cliff: 0,
+            vesting_duration: 0,
+            vesting_schedule: VestingSchedule::Linear,
+            token_mint: None,
+            token_vault: None,
+        }
+    }
+
+    fn registration(campaign: &OnChainCampaign) -> (String, Hash) {
+        (bs58::encode(campaign.authority).into_string(), campaign.merkle_root)
+    }
+
+    #[test]
+    fn test_campaign_account_roundtrip() {
+        let mut campaign = sample_campaign();
+        assert_eq!(OnChainCampaign::decode(&campaign.encode()).unwrap(), campaign);
+
+        campaign.token_mint = Some([3u8; 32]);
+        campaign.token_vault = Some([4u8; 32]);
+        campaign.vesting_schedule = VestingSchedule::Step { period: 86_400 };
+        let data = campaign.encode();
+        // 8 + 1 + 32 + 2*32 + 1 + 3*8 + 3 + 32 + 1 + 3*8 + 82 + 2*33, the program's account size
+        assert_eq!(data.len(), 338);
+        assert_eq!(OnChainCampaign::decode(&data).unwrap(), campaign);
+
+        assert!(OnChainCampaign::decode(&data[..100]).is_err());
+        let mut other = data.clone();
+        other[0] ^= 1;
+        assert!(OnChainCampaign::decode(&other).is_err());
+
+        // Other layout versions
+        let mut other = data.clone();
+        other[8] = wire::CAMPAIGN_VERSION + 1;
+        assert!(OnChainCampaign::decode(&other).is_err());
+        assert!(OnChainCampaign::decode(&data[..LEGACY_ACCOUNT_SIZE]).is_err());
+    }
+
+    #[test]
+    fn test_registration_must_match() {
+        let onchain = sample_campaign();
+        let (authority, root) = registration(&onchain);
+        let submitted = Registration {
+            authority: &authority,
+            merkle_root: &root,
+            tree_depth: onchain.tree_depth,
+            claim_root: &onchain.claim_root,
+            total_amount: onchain.total_amount,
+            token_mint: None,
+        };
+        assert!(submitted.check(&onchain).is_ok());
+
+        let mint = bs58::encode([3u8; 32]).into_string();
+        let wrong = Registration {
+            tree_depth: 4,
+            claim_root: &root,
+            total_amount: 1,
+            token_mint: Some(&mint),
+            ..submitted
+        };
+        match wrong.check(&onchain) {
+            Err(ChainError::Mismatch(fields)) => {
+                assert_eq!(fields.len(), 4);
+                assert!(fields[0].starts_with("tree_depth"));
+                assert!(fields[1].starts_with("claim_root"));
+                assert!(fields[2].starts_with("total_amount"));
+                assert!(fields[3].starts_with("token_mint"));
+            }
+            other => panic!("expected mismatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_campaign_checks_owner() {
+        let rpc = InMemoryRpc::new();
+        assert!(matches!(
+            fetch_campaign(&rpc, PROGRAM_ID, "campaign").await,
+            Err(ChainError::AccountNotFound(_))
+        ));
+
+        let account = Account {
+            owner: "11111111111111111111111111111111".to_string(),
+            lamports: 1,
+            data: sample_campaign().encode(),
+        };
+        rpc.set_account("campaign", account.clone());
+        assert!(matches!(
+            fetch_campaign(&rpc, PROGRAM_ID, "campaign").await,
+            Err(ChainError::WrongOwner { .. })
+        ));
+
+        rpc.set_account(
+            "campaign",
+            Account {
+                owner: PROGRAM_ID.to_string(),
+                ..account
+            },
+        );
+        assert_eq!(
+            fetch_campaign(&rpc, PROGRAM_ID, "campaign").await.unwrap(),
+            sample_campaign()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Claims paid out by the program
+//!
+//! A claim instruction is recognised by its Anchor discriminator. The
+//! claimer and campaign are its first two accounts and the vault it pays
+//! from is the third, for every claim instruction; the amount is what the
+//! instruction actually moved out of that vault through its inner System or
+//! Token transfers (for vested claims this can be less than requested).
+//!
+//! `close_campaign` is decoded here too: it ends a campaign's claims, and
+//! its campaign is the second account.
+
+use sha2::{Digest, Sha256};
+
+use super::{Instruction, Transaction};
+
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+/// Program instructions that pay out a claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimKind {
+    Claim,
+    ClaimToken,
+    /// Removed from the program (it paid without a proof); still decoded so
+    /// the claims it made before then are indexed
+    ClaimCompressed,
+    ClaimZkVerified,
+    ClaimZkSimple,
+    ClaimZkToken,
+}
+
+impl ClaimKind {
+    const ALL: [Self; 6] = [
+        Self::Claim,
+        Self::ClaimToken,
+        Self::ClaimCompressed,
+        Self::ClaimZkVerified,
+        Self::ClaimZkSimple,
+        Self::ClaimZkToken,
+    ];
+
+    /// Instruction name in the program
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Claim => "claim",
+            Self::ClaimToken => "claim_token",
+            Self::ClaimCompressed => "claim_compressed",
+            Self::ClaimZkVerified => "claim_zk_verified",
+            Self::ClaimZkSimple => "claim_zk_simple",
+            Self::ClaimZkToken => "claim_zk_token",
+        }
+    }
+
+    /// Anchor instruction discriminator: `sha256("global:<name>")[..8]`
+    pub fn discriminator(&self) -> [u8; 8] {
+        let hash = Sha256::digest(format!("global:{}", self.as_str()));
+        hash[..8].try_into().unwrap()
+    }
+
+    fn from_data(data: &[u8]) -> Option<Self> {
+        let discriminator = data.get(..8)?;
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.discriminator() == discriminator)
+    }
+
+    /// The ZK instructions end with `nullifier: [u8; 32], claim_amount: u64`
+    fn nullifier(&self, data: &[u8]) -> Option<[u8; 32]> {
+        match self {
+            Self::Claim | Self::ClaimToken => None,
+            _ => {
+                let end = data.len().checked_sub(8)?;
+                data.get(end.checked_sub(32)?..end)?.try_into().ok()
+            }
+        }
+    }
+}
+
+/// A claim paid out by a successful transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainClaim {
+    /// Position of the claim instruction in the transaction
+    pub instruction_index: u32,
+    pub kind: ClaimKind,
+    /// Campaign account (base58)
+    pub campaign: String,
+    /// Claimer wallet (base58)
+    pub claimer: String,
+    /// Paid amount in base units (lamports or raw token amount)
+    pub amount: u64,
+    /// Nullifier of ZK claims
+    pub nullifier: Option<[u8; 32]>,
+}
+
+/// The claims `program_id` paid out in `tx`; empty for failed transactions
+pub fn decode_claims(tx: &Transaction, program_id: &str) -> Vec<ChainClaim> {
+    if tx.failed {
+        return Vec::new();
+    }
+
+    tx.instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, ix)| ix.program_id == program_id)
+        .filter_map(|(index, ix)| {
+            let kind = ClaimKind::from_data(&ix.data)?;
+            let [claimer, campaign, vault, ..] = ix.accounts.as_slice() else {
+                return None;
+            };
+            let amount = ix.inner.iter().filter_map(|inner| paid_from(inner, vault)).sum();
+
+            Some(ChainClaim {
+                instruction_index: index as u32,
+                kind,
+                campaign: campaign.clone(),
+                claimer: claimer.clone(),
+                amount,
+                nullifier: kind.nullifier(&ix.data),
+            })
+        })
+        .collect()
+}
+
+/// Campaigns `program_id` closed in `tx`; empty for failed transactions
+pub fn decode_closed_campaigns(tx: &Transaction, program_id: &str) -> Vec<String> {
+    if tx.failed {
+        return Vec::new();
+    }
+
+    let discriminator = Sha256::digest("global:close_campaign");
+    tx.instructions
+        .iter()
+        .filter(|ix| ix.program_id == program_id && ix.data.get(..8) == Some(&discriminator[..8]))
+        .filter_map(|ix| ix.accounts.get(1).cloned())
+        .collect()
+}
+
+/// Amount of a System or Token transfer out of `source`
+fn paid_from(ix: &Instruction, source: &str) -> Option<u64> {
+    if ix.accounts.first().map(String::as_str) != Some(source) {
+        return None;
+    }
+    let amount = |bytes: Option<&[u8]>| Some(u64::from_le_bytes(bytes?.try_into().ok()?));
+
+    if ix.program_id == SYSTEM_PROGRAM_ID {
+        // SystemInstruction::Transfer { lamports }: u32 tag 2, u64
+        if ix.data.get(..4)? != 2u32.to_le_bytes() {
+            return None;
+        }
+        amount(ix.data.get(4..12))
+    } else if ix.program_id == TOKEN_PROGRAM_ID {
+        // TokenInstruction::Transfer (3) or TransferChecked (12): u8 tag, u64
+        match ix.data.first() {
+            Some(3 | 12) => amount(ix.data.get(1..9)),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::transaction::tests::{CLAIM_TOKEN, CLAIM_ZK_SIMPLE, FAILED_CLAIM};
+    use crate::chain::campaign::tests::PROGRAM_ID;
+
+    #[test]
+    fn test_decode_zk_claim() {
+        let tx = Transaction::from_rpc_json(CLAIM_ZK_SIMPLE).unwrap();
+        let claims = decode_claims(&tx, PROGRAM_ID);
+
+        assert_eq!(claims.len(), 1);
+        let claim = &claims[0];
+        assert_eq!(claim.instruction_index, 1);
+        assert_eq!(claim.kind, ClaimKind::ClaimZkSimple);
+        assert_eq!(claim.claimer, tx.instructions[1].accounts[0]);
+        assert_eq!(claim.campaign, tx.instructions[1].accounts[1]);
+        assert_eq!(claim.amount, 1_500_000_000);
+        assert_eq!(claim.nullifier, Some([7u8; 32]));
+
+        assert!(decode_claims(&tx, "11111111111111111111111111111111").is_empty());
+    }
+
+    #[test]
+    fn test_vested_claim_amount_is_what_was_paid() {
+        let tx = Transaction::from_rpc_json(CLAIM_TOKEN).unwrap();
+        let claims = decode_claims(&tx, PROGRAM_ID);
+
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].kind, ClaimKind::ClaimToken);
+        // Requested 1_000_000, only a quarter had vested
+        assert_eq!(claims[0].amount, 250_000);
+        assert_eq!(claims[0].nullifier, None);
+    }
+
+    #[test]
+    fn test_decode_closed_campaigns() {
+        let mut tx = Transaction::from_rpc_json(CLAIM_ZK_SIMPLE).unwrap();
+        assert!(decode_closed_campaigns(&tx, PROGRAM_ID).is_empty());
+
+        let close = Instruction {
+            program_id: PROGRAM_ID.to_string(),
+            accounts: vec!["Authority".into(), "Campaign".into(), "Vault".into()],
+            data: Sha256::digest("global:close_campaign")[..8].to_vec(),
+            inner: Vec::new(),
+        };
+        tx.instructions.push(close);
+        assert_eq!(decode_closed_campaigns(&tx, PROGRAM_ID), ["Campaign"]);
+        assert_eq!(decode_claims(&tx, PROGRAM_ID).len(), 1);
+
+        tx.failed = true;
+        assert!(decode_closed_campaigns(&tx, PROGRAM_ID).is_empty());
+    }
+
+    #[test]
+    fn test_failed_transactions_pay_nothing() {
+        let tx = Transaction::from_rpc_json(FAILED_CLAIM).unwrap();
+        assert!(tx.failed);
+        assert!(decode_claims(&tx, PROGRAM_ID).is_empty());
+    }
+}
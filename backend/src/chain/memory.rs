@@ -0,0 +1,79 @@
+//! In-memory chain state for tests and offline development
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use async_trait::async_trait;
+
+use super::{Account, ChainError, ChainRpc, SignatureInfo, Transaction};
+
+/// `ChainRpc` serving accounts and transactions inserted by the caller
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRpc {
+    accounts: Arc<RwLock<HashMap<String, Account>>>,
+    /// In the order they were added, oldest first
+    transactions: Arc<RwLock<Vec<Transaction>>>,
+}
+
+impl InMemoryRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create or replace an account
+    pub fn set_account(&self, address: &str, account: Account) {
+        self.accounts
+            .write()
+            .unwrap()
+            .insert(address.to_string(), account);
+    }
+
+    /// Remove an account
+    pub fn remove_account(&self, address: &str) {
+        self.accounts.write().unwrap().remove(address);
+    }
+
+    /// Append a transaction, e.g. one parsed from a recorded
+    /// `getTransaction` response
+    pub fn add_transaction(&self, tx: Transaction) {
+        self.transactions.write().unwrap().push(tx);
+    }
+}
+
+#[async_trait]
+impl ChainRpc for InMemoryRpc {
+    async fn get_account(&self, address: &str) -> Result<Option<Account>, ChainError> {
+        Ok(self.accounts.read().unwrap().get(address).cloned())
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>, ChainError> {
+        let transactions = self.transactions.read().unwrap();
+        let newest_first = transactions.iter().rev().filter(|tx| tx.mentions(address));
+
+        Ok(newest_first
+            .skip_while(|tx| before.is_some_and(|b| b != tx.signature))
+            .skip(before.is_some() as usize)
+            .take_while(|tx| until != Some(tx.signature.as_str()))
+            .take(limit)
+            .map(Transaction::signature_info)
+            .collect())
+    }
+
+    async fn get_transaction(&self, signature: &str) -> Result<Option<Transaction>, ChainError> {
+        Ok(self
+            .transactions
+            .read()
+            .unwrap()
+            .iter()
+            .find(|tx| tx.signature == signature)
+            .cloned())
+    }
+}
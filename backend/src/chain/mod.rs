@@ -0,0 +1,90 @@
+//! Solana chain access
+//!
+//! Everything the API reads from the chain goes through the [`ChainRpc`]
+//! trait: [`SolanaRpc`] talks JSON-RPC to a validator, [`InMemoryRpc`]
+//! serves accounts and transactions from memory for tests and offline
+//! development.
+
+mod campaign;
+mod claims;
+mod memory;
+mod rpc;
+mod transaction;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+
+use crate::common::response::ApiErrorResponse;
+
+pub use campaign::{OnChainCampaign, Registration, fetch_campaign};
+pub use claims::{ChainClaim, ClaimKind, decode_claims, decode_closed_campaigns};
+pub use memory::InMemoryRpc;
+pub use rpc::SolanaRpc;
+pub use transaction::{Instruction, SignatureInfo, Transaction};
+
+/// An account as returned by `getAccountInfo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    /// Owning program (base58)
+    pub owner: String,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+/// Read access to chain state
+#[async_trait]
+pub trait ChainRpc: Send + Sync {
+    /// Fetch an account at the configured commitment; `None` if it does not
+    /// exist (or is not yet confirmed)
+    async fn get_account(&self, address: &str) -> Result<Option<Account>, ChainError>;
+
+    /// Signatures of transactions that loaded `address`, newest first:
+    /// at most `limit` of them, older than `before` and newer than `until`
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>, ChainError>;
+
+    /// Fetch a transaction; `None` if it is not (yet) available at the
+    /// configured commitment
+    async fn get_transaction(&self, signature: &str) -> Result<Option<Transaction>, ChainError>;
+}
+
+/// Chain access errors
+#[derive(Debug, thiserror::Error)]
+pub enum ChainError {
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("Account {0} not found on chain (not created or not yet confirmed)")]
+    AccountNotFound(String),
+
+    #[error("Account {address} is owned by {owner}, not the Shadow Drop program")]
+    WrongOwner { address: String, owner: String },
+
+    #[error("Invalid campaign account: {0}")]
+    InvalidAccount(String),
+
+    #[error("Campaign does not match the on-chain account: {}", .0.join(", "))]
+    Mismatch(Vec<String>),
+}
+
+impl From<ChainError> for ApiErrorResponse {
+    fn from(e: ChainError) -> Self {
+        match e {
+            ChainError::Rpc(ref err) => {
+                tracing::error!("Solana RPC error: {}", err);
+                ApiErrorResponse::default()
+                    .with_code(StatusCode::BAD_GATEWAY)
+                    .with_message("Could not reach the Solana RPC node")
+            }
+            e => ApiErrorResponse::default()
+                .with_code(StatusCode::BAD_REQUEST)
+                .with_message("Request does not match chain state")
+                .with_details(e.to_string()),
+        }
+    }
+}
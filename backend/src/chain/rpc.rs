@@ -0,0 +1,159 @@
+//! JSON-RPC client for a Solana node
+
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{Account, ChainError, ChainRpc, SignatureInfo, Transaction, transaction::RpcTransaction};
+
+/// `ChainRpc` over a node's HTTP JSON-RPC endpoint
+#[derive(Debug, Clone)]
+pub struct SolanaRpc {
+    client: reqwest::Client,
+    url: String,
+    commitment: String,
+}
+
+impl SolanaRpc {
+    /// Client for `url`, reading at `commitment` (`confirmed`, `finalized`)
+    pub fn new(url: impl Into<String>, commitment: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            commitment: commitment.into(),
+        }
+    }
+
+    /// Send a JSON-RPC request and return its `result`
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, ChainError> {
+        let response: RpcResponse = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ChainError::Rpc(format!("{} failed: {}", method, e)))?
+            .json()
+            .await
+            .map_err(|e| ChainError::Rpc(format!("Invalid {} response: {}", method, e)))?;
+
+        if let Some(error) = response.error {
+            return Err(ChainError::Rpc(format!(
+                "{} failed: {} ({})",
+                method, error.message, error.code
+            )));
+        }
+        // A `null` result is valid for methods returning an `Option`
+        serde_json::from_value(response.result)
+            .map_err(|e| ChainError::Rpc(format!("Invalid {} response: {}", method, e)))
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: serde_json::Value,
+    error: Option<RpcErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+/// `getAccountInfo` result: `{ context, value }`
+#[derive(Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct AccountInfo {
+    owner: String,
+    lamports: u64,
+    /// `[data, encoding]`
+    data: (String, String),
+}
+
+/// `getSignaturesForAddress` entry
+#[derive(Deserialize)]
+struct RpcSignature {
+    signature: String,
+    slot: u64,
+    err: Option<serde_json::Value>,
+}
+
+#[async_trait]
+impl ChainRpc for SolanaRpc {
+    async fn get_account(&self, address: &str) -> Result<Option<Account>, ChainError> {
+        let info: WithContext<Option<AccountInfo>> = self
+            .call(
+                "getAccountInfo",
+                json!([address, { "encoding": "base64", "commitment": self.commitment }]),
+            )
+            .await?;
+
+        info.value
+            .map(|info| {
+                let data = STANDARD
+                    .decode(&info.data.0)
+                    .map_err(|e| ChainError::Rpc(format!("Invalid account data: {}", e)))?;
+                Ok(Account {
+                    owner: info.owner,
+                    lamports: info.lamports,
+                    data,
+                })
+            })
+            .transpose()
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>, ChainError> {
+        let signatures: Vec<RpcSignature> = self
+            .call(
+                "getSignaturesForAddress",
+                json!([address, {
+                    "before": before,
+                    "until": until,
+                    "limit": limit,
+                    "commitment": self.commitment,
+                }]),
+            )
+            .await?;
+
+        Ok(signatures
+            .into_iter()
+            .map(|s| SignatureInfo {
+                signature: s.signature,
+                slot: s.slot,
+                failed: s.err.is_some(),
+            })
+            .collect())
+    }
+
+    async fn get_transaction(&self, signature: &str) -> Result<Option<Transaction>, ChainError> {
+        let tx: Option<RpcTransaction> = self
+            .call(
+                "getTransaction",
+                json!([signature, {
+                    "encoding": "json",
+                    "commitment": self.commitment,
+                    "maxSupportedTransactionVersion": 0,
+                }]),
+            )
+            .await?;
+        tx.map(Transaction::try_from).transpose()
+    }
+}
@@ -0,0 +1,228 @@
+//! Confirmed transactions
+//!
+//! Parsed from the `json` encoding of `getTransaction`, with account
+//! indexes resolved to addresses (including those loaded from lookup
+//! tables) and each instruction carrying the inner instructions it invoked.
+
+use serde::Deserialize;
+
+use super::ChainError;
+
+/// An entry of `getSignaturesForAddress`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    /// The transaction failed (its instructions had no effect)
+    pub failed: bool,
+}
+
+/// An instruction with its accounts resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Invoked program (base58)
+    pub program_id: String,
+    /// Account addresses (base58), in instruction order
+    pub accounts: Vec<String>,
+    pub data: Vec<u8>,
+    /// Instructions invoked through CPI (empty for inner instructions)
+    pub inner: Vec<Instruction>,
+}
+
+/// A confirmed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub signature: String,
+    pub slot: u64,
+    /// Unix timestamp, if the node has it
+    pub block_time: Option<i64>,
+    pub failed: bool,
+    /// Top-level instructions
+    pub instructions: Vec<Instruction>,
+}
+
+impl Transaction {
+    /// Parse a `getTransaction` result (`json` encoding)
+    pub fn from_rpc_json(json: &str) -> Result<Self, ChainError> {
+        let tx: RpcTransaction = serde_json::from_str(json)
+            .map_err(|e| ChainError::Rpc(format!("Invalid transaction: {}", e)))?;
+        tx.try_into()
+    }
+
+    /// Whether the transaction loads `address`
+    pub fn mentions(&self, address: &str) -> bool {
+        self.instructions.iter().any(|ix| {
+            ix.program_id == address || ix.accounts.iter().any(|a| a == address)
+        })
+    }
+
+    pub fn signature_info(&self) -> SignatureInfo {
+        SignatureInfo {
+            signature: self.signature.clone(),
+            slot: self.slot,
+            failed: self.failed,
+        }
+    }
+}
+
+/// `getTransaction` result
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct RpcTransaction {
+    slot: u64,
+    block_time: Option<i64>,
+    meta: Option<RpcMeta>,
+    transaction: RpcTransactionBody,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcMeta {
+    err: Option<serde_json::Value>,
+    #[serde(default)]
+    inner_instructions: Option<Vec<RpcInnerInstructions>>,
+    #[serde(default)]
+    loaded_addresses: Option<RpcLoadedAddresses>,
+}
+
+#[derive(Deserialize)]
+struct RpcInnerInstructions {
+    /// Index of the top-level instruction
+    index: usize,
+    instructions: Vec<RpcInstruction>,
+}
+
+#[derive(Default, Deserialize)]
+struct RpcLoadedAddresses {
+    writable: Vec<String>,
+    readonly: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RpcTransactionBody {
+    signatures: Vec<String>,
+    message: RpcMessage,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcMessage {
+    account_keys: Vec<String>,
+    instructions: Vec<RpcInstruction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcInstruction {
+    program_id_index: usize,
+    accounts: Vec<usize>,
+    /// base58
+    data: String,
+}
+
+impl RpcInstruction {
+    fn resolve(&self, keys: &[String]) -> Result<Instruction, ChainError> {
+        let key = |i: usize| {
+            keys.get(i)
+                .cloned()
+                .ok_or_else(|| ChainError::Rpc(format!("Invalid transaction: no account {}", i)))
+        };
+        Ok(Instruction {
+            program_id: key(self.program_id_index)?,
+            accounts: self.accounts.iter().map(|&i| key(i)).collect::<Result<_, _>>()?,
+            data: bs58::decode(&self.data)
+                .into_vec()
+                .map_err(|e| ChainError::Rpc(format!("Invalid instruction data: {}", e)))?,
+            inner: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<RpcTransaction> for Transaction {
+    type Error = ChainError;
+
+    fn try_from(tx: RpcTransaction) -> Result<Self, ChainError> {
+        let signature = tx
+            .transaction
+            .signatures
+            .into_iter()
+            .next()
+            .ok_or_else(|| ChainError::Rpc("Invalid transaction: no signature".to_string()))?;
+        let meta = tx.meta;
+        let failed = meta.as_ref().is_some_and(|m| m.err.is_some());
+
+        // Static keys, then lookup table writable and readonly addresses
+        let mut keys = tx.transaction.message.account_keys;
+        let loaded = meta
+            .as_ref()
+            .and_then(|m| m.loaded_addresses.as_ref());
+        if let Some(loaded) = loaded {
+            keys.extend(loaded.writable.iter().cloned());
+            keys.extend(loaded.readonly.iter().cloned());
+        }
+
+        let mut instructions = tx
+            .transaction
+            .message
+            .instructions
+            .iter()
+            .map(|ix| ix.resolve(&keys))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let inner = meta.and_then(|m| m.inner_instructions).unwrap_or_default();
+        for group in inner {
+            let parent = instructions.get_mut(group.index).ok_or_else(|| {
+                ChainError::Rpc(format!("Invalid transaction: no instruction {}", group.index))
+            })?;
+            for ix in &group.instructions {
+                parent.inner.push(ix.resolve(&keys)?);
+            }
+        }
+
+        Ok(Self {
+            signature,
+            slot: tx.slot,
+            block_time: tx.block_time,
+            failed,
+            instructions,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub const CLAIM_ZK_SIMPLE: &str = include_str!("../../fixtures/chain/claim_zk_simple.json");
+    pub const CLAIM_TOKEN: &str = include_str!("../../fixtures/chain/claim_token.json");
+    pub const FAILED_CLAIM: &str = include_str!("../../fixtures/chain/failed_claim.json");
+
+    #[test]
+    fn test_parse_transaction_fixture() {
+        let tx = Transaction::from_rpc_json(CLAIM_ZK_SIMPLE).unwrap();
+        assert!(!tx.failed);
+        assert_eq!(tx.instructions.len(), 2);
+
+        let claim = &tx.instructions[1];
+        assert_eq!(claim.accounts.len(), 6);
+        assert_eq!(claim.inner.len(), 2);
+        assert_eq!(claim.inner[1].program_id, "11111111111111111111111111111111");
+        assert_eq!(claim.inner[1].accounts[0], claim.accounts[2]);
+        assert!(tx.mentions(&claim.program_id));
+
+        assert!(Transaction::from_rpc_json(FAILED_CLAIM).unwrap().failed);
+    }
+
+    #[test]
+    fn test_lookup_table_accounts_are_resolved() {
+        let tx = Transaction::from_rpc_json(CLAIM_TOKEN).unwrap();
+        let transfer = &tx.instructions[0].inner[0];
+        // The token program is loaded from a lookup table
+        assert_eq!(transfer.program_id, "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+        // Without the lookup table the program index points nowhere
+        let mut json: serde_json::Value = serde_json::from_str(CLAIM_TOKEN).unwrap();
+        json["meta"]["loadedAddresses"]["readonly"] = serde_json::json!([]);
+        assert!(Transaction::from_rpc_json(&json.to_string()).is_err());
+    }
+}
@@ -2,25 +2,96 @@
 //! 
 //! This module provides a proper merkle tree for ZK proofs.
 //! Uses a simplified Poseidon-like hash for demo (replace with light-poseidon for production).
+//!
+//! The same tree, hashed with SHA-256 ([`MerkleScheme::Sha256`]), holds the
+//! allocations the program checks `claim` and `claim_token` against.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::OnceLock};
 
-/// Tree depth (supports 2^8 = 256 recipients)
-pub const TREE_DEPTH: usize = 8;
+use sha2::{Digest, Sha256};
+use shadow_drop_wire::{self as wire, claim_tree};
 
-/// Maximum number of leaves
-pub const MAX_LEAVES: usize = 1 << TREE_DEPTH;
+/// Default tree depth (supports 2^8 = 256 recipients)
+pub const DEFAULT_TREE_DEPTH: usize = 8;
+
+/// Deepest tree the backend will build (2^32 leaves)
+/// Proving additionally needs a circuit compiled for the chosen depth.
+pub const MAX_TREE_DEPTH: usize = 32;
 
 /// A 32-byte hash value
 pub type Hash = [u8; 32];
 
+/// Maximum number of leaves for a tree of the given depth
+pub fn max_leaves(depth: usize) -> usize {
+    1 << depth
+}
+
+/// Hash of an all-empty Poseidon subtree at each level, up to `MAX_TREE_DEPTH`
+///
+/// `zero_hashes()[0]` is the empty leaf and `zero_hashes()[i + 1]` is
+/// `hash(z[i], z[i])`. Computed once per process and shared by every tree.
+pub fn zero_hashes() -> &'static [Hash; MAX_TREE_DEPTH + 1] {
+    MerkleScheme::Poseidon.zero_hashes()
+}
+
+/// How a tree hashes its leaves and nodes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MerkleScheme {
+    /// Poseidon2 over `(recipient, amount, secret)`, proven by the ZK claims
+    #[default]
+    Poseidon,
+    /// SHA-256 over `(claimer, amount)`, checked on chain by `claim` and
+    /// `claim_token` (see `shadow_drop_wire::claim_tree`)
+    Sha256,
+}
+
+impl MerkleScheme {
+    /// Leaf of a recipient; `Sha256` leaves don't commit to the secret
+    pub fn leaf(self, wallet: &str, amount: u64, secret: &[u8; 32]) -> Hash {
+        match self {
+            Self::Poseidon => compute_leaf_hash(wallet, amount, secret),
+            Self::Sha256 => compute_claim_leaf(wallet, amount),
+        }
+    }
+
+    /// Hash two nodes together
+    pub fn hash_pair(self, left: &Hash, right: &Hash) -> Hash {
+        match self {
+            Self::Poseidon => hash_pair(left, right),
+            Self::Sha256 => claim_tree::node(sha256v, left, right),
+        }
+    }
+
+    /// Hash of an all-empty subtree at each level (see `zero_hashes`)
+    pub fn zero_hashes(self) -> &'static [Hash; MAX_TREE_DEPTH + 1] {
+        static POSEIDON: OnceLock<[Hash; MAX_TREE_DEPTH + 1]> = OnceLock::new();
+        static SHA256: OnceLock<[Hash; MAX_TREE_DEPTH + 1]> = OnceLock::new();
+
+        let cell = match self {
+            Self::Poseidon => &POSEIDON,
+            Self::Sha256 => &SHA256,
+        };
+        cell.get_or_init(|| {
+            let mut zeros = [[0u8; 32]; MAX_TREE_DEPTH + 1];
+            for level in 0..MAX_TREE_DEPTH {
+                zeros[level + 1] = self.hash_pair(&zeros[level], &zeros[level]);
+            }
+            zeros
+        })
+    }
+}
+
 /// Merkle tree structure
+///
+/// Only the populated part of each level is stored. Everything to the right
+/// of it is an all-empty subtree whose hash comes from `zero_hashes()`, so
+/// building costs O(recipients + depth) hashes instead of O(2^depth).
 #[derive(Debug, Clone)]
 pub struct MerkleTree {
-    /// All nodes in the tree (bottom-up, left-to-right per level)
-    nodes: Vec<Hash>,
-    #[allow(dead_code)]
-    leaf_count: usize,
+    /// Populated nodes per level (`levels[0]` = leaves, last = root level)
+    levels: Vec<Vec<Hash>>,
+    depth: usize,
+    scheme: MerkleScheme,
     /// Leaf index by recipient wallet
     leaf_indices: HashMap<String, usize>,
 }
@@ -31,90 +102,221 @@ pub struct MerkleProof {
     pub leaf_index: usize,
     pub siblings: Vec<Hash>,
     pub leaf: Hash,
+    pub scheme: MerkleScheme,
 }
 
-impl MerkleTree {
-    /// Build a merkle tree from recipient list
-    pub fn from_recipients(recipients: &[(String, u64, [u8; 32])]) -> Self {
-        let leaf_count = recipients.len();
-        assert!(leaf_count <= MAX_LEAVES, "Too many recipients");
-        
-        // Compute leaves: hash(recipient, amount, secret)
-        let mut leaves: Vec<Hash> = recipients
-            .iter()
-            .map(|(wallet, amount, secret)| {
-                compute_leaf_hash(wallet, *amount, secret)
-            })
-            .collect();
-        
-        // Pad to power of 2
-        let padded_size = (1 << TREE_DEPTH) as usize;
-        while leaves.len() < padded_size {
-            leaves.push([0u8; 32]); // Empty leaf
-        }
-        
-        // Build leaf index map
-        let mut leaf_indices = HashMap::new();
-        for (i, (wallet, _, _)) in recipients.iter().enumerate() {
-            leaf_indices.insert(wallet.clone(), i);
+impl MerkleProof {
+    /// Recompute the root from the leaf and sibling path
+    ///
+    /// Uses the same ordering as `compute_merkle_root` in the circuit: at each
+    /// level the low bit of the index says whether the current node is the
+    /// right child (`hash(sibling, current)`) or the left (`hash(current, sibling)`).
+    pub fn compute_root(&self) -> Hash {
+        let mut current = self.leaf;
+        let mut idx = self.leaf_index;
+
+        for sibling in &self.siblings {
+            current = if idx % 2 == 1 {
+                self.scheme.hash_pair(sibling, &current)
+            } else {
+                self.scheme.hash_pair(&current, sibling)
+            };
+            idx /= 2;
         }
-        
-        // Build tree bottom-up
-        let mut nodes = leaves.clone();
-        let mut current_level = leaves;
-        
-        for _ in 0..TREE_DEPTH {
-            let mut next_level = Vec::new();
-            for chunk in current_level.chunks(2) {
-                let parent = hash_pair(&chunk[0], &chunk[1]);
-                next_level.push(parent);
-                nodes.push(parent);
-            }
-            current_level = next_level;
+
+        current
+    }
+
+    /// Check that this proof leads to `root`
+    pub fn verify(&self, root: &Hash) -> bool {
+        self.compute_root() == *root
+    }
+}
+
+impl MerkleTree {
+    /// Build a merkle tree of the given depth from recipient list
+    ///
+    /// Panics if `depth` exceeds `MAX_TREE_DEPTH`, the recipients don't fit
+    /// or a wallet is not a base58 public key.
+    pub fn from_recipients(recipients: &[(String, u64, [u8; 32])], depth: usize) -> Self {
+        let mut builder = MerkleTreeBuilder::new(depth);
+        assert!(recipients.len() <= max_leaves(depth), "Too many recipients");
+
+        for (wallet, amount, secret) in recipients {
+            builder.push(wallet, *amount, secret);
         }
-        
-        Self {
-            nodes,
-            leaf_count,
-            leaf_indices,
+        builder.build()
+    }
+
+    /// Build the SHA-256 tree `claim` and `claim_token` check allocations
+    /// against, leaves in the given order
+    ///
+    /// Panics if `depth` exceeds `MAX_TREE_DEPTH`, the recipients don't fit
+    /// or a wallet is not a base58 public key.
+    pub fn claim_tree(recipients: &[(String, u64)], depth: usize) -> Self {
+        let mut builder = MerkleTreeBuilder::with_scheme(depth, MerkleScheme::Sha256);
+        assert!(recipients.len() <= max_leaves(depth), "Too many recipients");
+
+        for (wallet, amount) in recipients {
+            builder.push(wallet, *amount, &[0u8; 32]);
         }
+        builder.build()
     }
-    
+
     /// Get the merkle root
     pub fn root(&self) -> Hash {
-        *self.nodes.last().unwrap_or(&[0u8; 32])
+        self.node(self.depth, 0)
     }
-    
+
+    /// Tree depth (number of siblings in each proof)
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn scheme(&self) -> MerkleScheme {
+        self.scheme
+    }
+
     /// Get proof for a wallet
     pub fn get_proof(&self, wallet: &str) -> Option<MerkleProof> {
         let leaf_index = *self.leaf_indices.get(wallet)?;
-        let leaf = self.nodes[leaf_index];
-        
-        let mut siblings = Vec::new();
+        let leaf = self.node(0, leaf_index);
+
+        let mut siblings = Vec::with_capacity(self.depth);
         let mut idx = leaf_index;
-        let mut level_start = 0;
-        let mut level_size = 1 << TREE_DEPTH;
-        
-        for _ in 0..TREE_DEPTH {
-            let sibling_idx = if idx % 2 == 0 { idx + 1 } else { idx - 1 };
-            siblings.push(self.nodes[level_start + sibling_idx]);
-            
-            level_start += level_size;
-            level_size /= 2;
+
+        for level in 0..self.depth {
+            siblings.push(self.node(level, idx ^ 1));
             idx /= 2;
         }
-        
+
         Some(MerkleProof {
             leaf_index,
             siblings,
             leaf,
+            scheme: self.scheme,
         })
     }
-    
+
     /// Get leaf index for a wallet
     pub fn get_leaf_index(&self, wallet: &str) -> Option<usize> {
         self.leaf_indices.get(wallet).copied()
     }
+
+    /// Node at `level`/`index`, falling back to the empty subtree hash
+    fn node(&self, level: usize, index: usize) -> Hash {
+        self.levels[level]
+            .get(index)
+            .copied()
+            .unwrap_or(self.scheme.zero_hashes()[level])
+    }
+}
+
+/// Builds a `MerkleTree` one recipient at a time
+///
+/// Leaves are hashed as they are pushed, so a recipient list can be fed in
+/// while it is still being read and only the upper levels remain for
+/// `build`.
+#[derive(Debug, Clone)]
+pub struct MerkleTreeBuilder {
+    depth: usize,
+    scheme: MerkleScheme,
+    leaves: Vec<Hash>,
+    leaf_indices: HashMap<String, usize>,
+}
+
+impl MerkleTreeBuilder {
+    /// Panics if `depth` exceeds `MAX_TREE_DEPTH`
+    pub fn new(depth: usize) -> Self {
+        Self::with_scheme(depth, MerkleScheme::Poseidon)
+    }
+
+    /// Panics if `depth` exceeds `MAX_TREE_DEPTH`
+    pub fn with_scheme(depth: usize, scheme: MerkleScheme) -> Self {
+        assert!(depth <= MAX_TREE_DEPTH, "Tree too deep");
+        Self {
+            depth,
+            scheme,
+            leaves: Vec::new(),
+            leaf_indices: HashMap::new(),
+        }
+    }
+
+    /// Number of leaves pushed so far
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Whether another leaf fits the tree
+    pub fn is_full(&self) -> bool {
+        self.leaves.len() >= max_leaves(self.depth)
+    }
+
+    /// Append the leaf hash(recipient, amount, secret), returning its index
+    ///
+    /// Panics if the tree is full or `wallet` is not a base58 public key.
+    pub fn push(&mut self, wallet: &str, amount: u64, secret: &[u8; 32]) -> usize {
+        assert!(!self.is_full(), "Too many recipients");
+
+        let index = self.leaves.len();
+        self.leaves.push(self.scheme.leaf(wallet, amount, secret));
+        self.leaf_indices.insert(wallet.to_string(), index);
+        index
+    }
+
+    /// Hash the levels above the leaves
+    pub fn build(self) -> MerkleTree {
+        // Build tree bottom-up, padding odd levels with the empty subtree hash
+        let zero_hashes = self.scheme.zero_hashes();
+        let mut levels = Vec::with_capacity(self.depth + 1);
+        levels.push(self.leaves);
+
+        for level in 0..self.depth {
+            let current = &levels[level];
+            let next: Vec<Hash> = current
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(&zero_hashes[level]);
+                    self.scheme.hash_pair(&pair[0], right)
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        MerkleTree {
+            levels,
+            depth: self.depth,
+            scheme: self.scheme,
+            leaf_indices: self.leaf_indices,
+        }
+    }
+}
+
+/// Reference merkle root: pads to `2^depth` leaves and hashes every pair
+///
+/// This is the original dense construction. It is kept to check and
+/// benchmark `MerkleTree` against, not for production use.
+pub fn dense_merkle_root(recipients: &[(String, u64, [u8; 32])], depth: usize) -> Hash {
+    assert!(recipients.len() <= max_leaves(depth), "Too many recipients");
+
+    let mut level: Vec<Hash> = recipients
+        .iter()
+        .map(|(wallet, amount, secret)| compute_leaf_hash(wallet, *amount, secret))
+        .collect();
+    level.resize(max_leaves(depth), [0u8; 32]);
+
+    for _ in 0..depth {
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+    }
+
+    level[0]
 }
 
 use ark_bn254::Fr;
@@ -122,36 +324,35 @@ use ark_ff::{PrimeField, BigInteger};
 use taceo_poseidon2::bn254::t4 as poseidon2;
 
 /// Compute leaf hash: hash(recipient, amount, secret)
-/// Matches Noir circuit expectations: poseidon(recipient, amount, secret)
+/// Matches Noir circuit expectations: poseidon(recipient, amount, secret),
+/// with recipient and amount encoded as the proof's public inputs.
+///
+/// Panics if `wallet` is not a base58 public key; recipients are validated
+/// before they reach a tree.
 pub fn compute_leaf_hash(wallet: &str, amount: u64, secret: &[u8; 32]) -> Hash {
-    // 1. Recipient (Wallet) -> Field Element
-    // Must match `wallet_to_field` in zk_proofs.rs:
-    // Decode Base58, take first 31 bytes, pad to 32 bytes (BE)
-    let mut wallet_bytes = [0u8; 32];
-    if let Ok(decoded) = bs58::decode(wallet).into_vec() {
-         let len = decoded.len().min(31);
-         // Place at the end for proper BE integer representation (if we view it as a number)
-         wallet_bytes[32 - len..].copy_from_slice(&decoded[..len]);
-    } else {
-        // Fallback for tests/non-base58 (like "wallet1")
-        // Just use bytes, fit at end
-        let w_bytes = wallet.as_bytes();
-        let len = w_bytes.len().min(31);
-        wallet_bytes[32 - len..].copy_from_slice(&w_bytes[..len]);
-    }
-
-    // 2. Amount -> Field Element
-    // Use raw u64 directly (already in correct units)
-    let amount_lamports = amount;
-    // To read as correct integer in BE, put bytes at end.
-    let mut amount_arr = [0u8; 32];
-    amount_arr[24..32].copy_from_slice(&amount_lamports.to_be_bytes());
-
-    // 3. Secret is already [u8; 32], assuming it's random bytes valid for field.
-    // If secret >= Modulus, from_be_bytes will modulo it. That's fine for a secret.
-
-    // Poseidon hash 3 inputs
-    poseidon_hash_3(&wallet_bytes, &amount_arr, secret)
+    let recipient = wire::recipient_field(&expect_pubkey(wallet));
+    poseidon_hash_3(&recipient, &wire::amount_field(amount), secret)
+}
+
+/// Compute the `claim`/`claim_token` leaf: sha256(0x00 || claimer || amount LE)
+///
+/// Panics if `wallet` is not a base58 public key, like `compute_leaf_hash`.
+pub fn compute_claim_leaf(wallet: &str, amount: u64) -> Hash {
+    claim_tree::leaf(sha256v, &expect_pubkey(wallet), amount)
+}
+
+fn expect_pubkey(wallet: &str) -> [u8; 32] {
+    parse_pubkey(wallet)
+        .unwrap_or_else(|| panic!("Recipient {:?} is not a base58 public key", wallet))
+}
+
+/// SHA-256 of the concatenated slices, as the program's `hashv` syscall
+fn sha256v(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
 }
 
 /// Compute nullifier: hash(secret, leaf_index)
@@ -176,7 +377,7 @@ fn hash_pair(left: &Hash, right: &Hash) -> Hash {
 /// - Initial state: [0, 0, 0, iv] where iv = message_length * 2^64
 /// - Absorb inputs by adding to state[0..n]
 /// - Permute and squeeze state[0]
-fn poseidon_hash_2(a: &[u8; 32], b: &[u8; 32]) -> Hash {
+pub fn poseidon_hash_2(a: &[u8; 32], b: &[u8; 32]) -> Hash {
     let a_field = bytes_to_field_element(a);
     let b_field = bytes_to_field_element(b);
     
@@ -196,7 +397,7 @@ fn poseidon_hash_2(a: &[u8; 32], b: &[u8; 32]) -> Hash {
 
 /// Poseidon2 sponge hash for 3 inputs (BN254)
 /// Matches Noir's Poseidon2::hash([a, b, c], 3)
-fn poseidon_hash_3(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> Hash {
+pub fn poseidon_hash_3(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> Hash {
     let a_field = bytes_to_field_element(a);
     let b_field = bytes_to_field_element(b);
     let c_field = bytes_to_field_element(c);
@@ -233,55 +434,255 @@ fn field_element_to_bytes(field: Fr) -> [u8; 32] {
 /// Generate a random secret for a recipient
 /// Returns bytes that represent a valid BN254 field element (< modulus)
 pub fn generate_secret() -> [u8; 32] {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    
-    // Generate random-ish bytes
+    use rand::{RngCore, rngs::OsRng};
+
     let mut random_bytes = [0u8; 32];
-    for (i, chunk) in random_bytes.chunks_mut(8).enumerate() {
-        let val = now.wrapping_add(i as u128).to_le_bytes();
-        chunk.copy_from_slice(&val[0..8]);
-    }
-    
+    OsRng.fill_bytes(&mut random_bytes);
+
     // Convert to field element (reduces mod field order) then back to bytes
     // This ensures the value is always < BN254 field modulus
     let field_element = Fr::from_be_bytes_mod_order(&random_bytes);
     field_element_to_bytes(field_element)
 }
 
+/// Parse a hex field element (0x-prefixed or not, up to 32 bytes BE)
+/// The value is reduced mod the BN254 field order so it is always a canonical
+/// circuit input, matching how `bytes_to_field_element` treats it when hashing.
+pub fn parse_field_hex(hex_str: &str) -> Option<Hash> {
+    let clean = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    let bytes = hex::decode(clean).ok()?;
+    if bytes.is_empty() || bytes.len() > 32 {
+        return None;
+    }
+    Some(field_element_to_bytes(Fr::from_be_bytes_mod_order(&bytes)))
+}
+
+/// Decode a base58 Solana public key
+pub fn parse_pubkey(value: &str) -> Option<[u8; 32]> {
+    bs58::decode(value).into_vec().ok()?.try_into().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use proptest::prelude::*;
+
+    fn wallet(n: u8) -> String {
+        bs58::encode([n; 32]).into_string()
+    }
+
     #[test]
     fn test_merkle_tree_basic() {
         let secret1 = generate_secret();
         let secret2 = generate_secret();
         
         let recipients = vec![
-            ("wallet1".to_string(), 1, secret1),
-            ("wallet2".to_string(), 2, secret2),
+            (wallet(1), 1, secret1),
+            (wallet(2), 2, secret2),
         ];
         
-        let tree = MerkleTree::from_recipients(&recipients);
+        let tree = MerkleTree::from_recipients(&recipients, DEFAULT_TREE_DEPTH);
         
         // Root should be non-zero
         let root = tree.root();
         assert_ne!(root, [0u8; 32]);
         
         // Should get proof for wallet1
-        let proof = tree.get_proof("wallet1");
+        let proof = tree.get_proof(&wallet(1));
         assert!(proof.is_some());
         
         let proof = proof.unwrap();
         assert_eq!(proof.leaf_index, 0);
-        assert_eq!(proof.siblings.len(), TREE_DEPTH);
+        assert_eq!(proof.siblings.len(), DEFAULT_TREE_DEPTH);
+    }
+
+    #[test]
+    fn test_merkle_proof_verify() {
+        let recipients = vec![
+            (wallet(1), 1, generate_secret()),
+            (wallet(2), 2, generate_secret()),
+            (wallet(3), 3, generate_secret()),
+        ];
+        let tree = MerkleTree::from_recipients(&recipients, DEFAULT_TREE_DEPTH);
+        let root = tree.root();
+
+        for (wallet, _, _) in &recipients {
+            assert!(tree.get_proof(wallet).unwrap().verify(&root));
+        }
+
+        // Wrong index flips the hash order
+        let mut proof = tree.get_proof(&wallet(1)).unwrap();
+        proof.leaf_index = 1;
+        assert!(!proof.verify(&root));
+
+        // Tampered sibling
+        let mut proof = tree.get_proof(&wallet(3)).unwrap();
+        proof.siblings[3][31] ^= 1;
+        assert!(!proof.verify(&root));
     }
     
+    #[test]
+    fn test_merkle_tree_is_deterministic() {
+        let recipients = vec![
+            (wallet(1), 1, generate_secret()),
+            (wallet(2), 2, generate_secret()),
+            (wallet(3), 3, generate_secret()),
+        ];
+
+        let first = MerkleTree::from_recipients(&recipients, DEFAULT_TREE_DEPTH);
+        let second = MerkleTree::from_recipients(&recipients, DEFAULT_TREE_DEPTH);
+        assert_eq!(first.root(), second.root());
+    }
+
+    #[test]
+    fn test_deep_tree_proof_length() {
+        let recipients = vec![
+            (wallet(1), 1, generate_secret()),
+            (wallet(2), 2, generate_secret()),
+        ];
+
+        let tree = MerkleTree::from_recipients(&recipients, 20);
+        let proof = tree.get_proof(&wallet(2)).unwrap();
+        assert_eq!(proof.leaf_index, 1);
+        assert_eq!(proof.siblings.len(), 20);
+
+        // Same leaves at a different depth give a different root
+        let shallow = MerkleTree::from_recipients(&recipients, DEFAULT_TREE_DEPTH);
+        assert_ne!(tree.root(), shallow.root());
+    }
+
+    #[test]
+    fn test_builder_stops_at_capacity() {
+        let mut builder = MerkleTreeBuilder::new(1);
+        assert_eq!(builder.push(&wallet(1), 1, &[1u8; 32]), 0);
+        assert!(!builder.is_full());
+        assert_eq!(builder.push(&wallet(2), 2, &[2u8; 32]), 1);
+        assert!(builder.is_full());
+
+        let tree = builder.build();
+        assert_eq!(tree.get_leaf_index(&wallet(2)), Some(1));
+        assert!(tree.get_proof(&wallet(1)).unwrap().verify(&tree.root()));
+    }
+
+    #[test]
+    fn test_leaf_uses_public_input_encodings() {
+        let secret = generate_secret();
+        let key = [7u8; 32];
+        let wallet = bs58::encode(key).into_string();
+
+        assert_eq!(
+            compute_leaf_hash(&wallet, 391, &secret),
+            poseidon_hash_3(
+                &wire::recipient_field(&key),
+                &wire::amount_field(391),
+                &secret
+            )
+        );
+        assert_eq!(parse_pubkey(&wallet), Some(key));
+        assert_eq!(parse_pubkey("wallet1"), None);
+        assert_eq!(parse_pubkey(&bs58::encode([7u8; 31]).into_string()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a base58 public key")]
+    fn test_leaf_rejects_non_pubkey_wallet() {
+        compute_leaf_hash("wallet1", 1, &[1u8; 32]);
+    }
+
+    #[test]
+    fn test_parse_field_hex_is_canonical() {
+        let secret = generate_secret();
+        let hex_str = format!("0x{}", hex::encode(secret));
+        assert_eq!(parse_field_hex(&hex_str), Some(secret));
+
+        // Values above the modulus are reduced, so they hash like the original
+        let reduced = parse_field_hex(&"ff".repeat(32)).unwrap();
+        assert_eq!(reduced, field_element_to_bytes(Fr::from_be_bytes_mod_order(&[0xff; 32])));
+
+        assert_eq!(parse_field_hex("not hex"), None);
+        assert_eq!(parse_field_hex(&"00".repeat(33)), None);
+    }
+
+    #[test]
+    fn test_zero_hashes_match_empty_dense_tree() {
+        for depth in [0, 1, 5, DEFAULT_TREE_DEPTH] {
+            assert_eq!(zero_hashes()[depth], dense_merkle_root(&[], depth));
+            assert_eq!(MerkleTree::from_recipients(&[], depth).root(), zero_hashes()[depth]);
+        }
+    }
+
+    #[test]
+    fn test_claim_tree_matches_program_scheme() {
+        let wallets = [[1u8; 32], [2u8; 32]].map(|key| bs58::encode(key).into_string());
+        let recipients = vec![(wallets[0].clone(), 1_000), (wallets[1].clone(), 2_500)];
+
+        // sha256(0x00 || claimer || amount LE), sha256(0x01 || left || right)
+        assert_eq!(
+            hex::encode(compute_claim_leaf(&wallets[0], 1_000)),
+            "7fb8a254740be5df899cec1cfae84eb9e0108fbc9da24c9df771e79eb3f7cc37"
+        );
+        let tree = MerkleTree::claim_tree(&recipients, 2);
+        assert_eq!(
+            hex::encode(tree.root()),
+            "aff7da8c8de71e225c15dfba758554dd29c83173fefd4e45b95711186adcc67b"
+        );
+
+        // Proofs check out the way the program checks them
+        let deep = MerkleTree::claim_tree(&recipients, DEFAULT_TREE_DEPTH);
+        for (wallet, amount) in &recipients {
+            let proof = deep.get_proof(wallet).unwrap();
+            assert_eq!(proof.scheme, MerkleScheme::Sha256);
+            assert!(proof.verify(&deep.root()));
+
+            let claimer: [u8; 32] = bs58::decode(wallet).into_vec().unwrap().try_into().unwrap();
+            let index = proof.leaf_index as u32;
+            let leaf = claim_tree::leaf(sha256v, &claimer, *amount);
+            let root = claim_tree::compute_root(sha256v, leaf, index, &proof.siblings);
+            assert_eq!(root, Some(deep.root()));
+
+            let inflated = claim_tree::leaf(sha256v, &claimer, amount + 1);
+            let root = claim_tree::compute_root(sha256v, inflated, index, &proof.siblings);
+            assert_ne!(root, Some(deep.root()));
+        }
+        assert_eq!(
+            MerkleTree::claim_tree(&[], 3).root(),
+            MerkleScheme::Sha256.zero_hashes()[3]
+        );
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_sparse_matches_dense(
+            amounts in proptest::collection::vec(any::<u64>(), 0..40),
+            extra_depth in 0usize..3,
+            seed in any::<[u8; 32]>(),
+        ) {
+            // Smallest depth that fits, plus some headroom
+            let min_depth = amounts.len().next_power_of_two().trailing_zeros() as usize;
+            let depth = min_depth + extra_depth;
+
+            let recipients: Vec<(String, u64, [u8; 32])> = amounts
+                .iter()
+                .enumerate()
+                .map(|(i, amount)| {
+                    let mut secret = seed;
+                    secret[0] = 0; // keep below the field modulus
+                    secret[31] = i as u8;
+                    (wallet(i as u8), *amount, secret)
+                })
+                .collect();
+
+            let tree = MerkleTree::from_recipients(&recipients, depth);
+            prop_assert_eq!(tree.root(), dense_merkle_root(&recipients, depth));
+
+            for (wallet, _, _) in &recipients {
+                prop_assert!(tree.get_proof(wallet).unwrap().verify(&tree.root()));
+            }
+        }
+    }
+
     #[test]
     fn test_nullifier_uniqueness() {
         let secret = generate_secret();
@@ -1,3 +1,4 @@
 pub mod merkle;
 pub mod response;
+pub mod secrets;
 pub mod server;
@@ -12,6 +12,20 @@ pub struct Errors {
     pub code: u16,
     pub message: String,
     pub details: Option<String>,
+    /// Per-field problems of a rejected request body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldError>>,
+}
+
+/// A problem with one field of a request, optionally in one row of a list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Field name, e.g. `total_amount`, or `wallet` within a row
+    pub field: String,
+    /// Index of the row in the list the field belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row: Option<usize>,
+    pub message: String,
 }
 
 /// The standardized application error response structure.
@@ -32,6 +46,7 @@ impl Default for ApiErrorResponse {
                 code: 500,
                 message: "An internal server error occurred.".to_owned(),
                 details: None,
+                fields: None,
             },
             timestamp: Utc::now().timestamp(),
         }
@@ -56,6 +71,11 @@ impl ApiErrorResponse {
         self.errors.details = Some(details);
         self
     }
+    /// Attaches per-field errors of a rejected request.
+    pub fn with_field_errors(mut self, fields: Vec<FieldError>) -> Self {
+        self.errors.fields = Some(fields);
+        self
+    }
 }
 
 /// --- IntoResponse Implementation ---
@@ -1,7 +1,7 @@
 mod error;
 mod success;
 
-pub use error::ApiErrorResponse;
+pub use error::{ApiErrorResponse, FieldError};
 pub use success::ApiSuccessResponse;
 
 pub type ApiResponse<T> = Result<ApiSuccessResponse<T>, ApiErrorResponse>;
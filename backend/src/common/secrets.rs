@@ -0,0 +1,103 @@
+//! Encryption for per-recipient claim secrets
+//!
+//! Secrets are generated once when a campaign is created and must survive
+//! restarts, because every leaf of the merkle tree commits to one. They are
+//! stored AES-256-GCM encrypted as hex(`nonce || ciphertext`).
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+
+use super::merkle::Hash;
+
+/// AES-GCM nonce size in bytes
+const NONCE_SIZE: usize = 12;
+
+/// Encrypts and decrypts recipient secrets with a server-side key
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for SecretCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretCipher").finish_non_exhaustive()
+    }
+}
+
+impl SecretCipher {
+    /// Create a cipher from a 32-byte key
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// Encrypt a secret, returning hex(nonce || ciphertext)
+    pub fn encrypt(&self, secret: &Hash) -> Result<String, SecretError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, secret.as_slice())
+            .map_err(|_| SecretError::Encrypt)?;
+
+        let mut out = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(hex::encode(out))
+    }
+
+    /// Decrypt a value produced by [`SecretCipher::encrypt`]
+    pub fn decrypt(&self, encoded: &str) -> Result<Hash, SecretError> {
+        let bytes = hex::decode(encoded).map_err(|_| SecretError::Malformed)?;
+        if bytes.len() <= NONCE_SIZE {
+            return Err(SecretError::Malformed);
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(NONCE_SIZE);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretError::Decrypt)?;
+
+        plaintext.try_into().map_err(|_| SecretError::Malformed)
+    }
+}
+
+/// Secret encryption errors
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Failed to encrypt secret")]
+    Encrypt,
+
+    #[error("Failed to decrypt secret (wrong key or corrupted data)")]
+    Decrypt,
+
+    #[error("Malformed encrypted secret")]
+    Malformed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::merkle::generate_secret;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let cipher = SecretCipher::new(&[7u8; 32]);
+        let secret = generate_secret();
+
+        let encrypted = cipher.encrypt(&secret).unwrap();
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_key_fails() {
+        let secret = generate_secret();
+        let encrypted = SecretCipher::new(&[1u8; 32]).encrypt(&secret).unwrap();
+
+        let result = SecretCipher::new(&[2u8; 32]).decrypt(&encrypted);
+        assert!(matches!(result, Err(SecretError::Decrypt)));
+    }
+}
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf, time::Duration};
 
 use dotenv::dotenv;
 
@@ -13,6 +13,88 @@ pub struct LoggingConfig {
     pub level: String,
 }
 
+#[derive(Clone)]
+pub struct SecretsConfig {
+    /// AES-256 key used to encrypt recipient claim secrets at rest
+    pub encryption_key: [u8; 32],
+}
+
+impl std::fmt::Debug for SecretsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretsConfig")
+            .field("encryption_key", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// How long a sign-in nonce can be used
+    pub nonce_ttl: Duration,
+    /// How long a session token stays valid
+    pub session_ttl: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainConfig {
+    /// Solana JSON-RPC endpoint
+    pub rpc_url: String,
+    /// Commitment level for reads (`confirmed` or `finalized`)
+    pub commitment: String,
+    /// Shadow Drop program id (base58)
+    pub program_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    /// Reconcile claims from program transactions in the background
+    pub enabled: bool,
+    /// Delay between indexer runs
+    pub poll_interval: Duration,
+}
+
+/// Whether ZK proofs are actually generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProverMode {
+    /// Generate real proofs; fail if the prover is not set up
+    Real,
+    /// Return placeholder proofs flagged as `mock` (development only)
+    Mock,
+    /// ZK proof endpoints are turned off
+    Disabled,
+}
+
+impl ProverMode {
+    /// Parse `PROVER_MODE`; mock mode is refused in production
+    pub fn parse(value: Option<&str>, is_production: bool) -> Result<Self, String> {
+        let mode = match value {
+            None | Some("real") => Self::Real,
+            Some("mock") => Self::Mock,
+            Some("disabled") => Self::Disabled,
+            Some(other) => {
+                return Err(format!(
+                    "PROVER_MODE must be 'real', 'mock' or 'disabled', got '{}'",
+                    other
+                ));
+            }
+        };
+
+        if mode == Self::Mock && is_production {
+            return Err("PROVER_MODE=mock is not allowed in production".to_string());
+        }
+        Ok(mode)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProverConfig {
+    pub mode: ProverMode,
+    /// Noir circuit packages, with their sunspot keys
+    pub circuits_dir: PathBuf,
+    /// Maximum number of proofs generated concurrently
+    pub workers: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub rust_env: String,
@@ -20,6 +102,11 @@ pub struct Config {
 
     pub server: ServerConfig,
     pub logging: LoggingConfig,
+    pub secrets: SecretsConfig,
+    pub auth: AuthConfig,
+    pub chain: ChainConfig,
+    pub indexer: IndexerConfig,
+    pub prover: ProverConfig,
 }
 
 impl Config {
@@ -48,6 +135,60 @@ impl Config {
             logging: LoggingConfig {
                 level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
             },
+            secrets: SecretsConfig {
+                encryption_key: env::var("RECIPIENT_SECRETS_KEY")
+                    .ok()
+                    .and_then(|k| hex::decode(k.trim()).ok())
+                    .and_then(|k| k.try_into().ok())
+                    .expect("RECIPIENT_SECRETS_KEY must be set to 32 bytes of hex"),
+            },
+            auth: AuthConfig {
+                nonce_ttl: Self::get_seconds("AUTH_NONCE_TTL_SECONDS", 5 * 60),
+                session_ttl: Self::get_seconds("AUTH_SESSION_TTL_SECONDS", 60 * 60),
+            },
+            chain: ChainConfig {
+                rpc_url: env::var("SOLANA_RPC_URL")
+                    .unwrap_or_else(|_| "http://127.0.0.1:8899".to_string()),
+                commitment: env::var("SOLANA_COMMITMENT")
+                    .unwrap_or_else(|_| "confirmed".to_string()),
+                program_id: env::var("PROGRAM_ID")
+                    .unwrap_or_else(|_| "7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v".to_string()),
+            },
+            indexer: IndexerConfig {
+                enabled: env::var("INDEXER_ENABLED").map_or(true, |v| v != "false"),
+                poll_interval: Self::get_seconds("INDEXER_POLL_SECONDS", 10),
+            },
+            prover: Self::get_prover_config(is_production),
+        }
+    }
+
+    fn get_seconds(name: &str, default: u64) -> Duration {
+        Duration::from_secs(
+            env::var(name)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default),
+        )
+    }
+
+    fn get_prover_config(is_production: bool) -> ProverConfig {
+        let mode = ProverMode::parse(env::var("PROVER_MODE").ok().as_deref(), is_production)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let circuits_dir =
+            PathBuf::from(env::var("CIRCUITS_DIR").unwrap_or_else(|_| "../circuits".to_string()));
+        let workers = env::var("PROVER_WORKERS")
+            .ok()
+            .and_then(|w| w.parse().ok())
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1)
+            });
+
+        ProverConfig {
+            mode,
+            circuits_dir,
+            workers,
         }
     }
 
@@ -59,3 +200,21 @@ impl Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prover_mode_defaults_to_real() {
+        assert_eq!(ProverMode::parse(None, true), Ok(ProverMode::Real));
+        assert_eq!(ProverMode::parse(Some("disabled"), true), Ok(ProverMode::Disabled));
+        assert!(ProverMode::parse(Some("fake"), false).is_err());
+    }
+
+    #[test]
+    fn test_mock_prover_mode_is_refused_in_production() {
+        assert_eq!(ProverMode::parse(Some("mock"), false), Ok(ProverMode::Mock));
+        assert!(ProverMode::parse(Some("mock"), true).is_err());
+    }
+}
@@ -0,0 +1,508 @@
+//! Streaming recipient import
+//!
+//! Recipient lists are uploaded as CSV or NDJSON and fed in chunk by chunk
+//! as the upload arrives. Each line is parsed and checked as soon as it is
+//! complete, with problems reported per row, and each valid recipient's
+//! leaf is hashed into a [`MerkleTreeBuilder`] right away, so only the
+//! upper levels of the tree are left once the last chunk is in.
+//!
+//! CSV rows are `wallet,amount[,secret]`, in that order unless a header
+//! row names the columns. NDJSON has one `{"wallet", "amount", "secret"}`
+//! object per line, `amount` being a string or a number. Secrets are
+//! optional and generated when missing, as in `POST /campaigns`.
+//!
+//! Accepted rows are kept until the list is complete, so uploads are capped
+//! by what the campaign's tree can hold ([`max_import_bytes`]).
+
+use serde::Deserialize;
+
+use crate::{
+    common::merkle::{MerkleTree, MerkleTreeBuilder, generate_secret, max_leaves},
+    models::Recipient,
+    validation::{CampaignLimits, RecipientRows, ValidationErrors},
+};
+
+/// Largest upload accepted by the import endpoint, whatever the tree depth
+pub const MAX_IMPORT_BYTES: usize = 16 << 20;
+
+/// Longest line accepted
+pub const MAX_LINE_BYTES: usize = 4096;
+
+/// Largest recipient list accepted for a tree of `depth`: a header and a
+/// full tree of the longest lines, within `MAX_IMPORT_BYTES`
+pub fn max_import_bytes(depth: usize) -> usize {
+    max_leaves(depth)
+        .saturating_add(1)
+        .saturating_mul(MAX_LINE_BYTES + 1)
+        .min(MAX_IMPORT_BYTES)
+}
+
+/// Row errors listed in full; any further ones are only counted
+pub const MAX_ROW_ERRORS: usize = 100;
+
+/// Format of an uploaded recipient list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientFormat {
+    Csv,
+    Ndjson,
+}
+
+impl RecipientFormat {
+    /// Format from a part's content type, or else its file extension
+    pub fn detect(content_type: Option<&str>, file_name: Option<&str>) -> Option<Self> {
+        let essence = content_type
+            .and_then(|c| c.split(';').next())
+            .map(|c| c.trim().to_ascii_lowercase());
+        match essence.as_deref() {
+            Some("text/csv") => return Some(Self::Csv),
+            Some("application/x-ndjson" | "application/jsonl" | "application/x-jsonlines") => {
+                return Some(Self::Ndjson);
+            }
+            _ => {}
+        }
+
+        let (_, extension) = file_name?.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// Column positions in a CSV file
+#[derive(Debug, Clone, Copy)]
+struct CsvColumns {
+    wallet: usize,
+    amount: usize,
+    secret: Option<usize>,
+    /// Cells per row, if set by a header
+    width: Option<usize>,
+}
+
+/// Columns of a CSV file without a header
+const DEFAULT_COLUMNS: CsvColumns = CsvColumns {
+    wallet: 0,
+    amount: 1,
+    secret: Some(2),
+    width: None,
+};
+
+/// A line of an NDJSON file
+#[derive(Deserialize)]
+struct NdjsonRow {
+    wallet: String,
+    /// A string or a number; anything else fails the amount check
+    amount: serde_json::Value,
+    #[serde(default)]
+    secret: Option<String>,
+}
+
+/// A fully read recipient list, in leaf order, and its tree
+#[derive(Debug)]
+pub struct ImportedRecipients {
+    pub recipients: Vec<Recipient>,
+    pub tree: MerkleTree,
+}
+
+/// Parses, checks and hashes a recipient list as it arrives
+#[derive(Debug)]
+pub struct RecipientImport {
+    format: RecipientFormat,
+    limits: CampaignLimits,
+    /// The line being read
+    line: Vec<u8>,
+    /// Dropping the rest of an overlong line
+    skipping: bool,
+    /// Lines read, to strip a byte order mark from the first
+    lines: usize,
+    /// Known once the first CSV line is read
+    columns: Option<CsvColumns>,
+    rows: RecipientRows,
+    errors: ValidationErrors,
+    /// Row errors past `MAX_ROW_ERRORS`
+    omitted: usize,
+    recipients: Vec<Recipient>,
+    /// `None` without a valid tree depth
+    tree: Option<MerkleTreeBuilder>,
+}
+
+impl RecipientImport {
+    pub fn new(format: RecipientFormat, limits: CampaignLimits) -> Self {
+        Self {
+            format,
+            limits,
+            line: Vec::new(),
+            skipping: false,
+            lines: 0,
+            columns: None,
+            rows: RecipientRows::new(),
+            errors: ValidationErrors::default(),
+            omitted: 0,
+            recipients: Vec::new(),
+            tree: limits.depth.map(MerkleTreeBuilder::new),
+        }
+    }
+
+    /// Feed the next chunk of the upload
+    pub fn push(&mut self, mut chunk: &[u8]) {
+        while let Some(end) = chunk.iter().position(|&b| b == b'\n') {
+            self.extend_line(&chunk[..end]);
+            self.end_line();
+            chunk = &chunk[end + 1..];
+        }
+        self.extend_line(chunk);
+    }
+
+    /// Check the list as a whole and build the tree
+    ///
+    /// Fails with the row and list errors; these may be empty if the
+    /// campaign fields had no valid tree depth, which they report.
+    pub fn finish(mut self) -> Result<ImportedRecipients, ValidationErrors> {
+        // The last line may have no newline
+        if !self.line.is_empty() || self.skipping {
+            self.end_line();
+        }
+
+        let mut errors = self.errors;
+        self.rows.finish(self.limits, &mut errors);
+        if self.omitted > 0 {
+            errors.add(
+                "recipients",
+                None,
+                format!("{} more row errors not listed", self.omitted),
+            );
+        }
+
+        match self.tree {
+            Some(tree) if errors.is_empty() => Ok(ImportedRecipients {
+                recipients: self.recipients,
+                tree: tree.build(),
+            }),
+            _ => Err(errors),
+        }
+    }
+
+    fn extend_line(&mut self, bytes: &[u8]) {
+        if self.skipping {
+            return;
+        }
+        if self.line.len() + bytes.len() > MAX_LINE_BYTES {
+            self.skipping = true;
+            self.line.clear();
+            return;
+        }
+        self.line.extend_from_slice(bytes);
+    }
+
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        if std::mem::take(&mut self.skipping) {
+            self.reject(format!("line is longer than {} bytes", MAX_LINE_BYTES));
+        } else {
+            self.read_line(&line);
+        }
+        // Reuse the allocation for the next line
+        self.line = line;
+        self.line.clear();
+    }
+
+    fn read_line(&mut self, mut bytes: &[u8]) {
+        if self.lines == 0 {
+            bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        }
+        self.lines += 1;
+
+        let Ok(text) = std::str::from_utf8(bytes) else {
+            self.reject("not valid UTF-8");
+            return;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        match self.format {
+            RecipientFormat::Csv => self.read_csv(text),
+            RecipientFormat::Ndjson => self.read_ndjson(text),
+        }
+    }
+
+    fn read_csv(&mut self, text: &str) {
+        let cells: Vec<&str> = text
+            .split(',')
+            .map(|c| c.trim().trim_matches('"').trim())
+            .collect();
+
+        let columns = match self.columns {
+            Some(columns) => columns,
+            None => {
+                let header = csv_header(&cells);
+                self.columns = Some(header.unwrap_or(DEFAULT_COLUMNS));
+                if header.is_some() {
+                    // A header row, not a recipient
+                    return;
+                }
+                DEFAULT_COLUMNS
+            }
+        };
+
+        let width_ok = match columns.width {
+            Some(width) => cells.len() == width,
+            None => (2..=3).contains(&cells.len()),
+        };
+        if !width_ok {
+            let expected = columns
+                .width
+                .map_or_else(|| "2 or 3".to_string(), |w| w.to_string());
+            self.reject(format!(
+                "expected {} columns, found {}",
+                expected,
+                cells.len()
+            ));
+            return;
+        }
+
+        let secret = columns
+            .secret
+            .and_then(|i| cells.get(i).copied())
+            .filter(|s| !s.is_empty());
+        self.add_row(cells[columns.wallet], cells[columns.amount], secret);
+    }
+
+    fn read_ndjson(&mut self, text: &str) {
+        let row: NdjsonRow = match serde_json::from_str(text) {
+            Ok(row) => row,
+            Err(e) => {
+                self.reject(format!("invalid JSON: {}", e));
+                return;
+            }
+        };
+        let amount = match row.amount {
+            serde_json::Value::String(amount) => amount,
+            other => other.to_string(),
+        };
+        self.add_row(&row.wallet, &amount, row.secret.as_deref());
+    }
+
+    fn add_row(&mut self, wallet: &str, amount: &str, secret: Option<&str>) {
+        let mut errors = ValidationErrors::default();
+        let checked = self.rows.check(wallet, amount, secret, &mut errors);
+        self.record(errors);
+
+        // Once any row failed nothing will be created; keep checking only
+        let Some((amount_value, secret)) = checked else {
+            return;
+        };
+        if !self.errors.is_empty() || self.omitted > 0 {
+            return;
+        }
+        let Some(tree) = self.tree.as_mut().filter(|tree| !tree.is_full()) else {
+            return;
+        };
+
+        // Secrets are fixed here, once, so the tree can be rebuilt for every proof
+        let secret = secret.unwrap_or_else(generate_secret);
+        tree.push(wallet, amount_value, &secret);
+        self.recipients.push(Recipient::new(
+            wallet.to_string(),
+            amount.to_string(),
+            secret,
+        ));
+    }
+
+    /// Count a row that could not be read
+    fn reject(&mut self, message: impl Into<String>) {
+        let mut errors = ValidationErrors::default();
+        self.rows.reject(message, &mut errors);
+        self.record(errors);
+    }
+
+    fn record(&mut self, errors: ValidationErrors) {
+        for error in errors.0 {
+            if self.errors.0.len() < MAX_ROW_ERRORS {
+                self.errors.0.push(error);
+            } else {
+                self.omitted += 1;
+            }
+        }
+    }
+}
+
+/// Columns named by a CSV header row; `None` if `cells` is not a header
+fn csv_header(cells: &[&str]) -> Option<CsvColumns> {
+    let find = |name: &str| cells.iter().position(|c| c.eq_ignore_ascii_case(name));
+    Some(CsvColumns {
+        wallet: find("wallet")?,
+        amount: find("amount")?,
+        secret: find("secret"),
+        width: Some(cells.len()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::merkle::DEFAULT_TREE_DEPTH;
+
+    fn key(byte: u8) -> String {
+        bs58::encode([byte; 32]).into_string()
+    }
+
+    fn limits(total_amount: u64) -> CampaignLimits {
+        CampaignLimits {
+            total_amount: Some(total_amount),
+            depth: Some(DEFAULT_TREE_DEPTH),
+        }
+    }
+
+    /// Feed `data` in chunks of `size` bytes
+    fn import(
+        format: RecipientFormat,
+        limits: CampaignLimits,
+        data: &str,
+        size: usize,
+    ) -> Result<ImportedRecipients, ValidationErrors> {
+        let mut import = RecipientImport::new(format, limits);
+        for chunk in data.as_bytes().chunks(size) {
+            import.push(chunk);
+        }
+        import.finish()
+    }
+
+    fn rows(errors: &ValidationErrors) -> Vec<(&str, Option<usize>)> {
+        errors.0.iter().map(|e| (e.field.as_str(), e.row)).collect()
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            RecipientFormat::detect(Some("text/csv; charset=utf-8"), None),
+            Some(RecipientFormat::Csv)
+        );
+        assert_eq!(
+            RecipientFormat::detect(Some("application/octet-stream"), Some("drop.JSONL")),
+            Some(RecipientFormat::Ndjson)
+        );
+        assert_eq!(RecipientFormat::detect(None, Some("drop.xlsx")), None);
+    }
+
+    #[test]
+    fn test_csv_in_any_chunk_size() {
+        let secret = format!("{:064x}", 7);
+        let csv = format!(
+            "\u{feff}amount,Wallet,secret\r\n100,{},{}\r\n\r\n250,\"{}\",\n",
+            key(3),
+            secret,
+            key(4)
+        );
+
+        let expected = import(RecipientFormat::Csv, limits(350), &csv, csv.len()).unwrap();
+        assert_eq!(expected.recipients.len(), 2);
+        assert_eq!(expected.recipients[1].wallet, key(4));
+        assert_eq!(expected.recipients[1].amount, "250");
+        assert_eq!(hex::encode(expected.recipients[0].secret.unwrap()), secret);
+
+        for size in [1, 3, 17] {
+            let imported = import(RecipientFormat::Csv, limits(350), &csv, size).unwrap();
+            assert_eq!(imported.recipients[0].secret, expected.recipients[0].secret);
+            assert_eq!(imported.tree.get_leaf_index(&key(4)), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_upload_size_follows_tree_depth() {
+        assert_eq!(max_import_bytes(2), 5 * (MAX_LINE_BYTES + 1));
+        assert_eq!(max_import_bytes(DEFAULT_TREE_DEPTH), 257 * (MAX_LINE_BYTES + 1));
+        assert_eq!(max_import_bytes(20), MAX_IMPORT_BYTES);
+    }
+
+    #[test]
+    fn test_tree_matches_recipients() {
+        let csv = format!("{},100\n{},250", key(3), key(4));
+        let imported = import(RecipientFormat::Csv, limits(350), &csv, 5).unwrap();
+
+        let leaves: Vec<_> = imported
+            .recipients
+            .iter()
+            .map(|r| {
+                (
+                    r.wallet.clone(),
+                    r.amount.parse().unwrap(),
+                    r.secret.unwrap(),
+                )
+            })
+            .collect();
+        let tree = MerkleTree::from_recipients(&leaves, DEFAULT_TREE_DEPTH);
+        assert_eq!(imported.tree.root(), tree.root());
+    }
+
+    #[test]
+    fn test_row_errors() {
+        let csv = format!(
+            "{w3},100\n{w4}\nnot-a-key,5\n{w3},x\n{long}\n{w5},1,2,3\n",
+            w3 = key(3),
+            w4 = key(4),
+            w5 = key(5),
+            long = "a".repeat(MAX_LINE_BYTES + 1),
+        );
+        let errors = import(RecipientFormat::Csv, limits(100), &csv, 64).unwrap_err();
+        assert_eq!(
+            rows(&errors),
+            [
+                ("recipients", Some(1)),
+                ("wallet", Some(2)),
+                ("wallet", Some(3)),
+                ("amount", Some(3)),
+                ("recipients", Some(4)),
+                ("recipients", Some(5)),
+            ]
+        );
+        assert_eq!(errors.0[2].message, "duplicate of row 0");
+        assert_eq!(errors.0[5].message, "expected 2 or 3 columns, found 4");
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let ndjson = format!(
+            "{{\"wallet\":\"{}\",\"amount\":100}}\n{{\"wallet\":\"{}\",\"amount\":\"250\"}}\n",
+            key(3),
+            key(4)
+        );
+        let imported = import(RecipientFormat::Ndjson, limits(350), &ndjson, 10).unwrap();
+        assert_eq!(imported.recipients[0].amount, "100");
+
+        let ndjson = format!("{{\"wallet\":\"{}\",\"amount\":-1}}\n{{oops\n", key(3));
+        let errors = import(RecipientFormat::Ndjson, limits(350), &ndjson, 10).unwrap_err();
+        assert_eq!(
+            rows(&errors),
+            [("amount", Some(0)), ("recipients", Some(1))]
+        );
+    }
+
+    #[test]
+    fn test_list_level_errors() {
+        let errors = import(RecipientFormat::Csv, limits(1), "wallet,amount\n", 4).unwrap_err();
+        assert_eq!(errors.0[0].message, "must not be empty");
+
+        let csv: String = (0..5).map(|i| format!("{},1\n", key(10 + i))).collect();
+        let shallow = CampaignLimits {
+            total_amount: Some(5),
+            depth: Some(2),
+        };
+        let errors = import(RecipientFormat::Csv, shallow, &csv, 8).unwrap_err();
+        assert_eq!(rows(&errors), [("recipients", None)]);
+    }
+
+    #[test]
+    fn test_row_errors_are_capped() {
+        let count = MAX_ROW_ERRORS + 5;
+        let csv = "x,1\n".repeat(count);
+        let errors = import(RecipientFormat::Csv, limits(count as u64), &csv, 100).unwrap_err();
+        assert_eq!(errors.0.len(), MAX_ROW_ERRORS + 1);
+        assert_eq!(
+            errors.0[MAX_ROW_ERRORS].message,
+            "5 more row errors not listed"
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Chain claim indexer
+//!
+//! Follows the program's transactions through `getSignaturesForAddress`,
+//! decodes the claims each successful one paid out and reconciles them
+//! into Postgres: every claim instruction is recorded in `chain_claims`,
+//! and the recipient it paid gets its claim status, claimed amount and
+//! transaction signature updated. Campaigns closed by `close_campaign`
+//! get their `closed_at` set. The signature of the last processed
+//! transaction is stored in the same database transaction as its claims,
+//! so a restarted indexer resumes exactly where it stopped.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::chain::{
+    ChainClaim, ChainError, ChainRpc, SignatureInfo, decode_claims, decode_closed_campaigns,
+};
+
+/// Cursor name of the claim indexer in `indexer_cursors`
+const CURSOR: &str = "claims";
+
+/// Signatures fetched per `getSignaturesForAddress` call (the RPC maximum)
+const PAGE_SIZE: usize = 1000;
+
+/// Indexer errors
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    #[error(transparent)]
+    Chain(#[from] ChainError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Indexer state persisted in PostgreSQL
+#[derive(Debug, Clone)]
+pub struct IndexerStore {
+    db: PgPool,
+}
+
+impl IndexerStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Signature of the last processed transaction
+    pub async fn cursor(&self) -> Result<Option<String>, sqlx::Error> {
+        let signature = sqlx::query_scalar!(
+            "SELECT signature FROM indexer_cursors WHERE name = $1",
+            CURSOR
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(signature)
+    }
+
+    /// Record the claims and closed campaigns of a transaction and move the
+    /// cursor past it
+    ///
+    /// Returns the number of recipients updated. Claims already recorded
+    /// (a replayed transaction) are skipped.
+    pub async fn record(
+        &self,
+        signature: &SignatureInfo,
+        block_time: Option<DateTime<Utc>>,
+        claims: &[ChainClaim],
+        closed: &[String],
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let mut updated = 0;
+
+        for claim in claims {
+            let amount = claim.amount.to_string();
+            let inserted = sqlx::query!(
+                r#"
+                INSERT INTO chain_claims
+                    (signature, instruction_index, campaign_address, wallet,
+                     instruction, amount, nullifier, slot, block_time)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (signature, instruction_index) DO NOTHING
+                "#,
+                signature.signature,
+                claim.instruction_index as i32,
+                claim.campaign,
+                claim.claimer,
+                claim.kind.as_str(),
+                amount,
+                claim.nullifier.map(hex::encode),
+                signature.slot as i64,
+                block_time
+            )
+            .execute(&mut *tx)
+            .await?;
+            if inserted.rows_affected() == 0 {
+                continue;
+            }
+
+            // Amounts are raw integer strings; sum them as NUMERIC. The total
+            // of the recorded claims, not an increment, so an allocation
+            // `mark_claimed` already reported as paid is not counted twice
+            let result = sqlx::query!(
+                r#"
+                UPDATE recipients
+                SET claimed = TRUE,
+                    claimed_at = COALESCE($3, claimed_at, NOW()),
+                    claimed_amount = GREATEST(
+                        claimed_amount::NUMERIC,
+                        (SELECT SUM(amount::NUMERIC) FROM chain_claims
+                         WHERE campaign_address = $1 AND wallet = $2)
+                    )::TEXT,
+                    claim_tx_signature = $4
+                WHERE campaign_address = $1 AND wallet = $2
+                "#,
+                claim.campaign,
+                claim.claimer,
+                block_time,
+                signature.signature
+            )
+            .execute(&mut *tx)
+            .await?;
+            updated += result.rows_affected();
+        }
+
+        for campaign in closed {
+            sqlx::query!(
+                r#"
+                UPDATE campaigns
+                SET closed_at = COALESCE($2, NOW())
+                WHERE address = $1 AND closed_at IS NULL
+                "#,
+                campaign,
+                block_time
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO indexer_cursors (name, signature, slot)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE
+            SET signature = EXCLUDED.signature, slot = EXCLUDED.slot, updated_at = NOW()
+            "#,
+            CURSOR,
+            signature.signature,
+            signature.slot as i64
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(updated)
+    }
+}
+
+/// Reconciles on-chain claims of one program into the database
+#[derive(Clone)]
+pub struct ClaimIndexer {
+    rpc: Arc<dyn ChainRpc>,
+    store: IndexerStore,
+    program_id: String,
+}
+
+impl ClaimIndexer {
+    pub fn new(rpc: Arc<dyn ChainRpc>, store: IndexerStore, program_id: impl Into<String>) -> Self {
+        Self {
+            rpc,
+            store,
+            program_id: program_id.into(),
+        }
+    }
+
+    /// Process every transaction since the cursor
+    ///
+    /// Returns the number of transactions processed. Stops early at a
+    /// transaction the node cannot return yet; the next run retries it.
+    pub async fn sync(&self) -> Result<usize, IndexerError> {
+        let cursor = self.store.cursor().await?;
+        let pending =
+            pending_signatures(self.rpc.as_ref(), &self.program_id, cursor.as_deref(), PAGE_SIZE)
+                .await?;
+
+        let mut processed = 0;
+        for signature in &pending {
+            let (block_time, claims, closed) = if signature.failed {
+                (None, Vec::new(), Vec::new())
+            } else {
+                let Some(tx) = self.rpc.get_transaction(&signature.signature).await? else {
+                    break;
+                };
+                let block_time = tx.block_time.and_then(|t| DateTime::from_timestamp(t, 0));
+                (
+                    block_time,
+                    decode_claims(&tx, &self.program_id),
+                    decode_closed_campaigns(&tx, &self.program_id),
+                )
+            };
+
+            let updated = self.store.record(signature, block_time, &claims, &closed).await?;
+            if !claims.is_empty() {
+                tracing::info!(
+                    signature = %signature.signature,
+                    claims = claims.len(),
+                    recipients = updated,
+                    "Indexed claim transaction"
+                );
+            }
+            for campaign in &closed {
+                tracing::info!(signature = %signature.signature, %campaign, "Indexed closed campaign");
+            }
+            processed += 1;
+        }
+        Ok(processed)
+    }
+}
+
+/// Signatures of `address` newer than `until`, oldest first, paging back
+/// `page_size` at a time
+async fn pending_signatures(
+    rpc: &dyn ChainRpc,
+    address: &str,
+    until: Option<&str>,
+    page_size: usize,
+) -> Result<Vec<SignatureInfo>, ChainError> {
+    let mut pending: Vec<SignatureInfo> = Vec::new();
+    loop {
+        let before = pending.last().map(|s| s.signature.clone());
+        let page = rpc
+            .get_signatures_for_address(address, before.as_deref(), until, page_size)
+            .await?;
+        let done = page.len() < page_size;
+        pending.extend(page);
+        if done {
+            break;
+        }
+    }
+    pending.reverse();
+    Ok(pending)
+}
+
+/// Spawn the indexer, syncing every `interval`
+pub fn spawn(indexer: ClaimIndexer, interval: Duration) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = indexer.sync().await {
+                tracing::warn!(error = %e, "Claim indexer sync failed");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{InMemoryRpc, Transaction};
+
+    const PROGRAM_ID: &str = "7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v";
+
+    fn fixtures() -> Vec<Transaction> {
+        [
+            include_str!("../fixtures/chain/claim_zk_simple.json"),
+            include_str!("../fixtures/chain/claim_token.json"),
+            include_str!("../fixtures/chain/failed_claim.json"),
+        ]
+        .into_iter()
+        .map(|json| Transaction::from_rpc_json(json).unwrap())
+        .collect()
+    }
+
+    fn signatures(infos: &[SignatureInfo]) -> Vec<&str> {
+        infos.iter().map(|s| s.signature.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_pending_signatures_resume_from_cursor() {
+        let rpc = InMemoryRpc::new();
+        let txs = fixtures();
+        for tx in &txs {
+            rpc.add_transaction(tx.clone());
+        }
+        let all: Vec<&str> = txs.iter().map(|tx| tx.signature.as_str()).collect();
+
+        // Oldest first, across pages
+        let pending = pending_signatures(&rpc, PROGRAM_ID, None, 2).await.unwrap();
+        assert_eq!(signatures(&pending), all);
+        assert!(pending[2].failed);
+
+        let pending = pending_signatures(&rpc, PROGRAM_ID, Some(all[0]), 1)
+            .await
+            .unwrap();
+        assert_eq!(signatures(&pending), &all[1..]);
+
+        let pending = pending_signatures(&rpc, PROGRAM_ID, Some(all[2]), 2)
+            .await
+            .unwrap();
+        assert!(pending.is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+//! Background ZK proof jobs
+//!
+//! `POST /zk-proofs/:address/jobs` inserts a row into `proof_jobs` and
+//! returns immediately; workers spawned at startup claim queued rows, prove
+//! them and store the `ZkProofResponse`. State lives in Postgres only:
+//! a claimed job holds a lease, and if its worker dies (crash, deploy) the
+//! lease expires and another worker picks it up.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, types::Json};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::{
+    repository::StoreError,
+    routes::zk_proofs::{ZkProofError, ZkProofResponse, build_zk_proof},
+    state::AppState,
+    zk::ProverError,
+};
+
+/// Attempts before a job is failed for good
+pub const MAX_ATTEMPTS: i32 = 3;
+
+/// How long a worker owns a running job before others may take it over
+const LEASE: Duration = Duration::from_secs(10 * 60);
+
+/// Fallback poll interval when no submission wakes the workers
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Base delay before retrying a transient failure (doubled per attempt)
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Proof job lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "queued" => Self::Queued,
+            "running" => Self::Running,
+            "succeeded" => Self::Succeeded,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// A proof generation job
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofJob {
+    pub id: Uuid,
+    pub campaign_address: String,
+    pub wallet: String,
+    pub status: JobStatus,
+    pub attempts: i32,
+    /// The proof, once the job succeeded
+    pub result: Option<ZkProofResponse>,
+    /// Last failure message
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Row shape shared by the job queries
+#[derive(sqlx::FromRow)]
+struct ProofJobRow {
+    id: Uuid,
+    campaign_address: String,
+    wallet: String,
+    status: String,
+    attempts: i32,
+    result: Option<Json<ZkProofResponse>>,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<ProofJobRow> for ProofJob {
+    fn from(row: ProofJobRow) -> Self {
+        Self {
+            id: row.id,
+            campaign_address: row.campaign_address,
+            wallet: row.wallet,
+            status: JobStatus::parse(&row.status),
+            attempts: row.attempts,
+            result: row.result.map(|r| r.0),
+            error: row.error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Persistent proof job queue using PostgreSQL
+#[derive(Debug, Clone)]
+pub struct ProofJobStore {
+    db: PgPool,
+    /// Wakes idle workers when a job is submitted
+    notify: Arc<Notify>,
+}
+
+impl ProofJobStore {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Queue a job, or return the claim's job that is already queued/running
+    ///
+    /// A live job can finish between the skipped insert and the lookup, in
+    /// which case the insert is retried.
+    pub async fn enqueue(&self, address: &str, wallet: &str) -> Result<ProofJob, sqlx::Error> {
+        loop {
+            if let Some(job) = self.try_enqueue(address, wallet).await? {
+                return Ok(job);
+            }
+        }
+    }
+
+    /// One attempt of `enqueue`; `None` if no live job was inserted or found
+    async fn try_enqueue(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<Option<ProofJob>, sqlx::Error> {
+        let inserted = sqlx::query_as!(
+            ProofJobRow,
+            r#"
+            INSERT INTO proof_jobs (id, campaign_address, wallet)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (campaign_address, wallet) WHERE status IN ('queued', 'running')
+            DO NOTHING
+            RETURNING id, campaign_address, wallet, status, attempts,
+                      result as "result: Json<ZkProofResponse>", error, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            address,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        if let Some(row) = inserted {
+            self.notify.notify_one();
+            return Ok(Some(row.into()));
+        }
+
+        let row = sqlx::query_as!(
+            ProofJobRow,
+            r#"
+            SELECT id, campaign_address, wallet, status, attempts,
+                   result as "result: Json<ZkProofResponse>", error, created_at, updated_at
+            FROM proof_jobs
+            WHERE campaign_address = $1 AND wallet = $2 AND status IN ('queued', 'running')
+            "#,
+            address,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Get a job by id
+    pub async fn get(&self, id: Uuid) -> Result<Option<ProofJob>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            ProofJobRow,
+            r#"
+            SELECT id, campaign_address, wallet, status, attempts,
+                   result as "result: Json<ZkProofResponse>", error, created_at, updated_at
+            FROM proof_jobs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Take the oldest runnable job: a due queued one, or a running one
+    /// whose lease expired
+    pub async fn claim_next(&self) -> Result<Option<ProofJob>, sqlx::Error> {
+        let now = Utc::now();
+        let row = sqlx::query_as!(
+            ProofJobRow,
+            r#"
+            UPDATE proof_jobs
+            SET status = 'running', attempts = attempts + 1,
+                locked_until = $2, updated_at = $1
+            WHERE id = (
+                SELECT id FROM proof_jobs
+                WHERE (status = 'queued' AND run_after <= $1)
+                   OR (status = 'running' AND locked_until < $1)
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, campaign_address, wallet, status, attempts,
+                      result as "result: Json<ZkProofResponse>", error, created_at, updated_at
+            "#,
+            now,
+            now + LEASE
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Store the proof of a finished job
+    pub async fn succeed(&self, id: Uuid, result: &ZkProofResponse) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE proof_jobs
+            SET status = 'succeeded', result = $2, error = NULL,
+                locked_until = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            Json(result) as _
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Put a job back in the queue to run again after `delay`
+    pub async fn retry(&self, id: Uuid, error: &str, delay: Duration) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE proof_jobs
+            SET status = 'queued', error = $2, run_after = $3,
+                locked_until = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            error,
+            Utc::now() + delay
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a job as permanently failed
+    pub async fn fail(&self, id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE proof_jobs
+            SET status = 'failed', error = $2, locked_until = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            error
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Wait for a submission or the poll interval, whichever comes first
+    async fn wait_for_work(&self) {
+        let _ = tokio::time::timeout(POLL_INTERVAL, self.notify.notified()).await;
+    }
+}
+
+/// What to do with a job after an attempt
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Retry(Duration),
+    Fail,
+}
+
+/// Only transient failures (a prover binary that could not be run, the
+/// database being unreachable) are retried; invalid claims and proving
+/// errors fail right away
+fn on_error(error: &ZkProofError, attempts: i32) -> Outcome {
+    match error {
+        ZkProofError::Prover(ProverError::CommandError(_))
+        | ZkProofError::Store(StoreError::Unavailable(_))
+            if attempts < MAX_ATTEMPTS =>
+        {
+            Outcome::Retry(RETRY_BACKOFF * 2u32.pow(attempts.max(1) as u32 - 1))
+        }
+        _ => Outcome::Fail,
+    }
+}
+
+/// Spawn `workers` job workers
+pub fn spawn_workers(state: AppState, workers: usize) {
+    for worker in 0..workers.max(1) {
+        tokio::spawn(run_worker(state.clone(), worker));
+    }
+}
+
+async fn run_worker(state: AppState, worker: usize) {
+    loop {
+        match state.proof_jobs.claim_next().await {
+            Ok(Some(job)) => {
+                if let Err(e) = process(&state, &job).await {
+                    tracing::error!(worker, job = %job.id, error = %e, "Failed to update proof job");
+                }
+            }
+            Ok(None) => state.proof_jobs.wait_for_work().await,
+            Err(e) => {
+                tracing::error!(worker, error = %e, "Failed to claim proof job");
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn process(state: &AppState, job: &ProofJob) -> Result<(), sqlx::Error> {
+    let jobs = &state.proof_jobs;
+
+    // Taken over from a worker that died mid-attempt too many times
+    if job.attempts > MAX_ATTEMPTS {
+        return jobs.fail(job.id, "Exceeded retry limit").await;
+    }
+
+    tracing::info!(job = %job.id, attempt = job.attempts, "Running proof job");
+    match build_zk_proof(state, &job.campaign_address, &job.wallet).await {
+        Ok((response, _)) => jobs.succeed(job.id, &response).await,
+        Err(e) => match on_error(&e, job.attempts) {
+            Outcome::Retry(delay) => {
+                tracing::warn!(job = %job.id, error = %e, "Proof job failed, retrying");
+                jobs.retry(job.id, &e.to_string(), delay).await
+            }
+            Outcome::Fail => {
+                tracing::warn!(job = %job.id, error = %e, "Proof job failed");
+                jobs.fail(job.id, &e.to_string()).await
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::response::ApiErrorResponse;
+
+    #[test]
+    fn test_only_command_errors_are_retried() {
+        let command = || ZkProofError::Prover(ProverError::CommandError("spawn".into()));
+
+        assert_eq!(on_error(&command(), 1), Outcome::Retry(RETRY_BACKOFF));
+        assert_eq!(on_error(&command(), 2), Outcome::Retry(RETRY_BACKOFF * 2));
+        assert_eq!(on_error(&command(), MAX_ATTEMPTS), Outcome::Fail);
+
+        let proving = ZkProofError::Prover(ProverError::SunspotError("bad".into()));
+        assert_eq!(on_error(&proving, 1), Outcome::Fail);
+        let rejected = ZkProofError::Rejected(ApiErrorResponse::default());
+        assert_eq!(on_error(&rejected, 1), Outcome::Fail);
+
+        let outage = ZkProofError::Store(StoreError::Unavailable(sqlx::Error::PoolTimedOut));
+        assert_eq!(on_error(&outage, 1), Outcome::Retry(RETRY_BACKOFF));
+        let missing = ZkProofError::Store(StoreError::NotFound("Campaign"));
+        assert_eq!(on_error(&missing, 1), Outcome::Fail);
+    }
+}
@@ -1,8 +1,16 @@
+pub mod auth;
+pub mod chain;
 pub mod common;
 pub mod config;
+pub mod import;
+pub mod indexer;
+pub mod jobs;
 pub mod logging;
 pub mod middleware;
 pub mod models;
+pub mod repository;
 pub mod routes;
 pub mod state;
+pub mod validation;
+pub mod vesting;
 pub mod zk;
@@ -6,7 +6,10 @@ use axum::{
 };
 
 use shadow_drop_api::{
-    common::server::create_dual_stack_listener, config::Config, logging,
+    common::server::create_dual_stack_listener,
+    config::Config,
+    indexer::{self, ClaimIndexer, IndexerStore},
+    jobs, logging,
     middleware::http_trace_middleware::http_trace_middleware, routes::app_routes, state::AppState,
 };
 use sqlx::PgPool;
@@ -41,9 +44,29 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to run migrations");
 
-    let app_state = AppState::new(config.clone(), pool);
+    let app_state = AppState::new(config.clone(), pool.clone());
     info!("✅ Application state initialized");
 
+    jobs::spawn_workers(app_state.clone(), config.prover.workers);
+    info!(
+        workers = config.prover.workers,
+        mode = ?config.prover.mode,
+        "✅ Proof job workers started"
+    );
+
+    if config.indexer.enabled {
+        let claims = ClaimIndexer::new(
+            app_state.chain.clone(),
+            IndexerStore::new(pool.clone()),
+            config.chain.program_id.clone(),
+        );
+        indexer::spawn(claims, config.indexer.poll_interval);
+        info!(
+            interval = ?config.indexer.poll_interval,
+            "✅ Claim indexer started"
+        );
+    }
+
     let allowed_origins: Vec<_> = app_state
         .config
         .server
@@ -59,7 +82,7 @@ async fn main() -> std::io::Result<()> {
     let cors = CorsLayer::new()
         .allow_origin(allowed_origins)
         .allow_methods([Method::GET, Method::POST])
-        .allow_headers([header::ACCEPT, header::CONTENT_TYPE]);
+        .allow_headers([header::ACCEPT, header::AUTHORIZATION, header::CONTENT_TYPE]);
 
     let app = app_routes(app_state.clone())
         .layer(middleware::from_fn(http_trace_middleware))
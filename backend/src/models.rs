@@ -1,6 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::FromRow;
+
+use crate::{
+    common::merkle::{Hash, MAX_TREE_DEPTH, MerkleTree, max_leaves, parse_pubkey},
+    vesting::VestingSchedule,
+};
 
 /// A single recipient in a campaign
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -11,6 +16,53 @@ pub struct Recipient {
     pub amount: String, // Stored as Raw Integer String
     pub claimed: bool,
     pub claimed_at: Option<DateTime<Utc>>,
+    /// Total paid out on chain, as reconciled by the indexer (raw integer string)
+    #[serde(skip_deserializing, default = "zero_amount")]
+    pub claimed_amount: String,
+    /// Signature of the latest on-chain claim
+    #[serde(skip_deserializing)]
+    pub claim_tx_signature: Option<String>,
+    /// Claim secret committed to in this recipient's merkle leaf
+    /// (encrypted at rest, never serialized)
+    #[serde(skip)]
+    #[sqlx(skip)]
+    pub secret: Option<Hash>,
+}
+
+impl Recipient {
+    /// A new, unclaimed recipient
+    pub fn new(wallet: String, amount: String, secret: Hash) -> Self {
+        Self {
+            id: None,
+            wallet,
+            amount,
+            claimed: false,
+            claimed_at: None,
+            claimed_amount: zero_amount(),
+            claim_tx_signature: None,
+            secret: Some(secret),
+        }
+    }
+
+    /// Whether nothing is left to claim, see [`fully_claimed`]
+    pub fn fully_claimed(&self) -> bool {
+        fully_claimed(&self.amount, &self.claimed_amount)
+    }
+}
+
+/// Whether a recipient has been paid its whole allocation
+///
+/// Only the amounts decide: vested allocations are paid out over several
+/// claims, so `claimed` just means at least one claim was made.
+pub fn fully_claimed(amount: &str, claimed_amount: &str) -> bool {
+    match (claimed_amount.parse::<u128>(), amount.parse::<u128>()) {
+        (Ok(claimed_amount), Ok(amount)) => claimed_amount >= amount,
+        _ => false,
+    }
+}
+
+fn zero_amount() -> String {
+    "0".to_string()
 }
 
 /// Campaign data stored in the backend
@@ -20,6 +72,7 @@ pub struct Campaign {
     pub address: String,
     pub name: String,
     pub merkle_root: String,
+    pub tree_depth: i16, // SQLx maps SMALLINT to i16.
     pub total_amount: String, // Stored as Raw Integer String
     pub creator_wallet: String,
     pub tx_signature: Option<String>,
@@ -32,10 +85,124 @@ pub struct Campaign {
     pub vesting_start: i64,
     pub vesting_cliff_seconds: i64,
     pub vesting_duration_seconds: i64,
+    #[sqlx(json)]
+    pub vesting_schedule: VestingSchedule,
     // Token fields (None = SOL campaign)
     pub token_mint: Option<String>,
     pub token_symbol: Option<String>,
     pub token_decimals: Option<i16>, // SQLx maps SMALLINT to i16.
+    /// When the program closed the campaign, as seen by the indexer
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl Campaign {
+    /// Rebuild the campaign's merkle tree from the persisted recipient secrets
+    ///
+    /// Leaves are taken in stored order, so the result is deterministic and
+    /// must equal `merkle_root` for proofs to be accepted on-chain.
+    pub fn merkle_tree(&self) -> Result<MerkleTree, CampaignTreeError> {
+        let depth = self.checked_tree_depth()?;
+
+        let leaves = self
+            .recipients
+            .iter()
+            .map(|r| {
+                let wallet = recipient_wallet(r)?;
+                let amount = recipient_amount(r)?;
+                let secret = r
+                    .secret
+                    .ok_or_else(|| CampaignTreeError::MissingSecret(r.wallet.clone()))?;
+                Ok((wallet, amount, secret))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(MerkleTree::from_recipients(&leaves, depth))
+    }
+
+    /// Build the tree `claim` and `claim_token` check allocations against
+    ///
+    /// Same depth and leaf order as `merkle_tree`, but SHA-256 leaves of
+    /// `(wallet, amount)` only, so no secrets are needed.
+    pub fn claim_tree(&self) -> Result<MerkleTree, CampaignTreeError> {
+        let depth = self.checked_tree_depth()?;
+
+        let leaves = self
+            .recipients
+            .iter()
+            .map(|r| Ok((recipient_wallet(r)?, recipient_amount(r)?)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(MerkleTree::claim_tree(&leaves, depth))
+    }
+
+    /// The tree depth, if valid and deep enough for every recipient
+    fn checked_tree_depth(&self) -> Result<usize, CampaignTreeError> {
+        let depth = usize::try_from(self.tree_depth)
+            .ok()
+            .filter(|d| (1..=MAX_TREE_DEPTH).contains(d))
+            .ok_or(CampaignTreeError::InvalidDepth(self.tree_depth))?;
+
+        if self.recipients.len() > max_leaves(depth) {
+            return Err(CampaignTreeError::TooManyRecipients {
+                count: self.recipients.len(),
+                depth,
+            });
+        }
+        Ok(depth)
+    }
+
+    /// Rebuild the merkle tree and check it against the persisted root
+    pub fn verified_merkle_tree(&self) -> Result<MerkleTree, CampaignTreeError> {
+        let tree = self.merkle_tree()?;
+        let computed = hex::encode(tree.root());
+        let expected = normalize_hex(&self.merkle_root);
+
+        if computed != expected {
+            return Err(CampaignTreeError::RootMismatch { expected, computed });
+        }
+        Ok(tree)
+    }
+}
+
+fn recipient_wallet(recipient: &Recipient) -> Result<String, CampaignTreeError> {
+    match parse_pubkey(&recipient.wallet) {
+        Some(_) => Ok(recipient.wallet.clone()),
+        None => Err(CampaignTreeError::InvalidWallet(recipient.wallet.clone())),
+    }
+}
+
+fn recipient_amount(recipient: &Recipient) -> Result<u64, CampaignTreeError> {
+    recipient
+        .amount
+        .parse::<u64>()
+        .map_err(|_| CampaignTreeError::InvalidAmount(recipient.wallet.clone()))
+}
+
+/// Normalize a hex string for comparison (no 0x prefix, lowercase)
+pub fn normalize_hex(value: &str) -> String {
+    value.strip_prefix("0x").unwrap_or(value).to_lowercase()
+}
+
+/// Errors rebuilding a campaign's merkle tree
+#[derive(Debug, thiserror::Error)]
+pub enum CampaignTreeError {
+    #[error("Invalid tree depth {0} (must be 1..={MAX_TREE_DEPTH})")]
+    InvalidDepth(i16),
+
+    #[error("{count} recipients do not fit in a tree of depth {depth}")]
+    TooManyRecipients { count: usize, depth: usize },
+
+    #[error("Recipient {0} is not a base58 public key")]
+    InvalidWallet(String),
+
+    #[error("Invalid amount for recipient {0}")]
+    InvalidAmount(String),
+
+    #[error("No stored secret for recipient {0}")]
+    MissingSecret(String),
+
+    #[error("Merkle root mismatch: expected {expected}, computed {computed}")]
+    RootMismatch { expected: String, computed: String },
 }
 
 /// Response for campaign info (without recipient list for privacy)
@@ -44,6 +211,8 @@ pub struct CampaignInfo {
     pub id: String,
     pub address: String,
     pub name: String,
+    pub merkle_root: String,
+    pub tree_depth: u8,
     pub total_amount: String,
     pub total_recipients: usize,
     pub claimed_count: usize,
@@ -51,6 +220,8 @@ pub struct CampaignInfo {
     pub vault_address: Option<String>, // PDA vault address for claims
     pub tx_signature: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub airdrop_type: String,
+    pub closed_at: Option<DateTime<Utc>>,
     // Token fields (None = SOL campaign)
     pub token_mint: Option<String>,
     pub token_symbol: Option<String>,
@@ -63,6 +234,8 @@ impl From<&Campaign> for CampaignInfo {
             id: campaign.id.clone(),
             address: campaign.address.clone(),
             name: campaign.name.clone(),
+            merkle_root: campaign.merkle_root.clone(),
+            tree_depth: campaign.tree_depth as u8,
             total_amount: campaign.total_amount.clone(),
             total_recipients: campaign.recipients.len(),
             claimed_count: campaign.recipients.iter().filter(|r| r.claimed).count(),
@@ -70,6 +243,8 @@ impl From<&Campaign> for CampaignInfo {
             vault_address: campaign.vault_address.clone(),
             tx_signature: campaign.tx_signature.clone(),
             created_at: campaign.created_at,
+            airdrop_type: campaign.airdrop_type.clone(),
+            closed_at: campaign.closed_at,
             token_mint: campaign.token_mint.clone(),
             token_symbol: campaign.token_symbol.clone(),
             token_decimals: campaign.token_decimals.map(|d| d as u8),
@@ -95,220 +270,9 @@ pub struct EligibleCampaign {
     pub total_recipients: usize,
     pub vault_address: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub airdrop_type: String,
+    pub closed_at: Option<DateTime<Utc>>,
     pub token_mint: Option<String>,
     pub token_symbol: Option<String>,
     pub token_decimals: Option<u8>,
 }
-
-/// Persistent campaign store using PostgreSQL
-#[derive(Debug, Clone)]
-pub struct CampaignStore {
-    db: PgPool,
-}
-
-impl CampaignStore {
-    pub fn new(db: PgPool) -> Self {
-        Self { db }
-    }
-
-    /// Create a new campaign
-    pub async fn create(&self, campaign: Campaign) -> Campaign {
-        let mut tx = self.db.begin().await.expect("Failed to begin transaction");
-
-        // Insert campaign
-        sqlx::query!(
-            r#"
-            INSERT INTO campaigns (
-                id, address, name, merkle_root, total_amount, creator_wallet, 
-                tx_signature, vault_address, created_at, airdrop_type, 
-                vesting_start, vesting_cliff_seconds, vesting_duration_seconds, 
-                token_mint, token_symbol, token_decimals
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
-            "#,
-            campaign.id,
-            campaign.address,
-            campaign.name,
-            campaign.merkle_root,
-            campaign.total_amount,
-            campaign.creator_wallet,
-            campaign.tx_signature,
-            campaign.vault_address,
-            campaign.created_at,
-            campaign.airdrop_type,
-            campaign.vesting_start,
-            campaign.vesting_cliff_seconds,
-            campaign.vesting_duration_seconds,
-            campaign.token_mint,
-            campaign.token_symbol,
-            campaign.token_decimals
-        )
-        .execute(&mut *tx)
-        .await
-        .expect("Failed to insert campaign");
-
-        // Insert recipients
-        for r in &campaign.recipients {
-            sqlx::query!(
-                r#"
-                INSERT INTO recipients (campaign_address, wallet, amount, claimed, claimed_at)
-                VALUES ($1, $2, $3, $4, $5)
-                "#,
-                campaign.address,
-                r.wallet,
-                r.amount, // String
-                r.claimed,
-                r.claimed_at
-            )
-            .execute(&mut *tx)
-            .await
-            .expect("Failed to insert recipient");
-        }
-
-        tx.commit().await.expect("Failed to commit transaction");
-
-        campaign
-    }
-
-    /// Get a campaign by address
-    pub async fn get(&self, address: &str) -> Option<Campaign> {
-        let rec = sqlx::query!(
-            "SELECT * FROM campaigns WHERE address = $1",
-            address
-        )
-        .fetch_optional(&self.db)
-        .await
-        .ok()??;
-
-        // Fetch recipients
-        let recipients = sqlx::query_as!(
-            Recipient,
-            r#"
-            SELECT id, wallet, amount, claimed, claimed_at 
-            FROM recipients 
-            WHERE campaign_address = $1
-            "#,
-            address
-        )
-        .fetch_all(&self.db)
-        .await
-        .unwrap_or_default();
-
-        Some(Campaign {
-            id: rec.id,
-            address: rec.address,
-            name: rec.name,
-            merkle_root: rec.merkle_root,
-            total_amount: rec.total_amount,
-            creator_wallet: rec.creator_wallet,
-            tx_signature: rec.tx_signature,
-            vault_address: rec.vault_address,
-            created_at: rec.created_at,
-            recipients,
-            airdrop_type: rec.airdrop_type,
-            vesting_start: rec.vesting_start,
-            vesting_cliff_seconds: rec.vesting_cliff_seconds,
-            vesting_duration_seconds: rec.vesting_duration_seconds,
-            token_mint: rec.token_mint,
-            token_symbol: rec.token_symbol,
-            token_decimals: rec.token_decimals, // Now i16 in query, structure expects i16
-        })
-    }
-
-    /// Get all campaigns for a wallet
-    pub async fn get_by_wallet(&self, wallet: &str) -> Vec<Campaign> {
-        let campaigns_recs = sqlx::query!(
-            "SELECT address FROM campaigns WHERE creator_wallet = $1 ORDER BY created_at DESC",
-            wallet
-        )
-        .fetch_all(&self.db)
-        .await
-        .unwrap_or_default();
-
-        let mut results = Vec::new();
-        for crec in campaigns_recs {
-            if let Some(c) = self.get(&crec.address).await {
-                results.push(c);
-            }
-        }
-        results
-    }
-
-    /// Check eligibility for a wallet in a campaign
-    pub async fn check_eligibility(&self, address: &str, wallet: &str) -> Option<EligibilityResponse> {
-        let recipient = sqlx::query!(
-            "SELECT amount, claimed FROM recipients WHERE campaign_address = $1 AND wallet = $2",
-            address,
-            wallet
-        )
-        .fetch_optional(&self.db)
-        .await
-        .ok()?;
-
-        Some(match recipient {
-            Some(r) => EligibilityResponse {
-                eligible: !r.claimed,
-                amount: Some(r.amount), // String
-                already_claimed: r.claimed,
-            },
-            None => EligibilityResponse {
-                eligible: false,
-                amount: None,
-                already_claimed: false,
-            },
-        })
-    }
-
-    /// Mark a recipient as claimed
-    pub async fn mark_claimed(&self, address: &str, wallet: &str) -> bool {
-        let result = sqlx::query!(
-            r#"
-            UPDATE recipients 
-            SET claimed = true, claimed_at = $1 
-            WHERE campaign_address = $2 AND wallet = $3 AND claimed = false
-            "#,
-            Utc::now(),
-            address,
-            wallet
-        )
-        .execute(&self.db)
-        .await;
-
-        match result {
-            Ok(res) => res.rows_affected() > 0,
-            Err(_) => false,
-        }
-    }
-
-    /// Get all campaigns where the wallet is eligible to claim
-    pub async fn get_eligible_for_wallet(&self, wallet: &str) -> Vec<EligibleCampaign> {
-        let rows = sqlx::query!(
-            r#"
-            SELECT c.address, c.name, c.total_amount, c.vault_address, c.created_at, 
-                   c.token_mint, c.token_symbol, c.token_decimals,
-                   r.amount,
-                   (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address) as total_recipients
-            FROM campaigns c
-            JOIN recipients r ON c.address = r.campaign_address
-            WHERE r.wallet = $1 AND r.claimed = false
-            "#,
-            wallet
-        )
-        .fetch_all(&self.db)
-        .await
-        .unwrap_or_default();
-
-        rows.into_iter().map(|row| EligibleCampaign {
-            address: row.address,
-            name: row.name,
-            amount: row.amount,
-            total_amount: row.total_amount,
-            total_recipients: row.total_recipients.unwrap_or(0) as usize,
-            vault_address: row.vault_address,
-            created_at: row.created_at,
-            token_mint: row.token_mint,
-            token_symbol: row.token_symbol,
-            token_decimals: row.token_decimals.map(|d| d as u8),
-        }).collect()
-    }
-}
@@ -0,0 +1,368 @@
+//! Campaign listings: filters, sort order and cursor pagination
+//!
+//! Listings are paged by keyset: a cursor names the last item of the
+//! previous page by its sort key and address (the tie-breaker), so pages
+//! stay stable while campaigns are created. Cursors are opaque to clients
+//! and only valid for the sort they were issued under.
+
+use std::cmp::Ordering;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Campaign, CampaignInfo, EligibleCampaign};
+
+/// Page size when the client asks for none
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// Largest page a client can ask for
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Field a listing is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignSort {
+    #[default]
+    CreatedAt,
+    Name,
+    TotalAmount,
+}
+
+impl CampaignSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "created_at" => Some(Self::CreatedAt),
+            "name" => Some(Self::Name),
+            "total_amount" => Some(Self::TotalAmount),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "asc" => Some(Self::Asc),
+            "desc" => Some(Self::Desc),
+            _ => None,
+        }
+    }
+}
+
+/// What a campaign pays out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asset {
+    Sol,
+    Token,
+}
+
+impl Asset {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sol" => Some(Self::Sol),
+            "token" => Some(Self::Token),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the program has closed a campaign
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CampaignStatus {
+    Active,
+    Closed,
+}
+
+impl CampaignStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(Self::Active),
+            "closed" => Some(Self::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// Campaigns a listing is restricted to; `None` matches anything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CampaignFilter {
+    pub asset: Option<Asset>,
+    pub airdrop_type: Option<String>,
+    pub status: Option<CampaignStatus>,
+}
+
+impl CampaignFilter {
+    pub fn matches(&self, campaign: &Campaign) -> bool {
+        let asset = match campaign.token_mint {
+            Some(_) => Asset::Token,
+            None => Asset::Sol,
+        };
+        let status = match campaign.closed_at {
+            Some(_) => CampaignStatus::Closed,
+            None => CampaignStatus::Active,
+        };
+
+        self.asset.is_none_or(|a| a == asset)
+            && self
+                .airdrop_type
+                .as_ref()
+                .is_none_or(|t| *t == campaign.airdrop_type)
+            && self.status.is_none_or(|s| s == status)
+    }
+}
+
+/// One page of a campaign listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CampaignListing {
+    pub filter: CampaignFilter,
+    pub sort: CampaignSort,
+    pub order: SortOrder,
+    /// Page size, 1..=MAX_PAGE_SIZE
+    pub limit: usize,
+    /// Last item of the previous page
+    pub after: Option<Cursor>,
+}
+
+impl Default for CampaignListing {
+    fn default() -> Self {
+        Self {
+            filter: CampaignFilter::default(),
+            sort: CampaignSort::default(),
+            order: SortOrder::default(),
+            limit: DEFAULT_PAGE_SIZE,
+            after: None,
+        }
+    }
+}
+
+impl CampaignListing {
+    /// Order of two items in this listing
+    pub fn compare(&self, a: (&SortKey, &str), b: (&SortKey, &str)) -> Ordering {
+        match self.order {
+            SortOrder::Asc => a.cmp(&b),
+            SortOrder::Desc => b.cmp(&a),
+        }
+    }
+
+    /// Whether an item comes after the cursor, i.e. belongs on this page or
+    /// a later one
+    pub fn is_after_cursor(&self, key: &SortKey, address: &str) -> bool {
+        self.after.as_ref().is_none_or(|cursor| {
+            self.compare((key, address), (&cursor.key, &cursor.address)) == Ordering::Greater
+        })
+    }
+}
+
+/// Position in a listing: the sort key and address of the last item seen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub sort: CampaignSort,
+    pub order: SortOrder,
+    pub key: SortKey,
+    pub address: String,
+}
+
+/// Wire form of a cursor, before base64
+#[derive(Serialize, Deserialize)]
+struct CursorToken {
+    sort: CampaignSort,
+    order: SortOrder,
+    key: String,
+    address: String,
+}
+
+impl Cursor {
+    /// Opaque, URL-safe form handed to clients
+    pub fn encode(&self) -> String {
+        let token = CursorToken {
+            sort: self.sort,
+            order: self.order,
+            key: self.key.to_string(),
+            address: self.address.clone(),
+        };
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&token).expect("cursor serializes"))
+    }
+
+    /// Parse a cursor from `encode`; `None` if it is malformed
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+        let token: CursorToken = serde_json::from_slice(&bytes).ok()?;
+        Some(Self {
+            sort: token.sort,
+            order: token.order,
+            key: SortKey::parse(token.sort, &token.key)?,
+            address: token.address,
+        })
+    }
+}
+
+/// Value of the sort field of an item
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    CreatedAt(DateTime<Utc>),
+    Name(String),
+    /// Raw integer amount; compared numerically, like `::NUMERIC` in SQL
+    TotalAmount(u128),
+}
+
+impl SortKey {
+    fn new(sort: CampaignSort, created_at: DateTime<Utc>, name: &str, total_amount: &str) -> Self {
+        match sort {
+            CampaignSort::CreatedAt => Self::CreatedAt(created_at),
+            CampaignSort::Name => Self::Name(name.to_string()),
+            CampaignSort::TotalAmount => Self::TotalAmount(total_amount.parse().unwrap_or(0)),
+        }
+    }
+
+    fn parse(sort: CampaignSort, value: &str) -> Option<Self> {
+        Some(match sort {
+            CampaignSort::CreatedAt => {
+                Self::CreatedAt(DateTime::parse_from_rfc3339(value).ok()?.to_utc())
+            }
+            CampaignSort::Name => Self::Name(value.to_string()),
+            CampaignSort::TotalAmount => Self::TotalAmount(value.parse().ok()?),
+        })
+    }
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CreatedAt(t) => f.write_str(&t.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            Self::Name(name) => f.write_str(name),
+            Self::TotalAmount(amount) => write!(f, "{}", amount),
+        }
+    }
+}
+
+/// An item of a campaign listing
+pub trait Listed {
+    fn sort_key(&self, sort: CampaignSort) -> SortKey;
+    fn address(&self) -> &str;
+}
+
+impl Listed for Campaign {
+    fn sort_key(&self, sort: CampaignSort) -> SortKey {
+        SortKey::new(sort, self.created_at, &self.name, &self.total_amount)
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+impl Listed for CampaignInfo {
+    fn sort_key(&self, sort: CampaignSort) -> SortKey {
+        SortKey::new(sort, self.created_at, &self.name, &self.total_amount)
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+impl Listed for EligibleCampaign {
+    fn sort_key(&self, sort: CampaignSort) -> SortKey {
+        SortKey::new(sort, self.created_at, &self.name, &self.total_amount)
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+/// A page of listed items
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor of the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T: Listed> Page<T> {
+    /// A page from the items after the listing's cursor, in listing order
+    ///
+    /// Backends fetch up to `limit + 1` items: the extra one only tells
+    /// that there is a next page.
+    pub fn from_items(mut items: Vec<T>, listing: &CampaignListing) -> Self {
+        let next_cursor = if items.len() > listing.limit {
+            items.truncate(listing.limit);
+            items.last().map(|last| {
+                Cursor {
+                    sort: listing.sort,
+                    order: listing.order,
+                    key: last.sort_key(listing.sort),
+                    address: last.address().to_string(),
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+        Self { items, next_cursor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let created_at = DateTime::parse_from_rfc3339("2024-02-08T10:00:00.123456Z")
+            .unwrap()
+            .to_utc();
+        for key in [
+            SortKey::CreatedAt(created_at),
+            SortKey::Name("Airdrop, \"season\" 2".to_string()),
+            SortKey::TotalAmount(u64::MAX as u128),
+        ] {
+            let sort = match key {
+                SortKey::CreatedAt(_) => CampaignSort::CreatedAt,
+                SortKey::Name(_) => CampaignSort::Name,
+                SortKey::TotalAmount(_) => CampaignSort::TotalAmount,
+            };
+            let cursor = Cursor {
+                sort,
+                order: SortOrder::Asc,
+                key,
+                address: "Campaign111".to_string(),
+            };
+            assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        }
+
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("{}")), None);
+    }
+
+    #[test]
+    fn test_listing_order_breaks_ties_by_address() {
+        let listing = CampaignListing {
+            sort: CampaignSort::TotalAmount,
+            after: Some(Cursor {
+                sort: CampaignSort::TotalAmount,
+                order: SortOrder::Desc,
+                key: SortKey::TotalAmount(100),
+                address: "B".to_string(),
+            }),
+            ..CampaignListing::default()
+        };
+
+        let key = |amount| SortKey::TotalAmount(amount);
+        assert!(!listing.is_after_cursor(&key(200), "A"));
+        assert!(!listing.is_after_cursor(&key(100), "C"));
+        assert!(!listing.is_after_cursor(&key(100), "B"));
+        assert!(listing.is_after_cursor(&key(100), "A"));
+        // Numeric, not lexicographic
+        assert!(listing.is_after_cursor(&key(99), "Z"));
+    }
+}
@@ -0,0 +1,207 @@
+//! In-memory campaign storage for tests and offline development
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use super::{CampaignListing, CampaignRepository, Listed, Page, StoreError};
+use crate::models::{Campaign, CampaignInfo, EligibilityResponse, EligibleCampaign, Recipient};
+
+/// `CampaignRepository` keeping campaigns in memory, secrets in the clear
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryCampaignRepository {
+    /// In creation order
+    campaigns: Arc<RwLock<Vec<Campaign>>>,
+    /// Last recipient id handed out (ids are global, like a `SERIAL`)
+    last_recipient_id: Arc<RwLock<i32>>,
+}
+
+impl InMemoryCampaignRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CampaignRepository for InMemoryCampaignRepository {
+    async fn create(&self, mut campaign: Campaign) -> Result<Campaign, StoreError> {
+        let mut campaigns = self.campaigns.write().unwrap();
+        if campaigns.iter().any(|c| c.address == campaign.address) {
+            return Err(StoreError::Conflict(format!(
+                "Campaign {} already exists",
+                campaign.address
+            )));
+        }
+        let mut wallets = HashSet::new();
+        if let Some(r) = campaign
+            .recipients
+            .iter()
+            .find(|r| !wallets.insert(&r.wallet))
+        {
+            return Err(StoreError::Conflict(format!(
+                "Recipient {} is listed twice",
+                r.wallet
+            )));
+        }
+
+        let mut last_id = self.last_recipient_id.write().unwrap();
+        for r in &mut campaign.recipients {
+            *last_id += 1;
+            r.id = Some(*last_id);
+        }
+        campaigns.push(campaign.clone());
+        Ok(campaign)
+    }
+
+    async fn get(&self, address: &str) -> Result<Campaign, StoreError> {
+        self.campaigns
+            .read()
+            .unwrap()
+            .iter()
+            .find(|c| c.address == address)
+            .cloned()
+            .ok_or(StoreError::NotFound("Campaign"))
+    }
+
+    async fn get_recipient(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<(Campaign, Recipient), StoreError> {
+        let mut campaign = self.get(address).await?;
+        let recipients = std::mem::take(&mut campaign.recipients);
+        let recipient = recipients
+            .into_iter()
+            .find(|r| r.wallet == wallet)
+            .ok_or(StoreError::NotFound("Recipient"))?;
+        Ok((
+            campaign,
+            Recipient {
+                secret: None,
+                ..recipient
+            },
+        ))
+    }
+
+    async fn list_by_creator(
+        &self,
+        wallet: &str,
+        listing: &CampaignListing,
+    ) -> Result<Page<CampaignInfo>, StoreError> {
+        let campaigns = self.campaigns.read().unwrap();
+        let items = list(&campaigns, listing, |c| {
+            (c.creator_wallet == wallet).then(|| CampaignInfo::from(c))
+        });
+        Ok(Page::from_items(items, listing))
+    }
+
+    async fn check_eligibility(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<EligibilityResponse, StoreError> {
+        let campaigns = self.campaigns.read().unwrap();
+        let recipient = campaigns
+            .iter()
+            .filter(|c| c.address == address)
+            .flat_map(|c| &c.recipients)
+            .find(|r| r.wallet == wallet);
+
+        Ok(match recipient {
+            Some(r) => EligibilityResponse {
+                eligible: !r.fully_claimed(),
+                amount: Some(r.amount.clone()),
+                already_claimed: r.fully_claimed(),
+            },
+            None => EligibilityResponse {
+                eligible: false,
+                amount: None,
+                already_claimed: false,
+            },
+        })
+    }
+
+    async fn mark_claimed(
+        &self,
+        address: &str,
+        wallet: &str,
+        paid: Option<&str>,
+    ) -> Result<bool, StoreError> {
+        let mut campaigns = self.campaigns.write().unwrap();
+        let recipient = campaigns
+            .iter_mut()
+            .filter(|c| c.address == address)
+            .flat_map(|c| &mut c.recipients)
+            .find(|r| r.wallet == wallet && !r.fully_claimed());
+
+        Ok(match recipient {
+            Some(r) => {
+                r.claimed = true;
+                r.claimed_at = Some(Utc::now());
+                if let Some(paid) = paid {
+                    r.claimed_amount = paid.to_string();
+                }
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn list_eligible(
+        &self,
+        wallet: &str,
+        listing: &CampaignListing,
+    ) -> Result<Page<EligibleCampaign>, StoreError> {
+        let campaigns = self.campaigns.read().unwrap();
+        let items = list(&campaigns, listing, |c| {
+            let r = c
+                .recipients
+                .iter()
+                .find(|r| r.wallet == wallet && !r.fully_claimed())?;
+            Some(EligibleCampaign {
+                address: c.address.clone(),
+                name: c.name.clone(),
+                amount: r.amount.clone(),
+                total_amount: c.total_amount.clone(),
+                total_recipients: c.recipients.len(),
+                vault_address: c.vault_address.clone(),
+                created_at: c.created_at,
+                airdrop_type: c.airdrop_type.clone(),
+                closed_at: c.closed_at,
+                token_mint: c.token_mint.clone(),
+                token_symbol: c.token_symbol.clone(),
+                token_decimals: c.token_decimals.map(|d| d as u8),
+            })
+        });
+        Ok(Page::from_items(items, listing))
+    }
+}
+
+/// Up to `limit + 1` items of the listing, from the campaigns `item` maps
+fn list<T>(
+    campaigns: &[Campaign],
+    listing: &CampaignListing,
+    item: impl Fn(&Campaign) -> Option<T>,
+) -> Vec<T> {
+    let mut matching: Vec<(&Campaign, T)> = campaigns
+        .iter()
+        .filter(|c| listing.filter.matches(c))
+        .filter(|c| listing.is_after_cursor(&c.sort_key(listing.sort), &c.address))
+        .filter_map(|c| Some((c, item(c)?)))
+        .collect();
+    matching.sort_by(|(a, _), (b, _)| {
+        listing.compare(
+            (&a.sort_key(listing.sort), &a.address),
+            (&b.sort_key(listing.sort), &b.address),
+        )
+    });
+    matching
+        .into_iter()
+        .take(listing.limit + 1)
+        .map(|(_, item)| item)
+        .collect()
+}
@@ -0,0 +1,155 @@
+//! Campaign storage
+//!
+//! Handlers read and write campaigns through the [`CampaignRepository`]
+//! trait: [`PgCampaignRepository`] stores them in PostgreSQL,
+//! [`InMemoryCampaignRepository`] keeps them in memory for tests and
+//! offline development.
+
+mod listing;
+mod memory;
+mod postgres;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+
+use crate::{
+    common::{response::ApiErrorResponse, secrets::SecretError},
+    models::{Campaign, CampaignInfo, EligibilityResponse, EligibleCampaign, Recipient},
+};
+
+pub use listing::{
+    Asset, CampaignFilter, CampaignListing, CampaignSort, CampaignStatus, Cursor, DEFAULT_PAGE_SIZE,
+    Listed, MAX_PAGE_SIZE, Page, SortKey, SortOrder,
+};
+pub use memory::InMemoryCampaignRepository;
+pub use postgres::PgCampaignRepository;
+
+/// Campaign and recipient storage
+#[async_trait]
+pub trait CampaignRepository: Send + Sync {
+    /// Create a new campaign
+    ///
+    /// Fails with `Conflict` if the address is taken or a wallet is listed
+    /// twice; nothing is stored then.
+    async fn create(&self, campaign: Campaign) -> Result<Campaign, StoreError>;
+
+    /// Get a campaign by address, with its recipients in leaf order
+    async fn get(&self, address: &str) -> Result<Campaign, StoreError>;
+
+    /// Get one recipient of a campaign, and the campaign without its
+    /// recipient list
+    ///
+    /// The recipient's secret is not loaded.
+    async fn get_recipient(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<(Campaign, Recipient), StoreError>;
+
+    /// List a page of the campaigns created by a wallet
+    async fn list_by_creator(
+        &self,
+        wallet: &str,
+        listing: &CampaignListing,
+    ) -> Result<Page<CampaignInfo>, StoreError>;
+
+    /// Check eligibility for a wallet in a campaign
+    async fn check_eligibility(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<EligibilityResponse, StoreError>;
+
+    /// Mark a recipient as claimed, recording `paid` as its claimed amount
+    /// when the claim is known to have paid that much
+    ///
+    /// Returns false if the wallet is not a recipient with something left
+    /// to claim.
+    async fn mark_claimed(
+        &self,
+        address: &str,
+        wallet: &str,
+        paid: Option<&str>,
+    ) -> Result<bool, StoreError>;
+
+    /// List a page of the campaigns where the wallet is eligible to claim
+    async fn list_eligible(
+        &self,
+        wallet: &str,
+        listing: &CampaignListing,
+    ) -> Result<Page<EligibleCampaign>, StoreError>;
+}
+
+/// Campaign storage errors
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("{0} not found")]
+    NotFound(&'static str),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    /// The database could not be reached (connection, pool exhausted)
+    #[error("Database unavailable: {0}")]
+    Unavailable(#[source] sqlx::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[source] sqlx::Error),
+
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => StoreError::Conflict(
+                format!("Conflicts with an existing record ({})", db.constraint().unwrap_or("unique")),
+            ),
+            sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_) => StoreError::Unavailable(e),
+            e => StoreError::Database(e),
+        }
+    }
+}
+
+impl From<StoreError> for ApiErrorResponse {
+    fn from(e: StoreError) -> Self {
+        match e {
+            StoreError::NotFound(_) => ApiErrorResponse::default()
+                .with_code(StatusCode::NOT_FOUND)
+                .with_message(&e.to_string()),
+            StoreError::Conflict(_) => ApiErrorResponse::default()
+                .with_code(StatusCode::CONFLICT)
+                .with_message(&e.to_string()),
+            StoreError::Unavailable(ref err) => {
+                tracing::error!("Database unavailable: {}", err);
+                ApiErrorResponse::default()
+                    .with_code(StatusCode::SERVICE_UNAVAILABLE)
+                    .with_message("Database unavailable, try again later")
+            }
+            e => {
+                tracing::error!("Campaign store error: {}", e);
+                ApiErrorResponse::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_error_status_codes() {
+        let status = |e: StoreError| ApiErrorResponse::from(e).errors.code;
+
+        assert_eq!(status(StoreError::NotFound("Campaign")), 404);
+        assert_eq!(status(StoreError::Conflict("Campaign x already exists".into())), 409);
+        assert_eq!(status(sqlx::Error::PoolTimedOut.into()), 503);
+        assert_eq!(status(sqlx::Error::RowNotFound.into()), 500);
+        assert_eq!(status(SecretError::Encrypt.into()), 500);
+    }
+}
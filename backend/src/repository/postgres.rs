@@ -0,0 +1,480 @@
+//! PostgreSQL campaign storage
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+
+use super::{
+    Asset, CampaignListing, CampaignRepository, CampaignSort, CampaignStatus, Page, SortKey,
+    SortOrder, StoreError,
+};
+use crate::{
+    common::secrets::SecretCipher,
+    models::{
+        Campaign, CampaignInfo, EligibilityResponse, EligibleCampaign, Recipient, fully_claimed,
+    },
+};
+
+/// Recipients inserted per statement; each batch binds one array per column
+const RECIPIENT_BATCH_SIZE: usize = 10_000;
+
+/// `CampaignRepository` in PostgreSQL, with recipient secrets encrypted
+#[derive(Debug, Clone)]
+pub struct PgCampaignRepository {
+    db: PgPool,
+    cipher: SecretCipher,
+}
+
+impl PgCampaignRepository {
+    pub fn new(db: PgPool, cipher: SecretCipher) -> Self {
+        Self { db, cipher }
+    }
+
+    /// A campaign without its recipients
+    async fn get_campaign_row(&self, address: &str) -> Result<Campaign, StoreError> {
+        let rec = sqlx::query!(
+            "SELECT * FROM campaigns WHERE address = $1",
+            address
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(StoreError::NotFound("Campaign"))?;
+
+        Ok(Campaign {
+            id: rec.id,
+            address: rec.address,
+            name: rec.name,
+            merkle_root: rec.merkle_root,
+            tree_depth: rec.tree_depth,
+            total_amount: rec.total_amount,
+            creator_wallet: rec.creator_wallet,
+            tx_signature: rec.tx_signature,
+            vault_address: rec.vault_address,
+            created_at: rec.created_at,
+            recipients: Vec::new(),
+            airdrop_type: rec.airdrop_type,
+            vesting_start: rec.vesting_start,
+            vesting_cliff_seconds: rec.vesting_cliff_seconds,
+            vesting_duration_seconds: rec.vesting_duration_seconds,
+            vesting_schedule: serde_json::from_value(rec.vesting_schedule)
+                .map_err(|e| sqlx::Error::Decode(e.into()))?,
+            token_mint: rec.token_mint,
+            token_symbol: rec.token_symbol,
+            token_decimals: rec.token_decimals, // Now i16 in query, structure expects i16
+            closed_at: rec.closed_at,
+        })
+    }
+}
+
+#[async_trait]
+impl CampaignRepository for PgCampaignRepository {
+    async fn create(&self, campaign: Campaign) -> Result<Campaign, StoreError> {
+        let mut wallets = HashSet::with_capacity(campaign.recipients.len());
+        if let Some(r) = campaign.recipients.iter().find(|r| !wallets.insert(&r.wallet)) {
+            return Err(StoreError::Conflict(format!(
+                "Recipient {} is listed twice",
+                r.wallet
+            )));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        // Insert campaign
+        sqlx::query!(
+            r#"
+            INSERT INTO campaigns (
+                id, address, name, merkle_root, total_amount, creator_wallet, 
+                tx_signature, vault_address, created_at, airdrop_type, 
+                vesting_start, vesting_cliff_seconds, vesting_duration_seconds, 
+                token_mint, token_symbol, token_decimals, tree_depth, vesting_schedule
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                    $18)
+            "#,
+            campaign.id,
+            campaign.address,
+            campaign.name,
+            campaign.merkle_root,
+            campaign.total_amount,
+            campaign.creator_wallet,
+            campaign.tx_signature,
+            campaign.vault_address,
+            campaign.created_at,
+            campaign.airdrop_type,
+            campaign.vesting_start,
+            campaign.vesting_cliff_seconds,
+            campaign.vesting_duration_seconds,
+            campaign.token_mint,
+            campaign.token_symbol,
+            campaign.token_decimals,
+            campaign.tree_depth,
+            sqlx::types::Json(&campaign.vesting_schedule) as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match StoreError::from(e) {
+            StoreError::Conflict(_) => {
+                StoreError::Conflict(format!("Campaign {} already exists", campaign.address))
+            }
+            e => e,
+        })?;
+
+        // Insert recipients in batches, in leaf order so `id` preserves tree
+        // position
+        for batch in campaign.recipients.chunks(RECIPIENT_BATCH_SIZE) {
+            let mut wallets = Vec::with_capacity(batch.len());
+            let mut amounts = Vec::with_capacity(batch.len());
+            let mut claimed = Vec::with_capacity(batch.len());
+            let mut claimed_at = Vec::with_capacity(batch.len());
+            let mut secret_ciphertexts = Vec::with_capacity(batch.len());
+            for r in batch {
+                wallets.push(r.wallet.clone());
+                amounts.push(r.amount.clone());
+                claimed.push(r.claimed);
+                claimed_at.push(r.claimed_at);
+                secret_ciphertexts.push(
+                    r.secret
+                        .map(|secret| self.cipher.encrypt(&secret))
+                        .transpose()?,
+                );
+            }
+
+            sqlx::query!(
+                r#"
+                INSERT INTO recipients (campaign_address, wallet, amount, claimed, claimed_at, secret_ciphertext)
+                SELECT $1, wallet, amount, claimed, claimed_at, secret_ciphertext
+                FROM UNNEST($2::TEXT[], $3::TEXT[], $4::BOOL[], $5::TIMESTAMPTZ[], $6::TEXT[])
+                    WITH ORDINALITY AS r(wallet, amount, claimed, claimed_at, secret_ciphertext, position)
+                ORDER BY position
+                "#,
+                campaign.address,
+                &wallets,
+                &amounts,
+                &claimed,
+                &claimed_at as &[Option<DateTime<Utc>>],
+                &secret_ciphertexts as &[Option<String>]
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(campaign)
+    }
+
+    async fn get(&self, address: &str) -> Result<Campaign, StoreError> {
+        let mut campaign = self.get_campaign_row(address).await?;
+
+        // Fetch recipients in leaf order
+        campaign.recipients = sqlx::query!(
+            r#"
+            SELECT id, wallet, amount, claimed, claimed_at, claimed_amount, claim_tx_signature,
+                   secret_ciphertext
+            FROM recipients 
+            WHERE campaign_address = $1
+            ORDER BY id
+            "#,
+            address
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(|r| Recipient {
+            id: Some(r.id),
+            wallet: r.wallet,
+            amount: r.amount,
+            claimed: r.claimed,
+            claimed_at: r.claimed_at,
+            claimed_amount: r.claimed_amount,
+            claim_tx_signature: r.claim_tx_signature,
+            secret: r.secret_ciphertext.and_then(|c| match self.cipher.decrypt(&c) {
+                Ok(secret) => Some(secret),
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to decrypt recipient secret");
+                    None
+                }
+            }),
+        })
+        .collect();
+
+        Ok(campaign)
+    }
+
+    async fn get_recipient(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<(Campaign, Recipient), StoreError> {
+        let campaign = self.get_campaign_row(address).await?;
+        let recipient = sqlx::query!(
+            r#"
+            SELECT id, wallet, amount, claimed, claimed_at, claimed_amount, claim_tx_signature
+            FROM recipients
+            WHERE campaign_address = $1 AND wallet = $2
+            "#,
+            address,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(StoreError::NotFound("Recipient"))?;
+
+        Ok((
+            campaign,
+            Recipient {
+                id: Some(recipient.id),
+                wallet: recipient.wallet,
+                amount: recipient.amount,
+                claimed: recipient.claimed,
+                claimed_at: recipient.claimed_at,
+                claimed_amount: recipient.claimed_amount,
+                claim_tx_signature: recipient.claim_tx_signature,
+                secret: None,
+            },
+        ))
+    }
+
+    async fn list_by_creator(
+        &self,
+        wallet: &str,
+        listing: &CampaignListing,
+    ) -> Result<Page<CampaignInfo>, StoreError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT c.id, c.address, c.name, c.merkle_root, c.tree_depth, c.total_amount,
+                   c.creator_wallet, c.vault_address, c.tx_signature, c.created_at,
+                   c.airdrop_type, c.closed_at, c.token_mint, c.token_symbol, c.token_decimals,
+                   (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address)
+                       AS total_recipients,
+                   (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address AND claimed)
+                       AS claimed_count
+            FROM campaigns c
+            WHERE c.creator_wallet = "#,
+        );
+        query.push_bind(wallet);
+        push_listing(&mut query, listing);
+
+        let rows: Vec<CampaignInfoRow> = query.build_query_as().fetch_all(&self.db).await?;
+        let items = rows.into_iter().map(CampaignInfo::from).collect();
+        Ok(Page::from_items(items, listing))
+    }
+
+    async fn check_eligibility(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Result<EligibilityResponse, StoreError> {
+        let recipient = sqlx::query!(
+            r#"
+            SELECT amount, claimed_amount
+            FROM recipients
+            WHERE campaign_address = $1 AND wallet = $2
+            "#,
+            address,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(match recipient {
+            Some(r) => {
+                let done = fully_claimed(&r.amount, &r.claimed_amount);
+                EligibilityResponse {
+                    eligible: !done,
+                    amount: Some(r.amount), // String
+                    already_claimed: done,
+                }
+            }
+            None => EligibilityResponse {
+                eligible: false,
+                amount: None,
+                already_claimed: false,
+            },
+        })
+    }
+
+    async fn mark_claimed(
+        &self,
+        address: &str,
+        wallet: &str,
+        paid: Option<&str>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE recipients
+            SET claimed = true, claimed_at = $1, claimed_amount = COALESCE($4, claimed_amount)
+            WHERE campaign_address = $2 AND wallet = $3
+              AND claimed_amount::NUMERIC < amount::NUMERIC
+            "#,
+            Utc::now(),
+            address,
+            wallet,
+            paid
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_eligible(
+        &self,
+        wallet: &str,
+        listing: &CampaignListing,
+    ) -> Result<Page<EligibleCampaign>, StoreError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT c.address, c.name, c.total_amount, c.vault_address, c.created_at,
+                   c.airdrop_type, c.closed_at, c.token_mint, c.token_symbol, c.token_decimals,
+                   r.amount,
+                   (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address)
+                       AS total_recipients
+            FROM campaigns c
+            JOIN recipients r ON c.address = r.campaign_address
+            WHERE r.claimed_amount::NUMERIC < r.amount::NUMERIC
+              AND r.wallet = "#,
+        );
+        query.push_bind(wallet);
+        push_listing(&mut query, listing);
+
+        let rows: Vec<EligibleCampaignRow> = query.build_query_as().fetch_all(&self.db).await?;
+        let items = rows.into_iter().map(EligibleCampaign::from).collect();
+        Ok(Page::from_items(items, listing))
+    }
+}
+
+/// Append the listing's filters, cursor, order and limit to a query over
+/// `campaigns c` whose WHERE clause is open
+///
+/// Fetches `limit + 1` rows so `Page` can tell whether a next page exists.
+fn push_listing(query: &mut QueryBuilder<'_, Postgres>, listing: &CampaignListing) {
+    let filter = &listing.filter;
+    match filter.asset {
+        Some(Asset::Sol) => query.push(" AND c.token_mint IS NULL"),
+        Some(Asset::Token) => query.push(" AND c.token_mint IS NOT NULL"),
+        None => query,
+    };
+    if let Some(airdrop_type) = &filter.airdrop_type {
+        query
+            .push(" AND c.airdrop_type = ")
+            .push_bind(airdrop_type.clone());
+    }
+    match filter.status {
+        Some(CampaignStatus::Active) => query.push(" AND c.closed_at IS NULL"),
+        Some(CampaignStatus::Closed) => query.push(" AND c.closed_at IS NOT NULL"),
+        None => query,
+    };
+
+    // Names compare bytewise, amounts numerically, as `SortKey` does
+    let column = match listing.sort {
+        CampaignSort::CreatedAt => "c.created_at",
+        CampaignSort::Name => r#"c.name COLLATE "C""#,
+        CampaignSort::TotalAmount => "c.total_amount::NUMERIC",
+    };
+    let direction = match listing.order {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+
+    if let Some(cursor) = &listing.after {
+        let comparison = match listing.order {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        };
+        query.push(format_args!(" AND ({column}, c.address) {comparison} ("));
+        match &cursor.key {
+            SortKey::CreatedAt(created_at) => query.push_bind(*created_at),
+            SortKey::Name(name) => query.push_bind(name.clone()),
+            SortKey::TotalAmount(amount) => query.push_bind(amount.to_string()).push("::NUMERIC"),
+        };
+        query.push(", ").push_bind(cursor.address.clone()).push(")");
+    }
+
+    query.push(format_args!(
+        " ORDER BY {column} {direction}, c.address {direction} LIMIT "
+    ));
+    query.push_bind((listing.limit + 1) as i64);
+}
+
+#[derive(FromRow)]
+struct CampaignInfoRow {
+    id: String,
+    address: String,
+    name: String,
+    merkle_root: String,
+    tree_depth: i16,
+    total_amount: String,
+    creator_wallet: String,
+    vault_address: Option<String>,
+    tx_signature: Option<String>,
+    created_at: DateTime<Utc>,
+    airdrop_type: String,
+    closed_at: Option<DateTime<Utc>>,
+    token_mint: Option<String>,
+    token_symbol: Option<String>,
+    token_decimals: Option<i16>,
+    total_recipients: i64,
+    claimed_count: i64,
+}
+
+impl From<CampaignInfoRow> for CampaignInfo {
+    fn from(row: CampaignInfoRow) -> Self {
+        CampaignInfo {
+            id: row.id,
+            address: row.address,
+            name: row.name,
+            merkle_root: row.merkle_root,
+            tree_depth: row.tree_depth as u8,
+            total_amount: row.total_amount,
+            total_recipients: row.total_recipients as usize,
+            claimed_count: row.claimed_count as usize,
+            creator_wallet: row.creator_wallet,
+            vault_address: row.vault_address,
+            tx_signature: row.tx_signature,
+            created_at: row.created_at,
+            airdrop_type: row.airdrop_type,
+            closed_at: row.closed_at,
+            token_mint: row.token_mint,
+            token_symbol: row.token_symbol,
+            token_decimals: row.token_decimals.map(|d| d as u8),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct EligibleCampaignRow {
+    address: String,
+    name: String,
+    amount: String,
+    total_amount: String,
+    total_recipients: i64,
+    vault_address: Option<String>,
+    created_at: DateTime<Utc>,
+    airdrop_type: String,
+    closed_at: Option<DateTime<Utc>>,
+    token_mint: Option<String>,
+    token_symbol: Option<String>,
+    token_decimals: Option<i16>,
+}
+
+impl From<EligibleCampaignRow> for EligibleCampaign {
+    fn from(row: EligibleCampaignRow) -> Self {
+        EligibleCampaign {
+            address: row.address,
+            name: row.name,
+            amount: row.amount,
+            total_amount: row.total_amount,
+            total_recipients: row.total_recipients as usize,
+            vault_address: row.vault_address,
+            created_at: row.created_at,
+            airdrop_type: row.airdrop_type,
+            closed_at: row.closed_at,
+            token_mint: row.token_mint,
+            token_symbol: row.token_symbol,
+            token_decimals: row.token_decimals.map(|d| d as u8),
+        }
+    }
+}
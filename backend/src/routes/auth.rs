@@ -0,0 +1,59 @@
+//! Wallet Sign-In Routes
+
+use axum::{Json, Router, extract::State, routing::post};
+use serde::Deserialize;
+
+use crate::{
+    auth::{Challenge, Session},
+    common::response::{ApiResponse, ApiSuccessResponse},
+    state::AppState,
+};
+
+/// Request body for a sign-in challenge
+#[derive(Debug, Deserialize)]
+pub struct NonceRequest {
+    pub wallet: String,
+}
+
+/// Request body for signing in with a signed challenge
+#[derive(Debug, Deserialize)]
+pub struct VerifyRequest {
+    pub wallet: String,
+    pub nonce: String,
+    /// ed25519 signature of the challenge message (base58)
+    pub signature: String,
+}
+
+/// Build auth routes
+pub fn auth_routes() -> Router<AppState> {
+    Router::new()
+        .route("/nonce", post(create_nonce))
+        .route("/verify", post(verify))
+}
+
+/// POST /api/v1/auth/nonce - Get a message for the wallet to sign
+async fn create_nonce(
+    State(state): State<AppState>,
+    Json(body): Json<NonceRequest>,
+) -> ApiResponse<Challenge> {
+    let challenge = state.auth.challenge(&body.wallet).await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(challenge)
+        .with_message("Sign this message with your wallet"))
+}
+
+/// POST /api/v1/auth/verify - Trade a signed challenge for a session token
+async fn verify(
+    State(state): State<AppState>,
+    Json(body): Json<VerifyRequest>,
+) -> ApiResponse<Session> {
+    let session = state
+        .auth
+        .sign_in(&body.wallet, &body.nonce, &body.signature)
+        .await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(session)
+        .with_message("Signed in"))
+}
@@ -1,6 +1,9 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{
+        DefaultBodyLimit, Multipart, Path, Query, State,
+        multipart::{Field, MultipartError},
+    },
     http::StatusCode,
     routing::{get, post},
 };
@@ -8,9 +11,23 @@ use chrono::Utc;
 use serde::Deserialize;
 
 use crate::{
-    common::response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
-    models::{Campaign, CampaignInfo, EligibilityResponse, EligibleCampaign, Recipient},
+    auth::AuthSession,
+    chain::{Registration, fetch_campaign},
+    common::{
+        merkle::{DEFAULT_TREE_DEPTH, MerkleTree, generate_secret, parse_field_hex},
+        response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
+    },
+    import::{MAX_IMPORT_BYTES, RecipientFormat, RecipientImport, max_import_bytes},
+    models::{
+        Campaign, CampaignInfo, EligibilityResponse, EligibleCampaign, Recipient, normalize_hex,
+    },
+    repository::Page,
     state::AppState,
+    validation::{
+        ValidationErrors, validate_campaign_fields, validate_campaign_listing,
+        validate_create_campaign,
+    },
+    vesting::{VestingSchedule, VestingStatus, vesting_status},
 };
 
 /// Request body for creating a campaign
@@ -18,11 +35,19 @@ use crate::{
 pub struct CreateCampaignRequest {
     pub address: String,
     pub name: String,
-    pub merkle_root: String,
+    /// Root the client registered on-chain; must match the tree built from
+    /// the recipient secrets. Omit to let the server compute it.
+    #[serde(default)]
+    pub merkle_root: Option<String>,
+    /// Merkle tree depth (defaults to 8, i.e. up to 256 recipients)
+    #[serde(default)]
+    pub tree_depth: Option<u8>,
     pub total_amount: String,
     pub creator_wallet: String,
     pub tx_signature: Option<String>,
     pub vault_address: Option<String>, // PDA vault address for claims
+    /// Left out by `POST /campaigns/import`, which takes a file instead
+    #[serde(default)]
     pub recipients: Vec<RecipientInput>,
     // Vesting fields (optional, defaults to instant)
     #[serde(default)]
@@ -33,6 +58,9 @@ pub struct CreateCampaignRequest {
     pub vesting_cliff_seconds: Option<i64>,
     #[serde(default)]
     pub vesting_duration_seconds: Option<i64>,
+    /// Shape of the vesting curve (default linear)
+    #[serde(default)]
+    pub vesting_schedule: Option<VestingSchedule>,
     // Token fields (optional, None = SOL campaign)
     #[serde(default)]
     pub token_mint: Option<String>,
@@ -46,6 +74,10 @@ pub struct CreateCampaignRequest {
 pub struct RecipientInput {
     pub wallet: String,
     pub amount: String,
+    /// Claim secret used in this recipient's leaf (hex field element).
+    /// Generated server-side when omitted.
+    #[serde(default)]
+    pub secret: Option<String>,
 }
 
 /// Request body for checking eligibility
@@ -60,56 +92,261 @@ pub struct MarkClaimedRequest {
     pub wallet: String,
 }
 
+/// Query string of the campaign listings
+///
+/// Values are checked by `validate_campaign_listing`, so bad ones get
+/// per-field errors.
+#[derive(Debug, Default, Deserialize)]
+pub struct ListCampaignsQuery {
+    /// Page size (default 20, at most 100)
+    pub limit: Option<String>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// `created_at` (default), `name` or `total_amount`
+    pub sort: Option<String>,
+    /// `asc` or `desc` (default)
+    pub order: Option<String>,
+    /// `sol` or `token`
+    pub asset: Option<String>,
+    pub airdrop_type: Option<String>,
+    /// `active` or `closed`
+    pub status: Option<String>,
+}
+
 /// Build campaign routes
 pub fn campaign_routes() -> Router<AppState> {
     Router::new()
         .route("/", post(create_campaign))
+        .route(
+            "/import",
+            post(import_campaign).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
         .route("/eligible/{wallet}", get(get_eligible_campaigns))
         .route("/{address}", get(get_campaign))
         .route("/{address}/check", post(check_eligibility))
         .route("/{address}/claim", post(mark_claimed))
+        .route("/{address}/vesting/{wallet}", get(get_vesting_status))
         .route("/wallet/{wallet}", get(get_campaigns_by_wallet))
 }
 
 /// POST /api/v1/campaigns - Create a new campaign
+///
+/// Requires a session for `creator_wallet`, and the campaign account must
+/// already be confirmed on chain with the submitted root, authority, total
+/// and mint.
 async fn create_campaign(
     State(state): State<AppState>,
-    Json(body): Json<CreateCampaignRequest>,
+    session: AuthSession,
+    Json(mut body): Json<CreateCampaignRequest>,
 ) -> ApiResponse<CampaignInfo> {
+    session.require_wallet(&body.creator_wallet)?;
+    validate_create_campaign(&body)?;
+
+    // Secrets are fixed here, once, so the tree can be rebuilt for every proof
+    let recipients = std::mem::take(&mut body.recipients)
+        .into_iter()
+        .map(|r| {
+            // Validated above
+            let secret = r
+                .secret
+                .as_deref()
+                .and_then(parse_field_hex)
+                .unwrap_or_else(generate_secret);
+            Recipient::new(r.wallet, r.amount, secret)
+        })
+        .collect();
+
+    let merkle_root = body.merkle_root.take();
+    let campaign = new_campaign(body, recipients);
+    let tree = match campaign.merkle_tree() {
+        Ok(tree) => tree,
+        Err(e) => {
+            return Err(ApiErrorResponse::default()
+                .with_code(StatusCode::BAD_REQUEST)
+                .with_message("Invalid recipient list")
+                .with_details(e.to_string()));
+        }
+    };
+
+    register_campaign(&state, campaign, &tree, merkle_root).await
+}
+
+/// POST /api/v1/campaigns/import - Create a campaign from an uploaded
+/// recipient list
+///
+/// A multipart form: a `campaign` part with the `POST /campaigns` body
+/// minus `recipients`, then a `recipients` file in CSV or NDJSON (see
+/// `crate::import`). The file is checked and hashed into the merkle tree
+/// while it uploads; session and on-chain checks are the same.
+async fn import_campaign(
+    State(state): State<AppState>,
+    session: AuthSession,
+    mut multipart: Multipart,
+) -> ApiResponse<CampaignInfo> {
+    let part = next_part(&mut multipart, "campaign").await?;
+    let bytes = part.bytes().await.map_err(multipart_error)?;
+    let mut body: CreateCampaignRequest = serde_json::from_slice(&bytes).map_err(|e| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Invalid campaign part")
+            .with_details(e.to_string())
+    })?;
+    session.require_wallet(&body.creator_wallet)?;
+
+    let mut errors = ValidationErrors::default();
+    if !body.recipients.is_empty() {
+        errors.add("recipients", None, "must be uploaded as the recipients part");
+    }
+    let limits = validate_campaign_fields(&body, &mut errors);
+
+    let mut part = next_part(&mut multipart, "recipients").await?;
+    let format = RecipientFormat::detect(part.content_type(), part.file_name()).ok_or_else(|| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+            .with_message("Recipients must be CSV or NDJSON")
+            .with_details(
+                "Send text/csv or application/x-ndjson, or name the file .csv, .ndjson or .jsonl"
+                    .to_string(),
+            )
+    })?;
+
+    // Rows are held until the list is complete, so stop reading past what
+    // the tree can hold
+    let max_bytes = limits.depth.map_or(MAX_IMPORT_BYTES, max_import_bytes);
+    let mut read = 0;
+    let mut import = RecipientImport::new(format, limits);
+    while let Some(chunk) = part.chunk().await.map_err(multipart_error)? {
+        read += chunk.len();
+        if read > max_bytes {
+            return Err(ApiErrorResponse::default()
+                .with_code(StatusCode::PAYLOAD_TOO_LARGE)
+                .with_message("Recipient list too large")
+                .with_details(format!(
+                    "at most {} bytes for this tree depth",
+                    max_bytes
+                )));
+        }
+        import.push(&chunk);
+    }
+    let imported = match import.finish() {
+        Ok(imported) if errors.is_empty() => imported,
+        Ok(_) => return Err(errors.into()),
+        Err(row_errors) => {
+            errors.0.extend(row_errors.0);
+            return Err(errors.into());
+        }
+    };
+
+    let merkle_root = body.merkle_root.take();
+    let campaign = new_campaign(body, imported.recipients);
+    register_campaign(&state, campaign, &imported.tree, merkle_root).await
+}
+
+/// The next multipart part, which must be called `name`
+async fn next_part<'a>(
+    multipart: &'a mut Multipart,
+    name: &str,
+) -> Result<Field<'a>, ApiErrorResponse> {
+    match multipart.next_field().await.map_err(multipart_error)? {
+        Some(part) if part.name() == Some(name) => Ok(part),
+        _ => Err(ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message(&format!("Expected a `{}` part", name))),
+    }
+}
+
+fn multipart_error(e: MultipartError) -> ApiErrorResponse {
+    ApiErrorResponse::default()
+        .with_code(e.status())
+        .with_message("Invalid multipart upload")
+        .with_details(e.body_text())
+}
+
+/// A campaign for a validated request, with the given recipients in place
+/// of the request's
+fn new_campaign(body: CreateCampaignRequest, recipients: Vec<Recipient>) -> Campaign {
     let now = Utc::now().timestamp();
-    let campaign = Campaign {
+    Campaign {
         id: uuid_simple(),
         address: body.address,
         name: body.name,
-        merkle_root: body.merkle_root,
+        merkle_root: String::new(),
+        tree_depth: body.tree_depth.map_or(DEFAULT_TREE_DEPTH as i16, i16::from),
         total_amount: body.total_amount,
         creator_wallet: body.creator_wallet,
         tx_signature: body.tx_signature,
         vault_address: body.vault_address, // Store vault PDA address
         created_at: Utc::now(),
-        recipients: body
-            .recipients
-            .into_iter()
-            .map(|r| Recipient {
-                wallet: r.wallet,
-                amount: r.amount,
-                claimed: false,
-                claimed_at: None,
-                id: None,
-            })
-            .collect(),
+        recipients,
         // Vesting fields with defaults
         airdrop_type: body.airdrop_type.unwrap_or_else(|| "instant".to_string()),
         vesting_start: body.vesting_start.unwrap_or(now),
         vesting_cliff_seconds: body.vesting_cliff_seconds.unwrap_or(0),
         vesting_duration_seconds: body.vesting_duration_seconds.unwrap_or(0),
+        vesting_schedule: body.vesting_schedule.unwrap_or_default(),
         // Token fields
         token_mint: body.token_mint,
         token_symbol: body.token_symbol,
         token_decimals: body.token_decimals.map(|d| d as i16),
-    };
+        closed_at: None,
+    }
+}
+
+/// Store a new campaign once its tree matches the submitted root and the
+/// campaign account on chain
+async fn register_campaign(
+    state: &AppState,
+    mut campaign: Campaign,
+    tree: &MerkleTree,
+    merkle_root: Option<String>,
+) -> ApiResponse<CampaignInfo> {
+    let computed_root = hex::encode(tree.root());
+
+    if let Some(merkle_root) = merkle_root
+        && normalize_hex(&merkle_root) != computed_root
+    {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("merkle_root does not match the recipient list and secrets")
+            .with_details(format!("expected {}, computed {}", merkle_root, computed_root)));
+    }
+    campaign.merkle_root = computed_root;
+    let claim_tree = campaign.claim_tree().map_err(|e| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Invalid recipient list")
+            .with_details(e.to_string())
+    })?;
+
+    let total_amount = campaign.total_amount.parse::<u64>().map_err(|_| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("total_amount must be an integer amount in base units")
+    })?;
+    let onchain = fetch_campaign(
+        state.chain.as_ref(),
+        &state.config.chain.program_id,
+        &campaign.address,
+    )
+    .await?;
+    Registration {
+        authority: &campaign.creator_wallet,
+        merkle_root: &tree.root(),
+        tree_depth: tree.depth() as u8,
+        claim_root: &claim_tree.root(),
+        total_amount,
+        token_mint: campaign.token_mint.as_deref(),
+    }
+    .check(&onchain)?;
 
-    let created = state.campaign_store.create(campaign).await;
+    // Claims vest by the schedule on chain (a zero start there means the
+    // creation time), so report that one
+    campaign.vesting_start = onchain.vesting_start;
+    campaign.vesting_cliff_seconds = onchain.vesting_cliff;
+    campaign.vesting_duration_seconds = onchain.vesting_duration;
+    campaign.vesting_schedule = onchain.vesting_schedule.into();
+
+    let created = state.campaign_store.create(campaign).await?;
     let info = CampaignInfo::from(&created);
 
     Ok(ApiSuccessResponse::default()
@@ -123,14 +360,11 @@ async fn get_campaign(
     State(state): State<AppState>,
     Path(address): Path<String>,
 ) -> ApiResponse<CampaignInfo> {
-    match state.campaign_store.get(&address).await {
-        Some(campaign) => Ok(ApiSuccessResponse::default()
-            .with_data(CampaignInfo::from(&campaign))
-            .with_message("Campaign found")),
-        None => Err(ApiErrorResponse::default()
-            .with_code(StatusCode::NOT_FOUND)
-            .with_message("Campaign not found")),
-    }
+    let campaign = state.campaign_store.get(&address).await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(CampaignInfo::from(&campaign))
+        .with_message("Campaign found"))
 }
 
 /// POST /api/v1/campaigns/:address/check - Check eligibility
@@ -139,23 +373,39 @@ async fn check_eligibility(
     Path(address): Path<String>,
     Json(body): Json<CheckEligibilityRequest>,
 ) -> ApiResponse<EligibilityResponse> {
-    match state.campaign_store.check_eligibility(&address, &body.wallet).await {
-        Some(eligibility) => Ok(ApiSuccessResponse::default()
-            .with_data(eligibility)
-            .with_message("Eligibility checked")),
-        None => Err(ApiErrorResponse::default()
-            .with_code(StatusCode::NOT_FOUND)
-            .with_message("Campaign not found")),
-    }
+    let eligibility = state
+        .campaign_store
+        .check_eligibility(&address, &body.wallet)
+        .await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(eligibility)
+        .with_message("Eligibility checked"))
 }
 
 /// POST /api/v1/campaigns/:address/claim - Mark as claimed
+///
+/// Requires a session for the claiming wallet. Without vesting a claim pays
+/// the whole allocation, so it is recorded as paid; vested claims pay what
+/// vested so far, which the indexer reconciles from the chain.
 async fn mark_claimed(
     State(state): State<AppState>,
+    session: AuthSession,
     Path(address): Path<String>,
     Json(body): Json<MarkClaimedRequest>,
 ) -> ApiResponse<()> {
-    if state.campaign_store.mark_claimed(&address, &body.wallet).await {
+    session.require_wallet(&body.wallet)?;
+    let (campaign, recipient) = state
+        .campaign_store
+        .get_recipient(&address, &body.wallet)
+        .await?;
+    let paid = (campaign.vesting_duration_seconds == 0).then_some(recipient.amount.as_str());
+
+    if state
+        .campaign_store
+        .mark_claimed(&address, &body.wallet, paid)
+        .await?
+    {
         Ok(ApiSuccessResponse::default()
             .with_message("Claimed successfully"))
     } else {
@@ -165,12 +415,29 @@ async fn mark_claimed(
     }
 }
 
+/// GET /api/v1/campaigns/:address/vesting/:wallet - Get a recipient's vesting status
+async fn get_vesting_status(
+    State(state): State<AppState>,
+    Path((address, wallet)): Path<(String, String)>,
+) -> ApiResponse<VestingStatus> {
+    let (campaign, recipient) = state.campaign_store.get_recipient(&address, &wallet).await?;
+    let status = vesting_status(&campaign, &recipient, Utc::now().timestamp())?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(status)
+        .with_message("Vesting status retrieved"))
+}
+
 /// GET /api/v1/campaigns/eligible/:wallet - Get campaigns where wallet is eligible
+///
+/// Paginated, see `ListCampaignsQuery`.
 async fn get_eligible_campaigns(
     State(state): State<AppState>,
     Path(wallet): Path<String>,
-) -> ApiResponse<Vec<EligibleCampaign>> {
-    let campaigns = state.campaign_store.get_eligible_for_wallet(&wallet).await;
+    Query(query): Query<ListCampaignsQuery>,
+) -> ApiResponse<Page<EligibleCampaign>> {
+    let listing = validate_campaign_listing(&query)?;
+    let campaigns = state.campaign_store.list_eligible(&wallet, &listing).await?;
 
     Ok(ApiSuccessResponse::default()
         .with_data(campaigns)
@@ -178,15 +445,18 @@ async fn get_eligible_campaigns(
 }
 
 /// GET /api/v1/campaigns/wallet/:wallet - Get campaigns by creator wallet
+///
+/// Paginated, see `ListCampaignsQuery`.
 async fn get_campaigns_by_wallet(
     State(state): State<AppState>,
     Path(wallet): Path<String>,
-) -> ApiResponse<Vec<CampaignInfo>> {
-    let campaigns = state.campaign_store.get_by_wallet(&wallet).await;
-    let infos: Vec<CampaignInfo> = campaigns.iter().map(CampaignInfo::from).collect();
+    Query(query): Query<ListCampaignsQuery>,
+) -> ApiResponse<Page<CampaignInfo>> {
+    let listing = validate_campaign_listing(&query)?;
+    let campaigns = state.campaign_store.list_by_creator(&wallet, &listing).await?;
 
     Ok(ApiSuccessResponse::default()
-        .with_data(infos)
+        .with_data(campaigns)
         .with_message("Campaigns retrieved"))
 }
 
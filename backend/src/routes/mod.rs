@@ -2,13 +2,15 @@ use axum::Router;
 
 use crate::state::AppState;
 
-mod campaigns;
+mod auth;
+pub(crate) mod campaigns;
 mod proofs;
-mod zk_proofs;
+pub(crate) mod zk_proofs;
 mod hash;
 
 pub fn app_routes(state: AppState) -> Router {
     let api_routes = Router::new()
+        .nest("/auth", auth::auth_routes())
         .nest("/campaigns", campaigns::campaign_routes())
         .nest("/proofs", proofs::proof_routes())
         .nest("/zk-proofs", zk_proofs::zk_proof_routes())
@@ -9,10 +9,12 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    auth::AuthSession,
     common::{
-        merkle::{compute_nullifier, generate_secret, MerkleTree},
+        merkle::{MerkleProof, MerkleScheme, compute_nullifier, parse_field_hex},
         response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
     },
+    models::{CampaignTreeError, normalize_hex},
     state::AppState,
 };
 
@@ -29,37 +31,76 @@ pub struct ProofResponse {
     pub merkle_root: String,
     /// Nullifier hash (32 bytes hex)
     pub nullifier_hash: String,
+    /// Leaf hash (32 bytes hex)
+    pub leaf: String,
     /// Leaf index in tree
     pub leaf_index: usize,
     /// Merkle proof path (array of 32-byte hashes)
     pub merkle_path: Vec<String>,
     /// Claim amount in SOL
     pub amount: String,
-    /// Secret for this claim (should be stored securely by user)
+    /// Secret committed to in this recipient's leaf (keep private)
     pub secret: String,
 }
 
+/// Allocation proof for the `claim` and `claim_token` instructions
+#[derive(Debug, Serialize)]
+pub struct ClaimProofResponse {
+    /// Root of the allocation tree stored on chain (32 bytes hex)
+    pub claim_root: String,
+    /// sha256(0x00 || claimer || amount LE) (32 bytes hex)
+    pub leaf: String,
+    /// Leaf index in tree
+    pub leaf_index: usize,
+    /// Sibling hashes, leaf level first (32 bytes hex each)
+    pub proof: Vec<String>,
+    /// Allocation the program pays vesting on (raw integer string)
+    pub amount: String,
+}
+
+/// Request body for verifying a merkle proof
+#[derive(Debug, Deserialize)]
+pub struct VerifyProofRequest {
+    /// Leaf hash (32 bytes hex)
+    pub leaf: String,
+    /// Leaf index in tree
+    pub leaf_index: usize,
+    /// Merkle proof path (array of 32-byte hashes, leaf level first)
+    pub merkle_path: Vec<String>,
+}
+
+/// Result of verifying a merkle proof against a campaign
+#[derive(Debug, Serialize)]
+pub struct VerifyProofResponse {
+    /// Whether the recomputed root equals the campaign's stored root
+    pub valid: bool,
+    /// Root recomputed from the submitted leaf and path (32 bytes hex)
+    pub computed_root: String,
+    /// Root stored for the campaign (32 bytes hex)
+    pub merkle_root: String,
+}
+
 /// Build proof routes
 pub fn proof_routes() -> Router<AppState> {
     Router::new()
         .route("/{address}/generate", post(generate_proof))
+        .route("/{address}/claim", post(generate_claim_proof))
+        .route("/{address}/verify", post(verify_proof))
 }
 
 /// POST /api/v1/proofs/:address/generate - Generate ZK proof for claim
+///
+/// Requires a session for `wallet`: the response carries its secret.
 async fn generate_proof(
     State(state): State<AppState>,
+    session: AuthSession,
     Path(address): Path<String>,
     Json(body): Json<GenerateProofRequest>,
 ) -> ApiResponse<ProofResponse> {
+    session.require_wallet(&body.wallet)?;
+
     // Get campaign
-    let campaign = match state.campaign_store.get(&address).await {
-        Some(c) => c,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Campaign not found"))
-        }
-    };
+    let campaign = state.campaign_store.get(&address).await?;
 
     // Find recipient
     let recipient = match campaign.recipients.iter().find(|r| r.wallet == body.wallet) {
@@ -71,39 +112,23 @@ async fn generate_proof(
         }
     };
 
-    // Check if already claimed
-    if recipient.claimed {
+    // Check if already claimed (vested allocations take several claims)
+    if recipient.fully_claimed() {
         return Err(ApiErrorResponse::default()
             .with_code(StatusCode::BAD_REQUEST)
             .with_message("Already claimed"));
     }
 
-    // Generate secret for this claim (in real app, this would be stored per-user)
-    let secret = generate_secret();
-
-    // Build merkle tree with secrets
-    let recipients_with_secrets: Vec<(String, u64, [u8; 32])> = campaign
-        .recipients
-        .iter()
-        .map(|r| {
-            // For demo: use deterministic secret based on wallet
-            // In production: secrets would be stored per-recipient
-            let mut recipient_secret = [0u8; 32];
-            if r.wallet == body.wallet {
-                recipient_secret = secret;
-            } else {
-                // Use wallet hash as placeholder secret for other recipients
-                let wallet_bytes = r.wallet.as_bytes();
-                for (i, b) in wallet_bytes.iter().enumerate() {
-                    recipient_secret[i % 32] ^= *b;
-                }
-            }
-            let amount_u64 = r.amount.parse::<u64>().unwrap_or(0);
-            (r.wallet.clone(), amount_u64, recipient_secret)
-        })
-        .collect();
-
-    let tree = MerkleTree::from_recipients(&recipients_with_secrets);
+    // Rebuild the tree from the persisted secrets
+    let tree = campaign.verified_merkle_tree().map_err(tree_error)?;
+    let secret = match recipient.secret {
+        Some(s) => s,
+        None => {
+            return Err(ApiErrorResponse::default()
+                .with_code(StatusCode::CONFLICT)
+                .with_message("No stored secret for this recipient"))
+        }
+    };
 
     // Get proof for wallet
     let proof = match tree.get_proof(&body.wallet) {
@@ -122,12 +147,13 @@ async fn generate_proof(
     let merkle_root_hex = hex::encode(tree.root());
     let nullifier_hex = hex::encode(nullifier);
     let secret_hex = hex::encode(secret);
-    let merkle_path: Vec<String> = proof.siblings.iter().map(|s| hex::encode(s)).collect();
+    let merkle_path: Vec<String> = proof.siblings.iter().map(hex::encode).collect();
 
     Ok(ApiSuccessResponse::default()
         .with_data(ProofResponse {
             merkle_root: merkle_root_hex,
             nullifier_hash: nullifier_hex,
+            leaf: hex::encode(proof.leaf),
             leaf_index: proof.leaf_index,
             merkle_path,
             amount: recipient.amount.clone(),
@@ -135,3 +161,133 @@ async fn generate_proof(
         })
         .with_message("Proof generated successfully"))
 }
+
+/// POST /api/v1/proofs/:address/claim - Allocation proof for a `claim` or
+/// `claim_token` transaction
+///
+/// The program pays the amount in the proven leaf, so this is all the
+/// claimer needs to send; no secret is involved.
+async fn generate_claim_proof(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Json(body): Json<GenerateProofRequest>,
+) -> ApiResponse<ClaimProofResponse> {
+    let campaign = state.campaign_store.get(&address).await?;
+
+    let recipient = match campaign.recipients.iter().find(|r| r.wallet == body.wallet) {
+        Some(r) => r,
+        None => {
+            return Err(ApiErrorResponse::default()
+                .with_code(StatusCode::NOT_FOUND)
+                .with_message("Wallet not found in campaign recipients"))
+        }
+    };
+    if recipient.fully_claimed() {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Already claimed"));
+    }
+
+    let tree = campaign.claim_tree().map_err(tree_error)?;
+    let proof = match tree.get_proof(&body.wallet) {
+        Some(p) => p,
+        None => {
+            return Err(ApiErrorResponse::default()
+                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
+                .with_message("Failed to generate proof"))
+        }
+    };
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(ClaimProofResponse {
+            claim_root: hex::encode(tree.root()),
+            leaf: hex::encode(proof.leaf),
+            leaf_index: proof.leaf_index,
+            proof: proof.siblings.iter().map(hex::encode).collect(),
+            amount: recipient.amount.clone(),
+        })
+        .with_message("Proof generated successfully"))
+}
+
+/// POST /api/v1/proofs/:address/verify - Check a merkle proof against the campaign root
+///
+/// Off-chain debugging aid for failed claims; no secrets are needed.
+async fn verify_proof(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Json(body): Json<VerifyProofRequest>,
+) -> ApiResponse<VerifyProofResponse> {
+    let campaign = state.campaign_store.get(&address).await?;
+
+    let depth = campaign.tree_depth as usize;
+    if body.merkle_path.len() != depth {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Merkle path length does not match campaign tree depth")
+            .with_details(format!(
+                "merkle_path has {} entries, tree depth is {}",
+                body.merkle_path.len(),
+                depth
+            )));
+    }
+    if body.leaf_index >= 1 << depth {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Leaf index out of range for campaign tree depth"));
+    }
+
+    let leaf = parse_field_hex(&body.leaf).ok_or_else(|| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Invalid leaf hash")
+    })?;
+    let siblings = body
+        .merkle_path
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            parse_field_hex(s).ok_or_else(|| {
+                ApiErrorResponse::default()
+                    .with_code(StatusCode::BAD_REQUEST)
+                    .with_message("Invalid merkle path entry")
+                    .with_details(format!("merkle_path[{}]", i))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let proof = MerkleProof {
+        leaf_index: body.leaf_index,
+        siblings,
+        leaf,
+        scheme: MerkleScheme::Poseidon,
+    };
+    let computed_root = hex::encode(proof.compute_root());
+    let merkle_root = normalize_hex(&campaign.merkle_root);
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(VerifyProofResponse {
+            valid: computed_root == merkle_root,
+            computed_root,
+            merkle_root,
+        })
+        .with_message("Proof verified"))
+}
+
+/// Map a tree rebuild failure to an API error
+///
+/// A root mismatch or missing secret means proofs for this campaign would be
+/// rejected on-chain, so the caller gets an explicit conflict instead.
+pub(super) fn tree_error(e: CampaignTreeError) -> ApiErrorResponse {
+    let code = match e {
+        CampaignTreeError::RootMismatch { .. } | CampaignTreeError::MissingSecret(_) => {
+            StatusCode::CONFLICT
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    tracing::error!(error = %e, "Failed to rebuild campaign merkle tree");
+
+    ApiErrorResponse::default()
+        .with_code(code)
+        .with_message("Failed to rebuild campaign merkle tree")
+        .with_details(e.to_string())
+}
@@ -1,21 +1,27 @@
-//! ZK Proof Generation Routes (Sunspot)
+//! ZK Proof Generation Routes
 
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use shadow_drop_wire::{PublicWitness, amount_field, recipient_field};
+use uuid::Uuid;
 
 use crate::{
+    auth::AuthSession,
     common::{
-        merkle::{compute_nullifier, generate_secret, MerkleTree},
+        merkle::{compute_nullifier, parse_pubkey},
         response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
     },
+    config::ProverMode,
+    jobs::ProofJob,
+    repository::StoreError,
+    routes::proofs::tree_error,
     state::AppState,
-    zk::{SunspotProver, ZkProofInput},
+    zk::{GROTH16_PROOF_SIZE, ProverError, ZkProofInput},
 };
 
 /// Request for ZK proof generation
@@ -25,11 +31,12 @@ pub struct GenerateZkProofRequest {
 }
 
 /// Response with ZK proof data
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZkProofResponse {
-    /// Groth16 proof (hex encoded, 256 bytes)
+    /// Groth16 proof (hex encoded, `GROTH16_PROOF_SIZE` bytes)
     pub groth16_proof: String,
-    /// Public inputs (hex encoded, 96 bytes)
+    /// Public witness: header, then merkle_root, nullifier_hash, recipient
+    /// and amount (hex encoded, `PUBLIC_INPUTS_SIZE` bytes)
     pub public_inputs: String,
     /// Nullifier hash for Light Protocol (hex)
     pub nullifier_hash: String,
@@ -43,93 +50,234 @@ pub struct ZkProofResponse {
     pub merkle_root: String,
     /// Leaf index
     pub leaf_index: usize,
+    /// Placeholder proof from `PROVER_MODE=mock`; not verifiable on-chain
+    #[serde(default)]
+    pub mock: bool,
 }
 
 /// Build ZK proof routes
 pub fn zk_proof_routes() -> Router<AppState> {
-    Router::new().route("/{address}/generate", post(generate_zk_proof))
+    Router::new()
+        .route("/{address}/generate", post(generate_zk_proof))
+        .route("/{address}/jobs", post(submit_zk_proof_job))
+        .route("/jobs/{id}", get(get_zk_proof_job))
 }
 
-/// POST /api/v1/zk-proofs/:address/generate - Generate Sunspot ZK proof for claim
+/// Why a ZK proof could not be produced
+#[derive(Debug)]
+pub(crate) enum ZkProofError {
+    /// The claim itself is invalid (unknown campaign or wallet, already claimed, ...)
+    Rejected(ApiErrorResponse),
+    /// The prover failed
+    Prover(ProverError),
+    /// The campaign could not be loaded
+    Store(StoreError),
+}
+
+impl From<StoreError> for ZkProofError {
+    fn from(e: StoreError) -> Self {
+        Self::Store(e)
+    }
+}
+
+impl std::fmt::Display for ZkProofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected(e) => write!(f, "{}", e.errors.message),
+            Self::Prover(e) => write!(f, "Failed to generate ZK proof: {}", e),
+            Self::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<ZkProofError> for ApiErrorResponse {
+    fn from(e: ZkProofError) -> Self {
+        match e {
+            ZkProofError::Rejected(e) => e,
+            ZkProofError::Store(e) => e.into(),
+            ZkProofError::Prover(ProverError::ConfigError(err)) => {
+                tracing::error!("Prover not configured: {}", err);
+                ApiErrorResponse::default()
+                    .with_code(StatusCode::SERVICE_UNAVAILABLE)
+                    .with_message("ZK prover is not configured")
+                    .with_details(err)
+            }
+            ZkProofError::Prover(ref err) => {
+                tracing::error!("Failed to generate ZK proof: {}", err);
+                ApiErrorResponse::default()
+                    .with_code(StatusCode::INTERNAL_SERVER_ERROR)
+                    .with_message(&e.to_string())
+            }
+        }
+    }
+}
+
+/// POST /api/v1/zk-proofs/:address/generate - Generate ZK proof for claim
+///
+/// Holds the request open while proving; prefer the job endpoints.
+/// Requires a session for `wallet`: the response carries its secret.
 async fn generate_zk_proof(
     State(state): State<AppState>,
+    session: AuthSession,
     Path(address): Path<String>,
     Json(body): Json<GenerateZkProofRequest>,
 ) -> ApiResponse<ZkProofResponse> {
-    // Get campaign
-    let campaign = match state.campaign_store.get(&address).await {
-        Some(c) => c,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Campaign not found"))
-        }
-    };
+    session.require_wallet(&body.wallet)?;
+    let (response, message) = build_zk_proof(&state, &address, &body.wallet).await?;
 
-    // Find recipient
-    let recipient = match campaign.recipients.iter().find(|r| r.wallet == body.wallet) {
-        Some(r) => r,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Wallet not found in campaign recipients"))
-        }
-    };
+    Ok(ApiSuccessResponse::default()
+        .with_data(response)
+        .with_message(message))
+}
 
-    // Check if already claimed
-    if recipient.claimed {
+/// POST /api/v1/zk-proofs/:address/jobs - Queue ZK proof generation
+///
+/// Returns the job right away; poll `GET /zk-proofs/jobs/:id` for the proof.
+/// A wallet with a job still queued or running gets that job back.
+/// Requires a session for `wallet`.
+async fn submit_zk_proof_job(
+    State(state): State<AppState>,
+    session: AuthSession,
+    Path(address): Path<String>,
+    Json(body): Json<GenerateZkProofRequest>,
+) -> ApiResponse<ProofJob> {
+    session.require_wallet(&body.wallet)?;
+    if state.config.prover.mode == ProverMode::Disabled {
+        return Err(prover_disabled());
+    }
+
+    // Reject obviously bad claims before queueing
+    let eligibility = state
+        .campaign_store
+        .check_eligibility(&address, &body.wallet)
+        .await?;
+    if eligibility.already_claimed {
         return Err(ApiErrorResponse::default()
             .with_code(StatusCode::BAD_REQUEST)
             .with_message("Already claimed"));
     }
+    if !eligibility.eligible {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::NOT_FOUND)
+            .with_message("Wallet not found in campaign recipients"));
+    }
+
+    let job = state
+        .proof_jobs
+        .enqueue(&address, &body.wallet)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue proof job");
+            ApiErrorResponse::default()
+                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
+                .with_message("Failed to queue proof job")
+        })?;
 
-    // Generate secret for this claim
-    let secret = generate_secret();
+    Ok(ApiSuccessResponse::default()
+        .with_code(StatusCode::ACCEPTED)
+        .with_data(job)
+        .with_message("Proof job queued"))
+}
 
-    // Build merkle tree with secrets
-    let recipients_with_secrets: Vec<(String, u64, [u8; 32])> = campaign
+/// GET /api/v1/zk-proofs/jobs/:id - Proof job status (and proof once done)
+///
+/// Requires a session for the job's wallet: the proof carries its secret.
+async fn get_zk_proof_job(
+    State(state): State<AppState>,
+    session: AuthSession,
+    Path(id): Path<String>,
+) -> ApiResponse<ProofJob> {
+    let not_found = || {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::NOT_FOUND)
+            .with_message("Proof job not found")
+    };
+    let id = Uuid::parse_str(&id).map_err(|_| not_found())?;
+
+    let job = state.proof_jobs.get(id).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to load proof job");
+        ApiErrorResponse::default()
+            .with_code(StatusCode::INTERNAL_SERVER_ERROR)
+            .with_message("Failed to load proof job")
+    })?;
+
+    let job = job.ok_or_else(not_found)?;
+    session.require_wallet(&job.wallet)?;
+
+    Ok(ApiSuccessResponse::default().with_data(job))
+}
+
+/// Generate the ZK proof for `wallet`'s claim in campaign `address`
+///
+/// Shared by the synchronous endpoint and the job workers. Returns the
+/// response along with a message describing how it was produced.
+pub(crate) async fn build_zk_proof(
+    state: &AppState,
+    address: &str,
+    wallet: &str,
+) -> Result<(ZkProofResponse, &'static str), ZkProofError> {
+    let rejected = |code: StatusCode, message: &str| {
+        ZkProofError::Rejected(ApiErrorResponse::default().with_code(code).with_message(message))
+    };
+
+    let mode = state.config.prover.mode;
+    if mode == ProverMode::Disabled {
+        return Err(ZkProofError::Rejected(prover_disabled()));
+    }
+
+    // Proven as the recipient public input
+    let pubkey = parse_pubkey(wallet)
+        .ok_or_else(|| rejected(StatusCode::BAD_REQUEST, "Invalid wallet address"))?;
+
+    // Get campaign
+    let campaign = state.campaign_store.get(address).await?;
+
+    // Find recipient
+    let recipient = campaign
         .recipients
         .iter()
-        .map(|r| {
-            let mut recipient_secret = [0u8; 32];
-            if r.wallet == body.wallet {
-                recipient_secret = secret;
-            } else {
-                // Use wallet hash as placeholder secret for other recipients
-                let wallet_bytes = r.wallet.as_bytes();
-                for (i, b) in wallet_bytes.iter().enumerate() {
-                    recipient_secret[i % 32] ^= *b;
-                }
-            }
-            let amount_u64 = r.amount.parse::<u64>().unwrap_or(0);
-            (r.wallet.clone(), amount_u64, recipient_secret)
-        })
-        .collect();
+        .find(|r| r.wallet == wallet)
+        .ok_or_else(|| rejected(StatusCode::NOT_FOUND, "Wallet not found in campaign recipients"))?;
+
+    // Check if already claimed (vested allocations take several claims)
+    if recipient.fully_claimed() {
+        return Err(rejected(StatusCode::BAD_REQUEST, "Already claimed"));
+    }
 
-    let tree = MerkleTree::from_recipients(&recipients_with_secrets);
+    // Rebuild the tree from the persisted secrets
+    let tree = campaign
+        .verified_merkle_tree()
+        .map_err(|e| ZkProofError::Rejected(tree_error(e)))?;
+    let secret = recipient
+        .secret
+        .ok_or_else(|| rejected(StatusCode::CONFLICT, "No stored secret for this recipient"))?;
 
     // Get proof for wallet
-    let proof = match tree.get_proof(&body.wallet) {
-        Some(p) => p,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
-                .with_message("Failed to generate merkle proof"))
-        }
-    };
+    let proof = tree.get_proof(wallet).ok_or_else(|| {
+        rejected(StatusCode::INTERNAL_SERVER_ERROR, "Failed to generate merkle proof")
+    })?;
 
     // Compute nullifier
     let nullifier = compute_nullifier(&secret, proof.leaf_index);
 
-    // Convert wallet to field element (use first 31 bytes of pubkey)
-    let wallet_field = wallet_to_field(&body.wallet);
+    // Proven as a public input, so the program checks it against the payout
+    let amount = recipient.amount.parse::<u64>().map_err(|_| {
+        rejected(StatusCode::INTERNAL_SERVER_ERROR, "Invalid stored amount for this recipient")
+    })?;
+
+    let public_witness = PublicWitness {
+        merkle_root: tree.root(),
+        nullifier_hash: nullifier,
+        recipient: recipient_field(&pubkey),
+        amount: amount_field(amount),
+    };
 
     // Prepare ZK proof input
     let zk_input = ZkProofInput {
-        merkle_root: format!("0x{}", hex::encode(tree.root())),
-        nullifier_hash: format!("0x{}", hex::encode(nullifier)),
-        recipient: format!("0x{}", wallet_field),
-        amount: recipient.amount.clone(), // Use raw string directly
+        merkle_root: format!("0x{}", hex::encode(public_witness.merkle_root)),
+        nullifier_hash: format!("0x{}", hex::encode(public_witness.nullifier_hash)),
+        recipient: format!("0x{}", hex::encode(public_witness.recipient)),
+        amount: amount.to_string(),
         secret: format!("0x{}", hex::encode(secret)),
         leaf_index: proof.leaf_index as u64,
         merkle_path: proof
@@ -139,80 +287,56 @@ async fn generate_zk_proof(
             .collect(),
     };
 
-    // Initialize Sunspot prover
-    let circuits_dir = PathBuf::from(
-        std::env::var("CIRCUITS_DIR")
-            .unwrap_or_else(|_| "../circuits".to_string()),
-    );
-    let prover = SunspotProver::new(circuits_dir);
+    if mode == ProverMode::Mock {
+        tracing::warn!(wallet, "Returning mock ZK proof (PROVER_MODE=mock)");
 
-    // Check prover health
-    if let Err(e) = prover.health_check().await {
-        tracing::warn!("Sunspot prover not configured: {}", e);
-
-        // Return mock proof for development (when Sunspot not available)
-        return Ok(ApiSuccessResponse::default()
-            .with_data(ZkProofResponse {
-                groth16_proof: "0x".to_string() + &"00".repeat(256),
-                public_inputs: format!(
-                    "{}{}{}",
-                    hex::encode(tree.root()),
-                    hex::encode(nullifier),
-                    wallet_field
-                ),
+        return Ok((
+            ZkProofResponse {
+                groth16_proof: "00".repeat(GROTH16_PROOF_SIZE),
+                public_inputs: hex::encode(public_witness.to_bytes()),
                 nullifier_hash: hex::encode(nullifier),
                 nullifier: hex::encode(nullifier),
-                amount: recipient.amount.parse::<u64>().unwrap_or(0),
+                amount,
                 secret: hex::encode(secret),
                 merkle_root: hex::encode(tree.root()),
                 leaf_index: proof.leaf_index,
-            })
-            .with_message("Mock proof generated (Sunspot not configured)"));
+                mock: true,
+            },
+            "Mock proof generated (PROVER_MODE=mock)",
+        ));
     }
 
+    // Check prover health
+    let prover = &state.prover;
+    prover
+        .health_check(tree.depth())
+        .await
+        .map_err(ZkProofError::Prover)?;
+
     // Generate real proof
-    match prover.generate_proof(zk_input).await {
-        Ok(zk_output) => {
-            Ok(ApiSuccessResponse::default()
-                .with_data(ZkProofResponse {
-                    groth16_proof: zk_output.proof,
-                    public_inputs: zk_output.public_inputs,
-                    nullifier_hash: hex::encode(nullifier),
-                    nullifier: hex::encode(nullifier),
-                    amount: recipient.amount.parse::<u64>().unwrap_or(0),
-                    secret: hex::encode(secret),
-                    merkle_root: hex::encode(tree.root()),
-                    leaf_index: proof.leaf_index,
-                })
-                .with_message("ZK proof generated successfully"))
-        }
-        Err(e) => {
-            tracing::error!("Failed to generate ZK proof: {}", e);
-            Err(ApiErrorResponse::default()
-                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
-                .with_message(&format!("Failed to generate ZK proof: {}", e)))
-        }
-    }
+    let zk_output = prover
+        .generate_proof(zk_input)
+        .await
+        .map_err(ZkProofError::Prover)?;
+
+    Ok((
+        ZkProofResponse {
+            groth16_proof: zk_output.proof,
+            public_inputs: zk_output.public_inputs,
+            nullifier_hash: hex::encode(nullifier),
+            nullifier: hex::encode(nullifier),
+            amount,
+            secret: hex::encode(secret),
+            merkle_root: hex::encode(tree.root()),
+            leaf_index: proof.leaf_index,
+            mock: false,
+        },
+        "ZK proof generated successfully",
+    ))
 }
 
-/// Convert wallet address to field element (hex string)
-fn wallet_to_field(wallet: &str) -> String {
-    // Decode base58 pubkey and take first 31 bytes (to fit in field)
-    let decoded: Result<Vec<u8>, _> = bs58::decode(wallet).into_vec();
-    match decoded {
-        Ok(bytes) => {
-            let mut field_bytes = [0u8; 32];
-            let len = bytes.len().min(31);
-            field_bytes[32 - len..].copy_from_slice(&bytes[..len]);
-            hex::encode(field_bytes)
-        }
-        Err(_) => {
-            // Fallback: hash the wallet string
-            let mut hash = [0u8; 32];
-            for (i, b) in wallet.as_bytes().iter().enumerate() {
-                hash[i % 32] ^= *b;
-            }
-            hex::encode(hash)
-        }
-    }
+fn prover_disabled() -> ApiErrorResponse {
+    ApiErrorResponse::default()
+        .with_code(StatusCode::SERVICE_UNAVAILABLE)
+        .with_message("ZK proof generation is disabled")
 }
@@ -1,7 +1,12 @@
 use std::{sync::Arc, time::Instant};
 
+use crate::auth::{AuthStore, PgAuthRepository};
+use crate::chain::{ChainRpc, SolanaRpc};
+use crate::common::secrets::SecretCipher;
 use crate::config::Config;
-use crate::models::CampaignStore;
+use crate::jobs::ProofJobStore;
+use crate::repository::{CampaignRepository, PgCampaignRepository};
+use crate::zk::{Prover, ProverPool, SunspotProver};
 
 use sqlx::PgPool;
 
@@ -9,15 +14,32 @@ use sqlx::PgPool;
 pub struct AppState {
     pub start_time: Instant,
     pub config: Arc<Config>,
-    pub campaign_store: CampaignStore,
+    pub campaign_store: Arc<dyn CampaignRepository>,
+    pub auth: AuthStore,
+    pub chain: Arc<dyn ChainRpc>,
+    pub prover: Arc<dyn Prover>,
+    pub proof_jobs: ProofJobStore,
 }
 
 impl AppState {
     pub fn new(config: Arc<Config>, db: PgPool) -> Self {
+        let cipher = SecretCipher::new(&config.secrets.encryption_key);
+        let prover: Arc<dyn Prover> = Arc::new(SunspotProver::new(config.prover.circuits_dir.clone()));
+        let prover = Arc::new(ProverPool::new(prover, config.prover.workers));
+        let auth = AuthStore::new(Arc::new(PgAuthRepository::new(db.clone())), &config.auth);
+        let chain = Arc::new(SolanaRpc::new(
+            config.chain.rpc_url.clone(),
+            config.chain.commitment.clone(),
+        ));
+
         AppState {
             start_time: Instant::now(),
             config,
-            campaign_store: CampaignStore::new(db),
+            campaign_store: Arc::new(PgCampaignRepository::new(db.clone(), cipher)),
+            auth,
+            chain,
+            prover,
+            proof_jobs: ProofJobStore::new(db),
         }
     }
 }
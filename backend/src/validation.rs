@@ -0,0 +1,625 @@
+//! Request body and query string validation
+//!
+//! Checks collect every problem instead of stopping at the first one, so a
+//! client can point at each offending field and recipient row at once.
+
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use shadow_drop_wire::vesting::{MAX_TRANCHES, ScheduleError, Vesting};
+
+use crate::{
+    common::{
+        merkle::{
+            DEFAULT_TREE_DEPTH, Hash, MAX_TREE_DEPTH, max_leaves, parse_field_hex, parse_pubkey,
+        },
+        response::{ApiErrorResponse, FieldError},
+    },
+    repository::{
+        Asset, CampaignFilter, CampaignListing, CampaignSort, CampaignStatus, Cursor,
+        DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, SortOrder,
+    },
+    routes::campaigns::{CreateCampaignRequest, ListCampaignsQuery},
+};
+
+/// Longest campaign name accepted
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Accepted `airdrop_type`s
+pub const AIRDROP_TYPES: [&str; 2] = ["instant", "vested"];
+
+/// Problems found in a request body
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &str, row: Option<usize>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            row,
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.0.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl From<ValidationErrors> for ApiErrorResponse {
+    fn from(e: ValidationErrors) -> Self {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Invalid request")
+            .with_details(format!("{} invalid field(s)", e.0.len()))
+            .with_field_errors(e.0)
+    }
+}
+
+/// Whether `value` is a base58 32-byte public key (on or off curve, so
+/// PDAs pass)
+pub fn is_pubkey(value: &str) -> bool {
+    parse_pubkey(value).is_some()
+}
+
+/// Parse an amount in base units
+fn parse_amount(value: &str) -> Result<u64, &'static str> {
+    match value.parse::<u64>() {
+        Ok(0) => Err("must be greater than zero"),
+        Ok(amount) => Ok(amount),
+        Err(_) => Err("must be an integer amount in base units (u64)"),
+    }
+}
+
+/// What the recipient list is checked against, from the campaign fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CampaignLimits {
+    /// `None` if `total_amount` is invalid
+    pub total_amount: Option<u64>,
+    /// `None` if `tree_depth` is invalid
+    pub depth: Option<usize>,
+}
+
+/// Check a campaign submission before anything is built from it
+pub fn validate_create_campaign(req: &CreateCampaignRequest) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    let limits = validate_campaign_fields(req, &mut errors);
+
+    let mut rows = RecipientRows::new();
+    for r in &req.recipients {
+        rows.check(&r.wallet, &r.amount, r.secret.as_deref(), &mut errors);
+    }
+    rows.finish(limits, &mut errors);
+
+    errors.into_result()
+}
+
+/// Check every field of a campaign submission but the recipient list
+pub fn validate_campaign_fields(
+    req: &CreateCampaignRequest,
+    errors: &mut ValidationErrors,
+) -> CampaignLimits {
+    for (field, value) in [("address", &req.address), ("creator_wallet", &req.creator_wallet)] {
+        if !is_pubkey(value) {
+            errors.add(field, None, "must be a base58 32-byte public key");
+        }
+    }
+    for (field, value) in [("vault_address", &req.vault_address), ("token_mint", &req.token_mint)] {
+        if value.as_deref().is_some_and(|v| !is_pubkey(v)) {
+            errors.add(field, None, "must be a base58 32-byte public key");
+        }
+    }
+
+    let name = req.name.trim();
+    if name.is_empty() {
+        errors.add("name", None, "must not be empty");
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        errors.add("name", None, format!("must be at most {} characters", MAX_NAME_LENGTH));
+    }
+
+    if req.merkle_root.as_deref().is_some_and(|r| parse_field_hex(r).is_none()) {
+        errors.add("merkle_root", None, "must be a hex field element of at most 32 bytes");
+    }
+
+    let depth = req.tree_depth.map_or(DEFAULT_TREE_DEPTH, usize::from);
+    let depth_valid = (1..=MAX_TREE_DEPTH).contains(&depth);
+    if !depth_valid {
+        errors.add("tree_depth", None, format!("must be between 1 and {}", MAX_TREE_DEPTH));
+    }
+
+    for (field, value) in [
+        ("vesting_cliff_seconds", req.vesting_cliff_seconds),
+        ("vesting_duration_seconds", req.vesting_duration_seconds),
+    ] {
+        if value.is_some_and(|v| v < 0) {
+            errors.add(field, None, "must not be negative");
+        }
+    }
+    if req.airdrop_type.as_deref().is_some_and(|t| !AIRDROP_TYPES.contains(&t)) {
+        errors.add("airdrop_type", None, format!("must be one of {}", AIRDROP_TYPES.join(", ")));
+    }
+    if let Some(schedule) = &req.vesting_schedule {
+        match schedule.to_wire() {
+            None => errors.add(
+                "vesting_schedule",
+                None,
+                format!("must have at most {} tranches", MAX_TRANCHES),
+            ),
+            Some(schedule) => {
+                let vesting = Vesting {
+                    start: req.vesting_start.unwrap_or(0),
+                    cliff: req.vesting_cliff_seconds.unwrap_or(0),
+                    duration: req.vesting_duration_seconds.unwrap_or(0),
+                    schedule,
+                };
+                match vesting.validate() {
+                    // Reported for the cliff or duration above
+                    Ok(()) | Err(ScheduleError::NegativeTime) => {}
+                    Err(e) => errors.add("vesting_schedule", None, e.to_string()),
+                }
+            }
+        }
+    }
+
+    let total_amount = match parse_amount(&req.total_amount) {
+        Ok(amount) => Some(amount),
+        Err(message) => {
+            errors.add("total_amount", None, message);
+            None
+        }
+    };
+
+    CampaignLimits {
+        total_amount,
+        depth: depth_valid.then_some(depth),
+    }
+}
+
+/// Checks a recipient list one row at a time, so it can be validated while
+/// it is still being read
+#[derive(Debug)]
+pub struct RecipientRows {
+    /// First row of each wallet
+    seen: HashMap<String, usize>,
+    count: usize,
+    /// `None` once the sum overflows; only compared when every amount parsed
+    sum: Option<u64>,
+    amounts_valid: bool,
+}
+
+impl Default for RecipientRows {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecipientRows {
+    pub fn new() -> Self {
+        Self {
+            seen: HashMap::new(),
+            count: 0,
+            sum: Some(0),
+            amounts_valid: true,
+        }
+    }
+
+    /// Rows checked so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Check the next row
+    ///
+    /// Returns its amount and secret if the row is valid.
+    pub fn check(
+        &mut self,
+        wallet: &str,
+        amount: &str,
+        secret: Option<&str>,
+        errors: &mut ValidationErrors,
+    ) -> Option<(u64, Option<Hash>)> {
+        let row = self.count;
+        self.count += 1;
+        let mut valid = true;
+
+        if !is_pubkey(wallet) {
+            errors.add("wallet", Some(row), "must be a base58 32-byte public key");
+            valid = false;
+        } else if let Some(first) = self.seen.get(wallet) {
+            errors.add("wallet", Some(row), format!("duplicate of row {}", first));
+            valid = false;
+        } else {
+            self.seen.insert(wallet.to_string(), row);
+        }
+
+        let amount = match parse_amount(amount) {
+            Ok(amount) => {
+                self.sum = self.sum.and_then(|s| s.checked_add(amount));
+                Some(amount)
+            }
+            Err(message) => {
+                errors.add("amount", Some(row), message);
+                self.amounts_valid = false;
+                None
+            }
+        };
+
+        let secret = secret.map(parse_field_hex);
+        if secret.is_some_and(|s| s.is_none()) {
+            errors.add("secret", Some(row), "must be a hex field element of at most 32 bytes");
+            valid = false;
+        }
+
+        if !valid {
+            return None;
+        }
+        Some((amount?, secret.flatten()))
+    }
+
+    /// Count a row that could not be read at all
+    pub fn reject(&mut self, message: impl Into<String>, errors: &mut ValidationErrors) {
+        errors.add("recipients", Some(self.count), message);
+        self.count += 1;
+        self.amounts_valid = false;
+    }
+
+    /// Check the list as a whole once every row is in
+    pub fn finish(self, limits: CampaignLimits, errors: &mut ValidationErrors) {
+        if self.count == 0 {
+            errors.add("recipients", None, "must not be empty");
+        } else if let Some(depth) = limits.depth
+            && self.count > max_leaves(depth)
+        {
+            errors.add(
+                "recipients",
+                None,
+                format!(
+                    "{} recipients do not fit a tree of depth {} (at most {})",
+                    self.count,
+                    depth,
+                    max_leaves(depth)
+                ),
+            );
+        }
+
+        if let Some(total) = limits.total_amount
+            && self.amounts_valid
+        {
+            match self.sum {
+                Some(sum) if sum == total => {}
+                Some(sum) => errors.add(
+                    "total_amount",
+                    None,
+                    format!("is {} but recipient amounts sum to {}", total, sum),
+                ),
+                None => errors.add("recipients", None, "amounts overflow u64"),
+            }
+        }
+    }
+}
+
+/// Validate a campaign listing query, decoding its cursor
+///
+/// A cursor must come from a listing with the same sort and order.
+pub fn validate_campaign_listing(
+    query: &ListCampaignsQuery,
+) -> Result<CampaignListing, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    fn parse<T>(
+        value: Option<&str>,
+        field: &str,
+        expected: &str,
+        parse: fn(&str) -> Option<T>,
+        errors: &mut ValidationErrors,
+    ) -> Option<T> {
+        let value = value?;
+        let parsed = parse(value);
+        if parsed.is_none() {
+            errors.add(field, None, format!("must be one of {}", expected));
+        }
+        parsed
+    }
+
+    let sort = parse(
+        query.sort.as_deref(),
+        "sort",
+        "created_at, name, total_amount",
+        CampaignSort::parse,
+        &mut errors,
+    )
+    .unwrap_or_default();
+    let order = parse(query.order.as_deref(), "order", "asc, desc", SortOrder::parse, &mut errors)
+        .unwrap_or_default();
+    let asset = parse(query.asset.as_deref(), "asset", "sol, token", Asset::parse, &mut errors);
+    let airdrop_type = parse(
+        query.airdrop_type.as_deref(),
+        "airdrop_type",
+        &AIRDROP_TYPES.join(", "),
+        |t| AIRDROP_TYPES.contains(&t).then(|| t.to_string()),
+        &mut errors,
+    );
+    let status = parse(
+        query.status.as_deref(),
+        "status",
+        "active, closed",
+        CampaignStatus::parse,
+        &mut errors,
+    );
+
+    let limit = match query.limit.as_deref() {
+        None => DEFAULT_PAGE_SIZE,
+        Some(limit) => match limit.parse::<usize>() {
+            Ok(limit) if (1..=MAX_PAGE_SIZE).contains(&limit) => limit,
+            _ => {
+                errors.add("limit", None, format!("must be between 1 and {}", MAX_PAGE_SIZE));
+                DEFAULT_PAGE_SIZE
+            }
+        },
+    };
+
+    let after = query.cursor.as_deref().and_then(|cursor| {
+        let cursor = Cursor::decode(cursor);
+        match &cursor {
+            None => errors.add("cursor", None, "is not a valid cursor"),
+            Some(c) if c.sort != sort || c.order != order => errors.add(
+                "cursor",
+                None,
+                "was issued for a different sort or order",
+            ),
+            Some(_) => {}
+        }
+        cursor
+    });
+
+    errors.into_result()?;
+    Ok(CampaignListing {
+        filter: CampaignFilter {
+            asset,
+            airdrop_type,
+            status,
+        },
+        sort,
+        order,
+        limit,
+        after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routes::campaigns::RecipientInput,
+        vesting::{VestingSchedule, VestingTranche},
+    };
+
+    fn key(byte: u8) -> String {
+        bs58::encode([byte; 32]).into_string()
+    }
+
+    fn recipient(byte: u8, amount: &str) -> RecipientInput {
+        RecipientInput {
+            wallet: key(byte),
+            amount: amount.to_string(),
+            secret: None,
+        }
+    }
+
+    fn request(recipients: Vec<RecipientInput>, total_amount: &str) -> CreateCampaignRequest {
+        CreateCampaignRequest {
+            address: key(1),
+            name: "Airdrop".to_string(),
+            merkle_root: None,
+            tree_depth: None,
+            total_amount: total_amount.to_string(),
+            creator_wallet: key(2),
+            tx_signature: None,
+            vault_address: None,
+            recipients,
+            airdrop_type: None,
+            vesting_start: None,
+            vesting_cliff_seconds: None,
+            vesting_duration_seconds: None,
+            vesting_schedule: None,
+            token_mint: None,
+            token_symbol: None,
+            token_decimals: None,
+        }
+    }
+
+    fn fields(errors: &ValidationErrors) -> Vec<(&str, Option<usize>)> {
+        errors.0.iter().map(|e| (e.field.as_str(), e.row)).collect()
+    }
+
+    #[test]
+    fn test_valid_request() {
+        let req = request(vec![recipient(3, "100"), recipient(4, "250")], "350");
+        assert_eq!(validate_create_campaign(&req), Ok(()));
+    }
+
+    #[test]
+    fn test_every_bad_row_is_reported() {
+        let mut req = request(
+            vec![
+                recipient(3, "100"),
+                RecipientInput {
+                    wallet: "not-a-key".to_string(),
+                    amount: "-5".to_string(),
+                    secret: Some("zz".to_string()),
+                },
+                recipient(3, "0"),
+            ],
+            "100",
+        );
+        req.creator_wallet = bs58::encode([2u8; 20]).into_string();
+
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(
+            fields(&errors),
+            [
+                ("creator_wallet", None),
+                ("wallet", Some(1)),
+                ("amount", Some(1)),
+                ("secret", Some(1)),
+                ("wallet", Some(2)),
+                ("amount", Some(2)),
+            ]
+        );
+        assert_eq!(errors.0[4].message, "duplicate of row 0");
+    }
+
+    #[test]
+    fn test_amounts_must_sum_to_total() {
+        let req = request(vec![recipient(3, "100"), recipient(4, "250")], "300");
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(fields(&errors), [("total_amount", None)]);
+
+        let max = u64::MAX.to_string();
+        let req = request(vec![recipient(3, &max), recipient(4, "1")], &max);
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(fields(&errors), [("recipients", None)]);
+    }
+
+    #[test]
+    fn test_recipients_must_fit_the_tree() {
+        let recipients = (0..5).map(|i| recipient(10 + i, "1")).collect();
+        let mut req = request(recipients, "5");
+        req.tree_depth = Some(2);
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(fields(&errors), [("recipients", None)]);
+
+        req.tree_depth = Some(0);
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(fields(&errors), [("tree_depth", None)]);
+    }
+
+    #[test]
+    fn test_vesting_schedule() {
+        let mut req = request(vec![recipient(3, "1")], "1");
+        req.airdrop_type = Some("vested".to_string());
+        req.vesting_duration_seconds = Some(3_600);
+        req.vesting_schedule = Some(VestingSchedule::Step {
+            period_seconds: 600,
+        });
+        assert_eq!(validate_create_campaign(&req), Ok(()));
+
+        req.vesting_schedule = Some(VestingSchedule::Step {
+            period_seconds: 700,
+        });
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(fields(&errors), [("vesting_schedule", None)]);
+
+        let tranche = |offset_seconds, bps| VestingTranche {
+            offset_seconds,
+            bps,
+        };
+        req.vesting_schedule = Some(VestingSchedule::Tranches {
+            tranches: vec![tranche(0, 2_500), tranche(3_600, 10_000)],
+        });
+        assert_eq!(validate_create_campaign(&req), Ok(()));
+        req.vesting_schedule = Some(VestingSchedule::Tranches {
+            tranches: (1..=9).map(|i| tranche(i * 400, i as u16 * 1_000)).collect(),
+        });
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(fields(&errors), [("vesting_schedule", None)]);
+
+        // A negative duration is only reported once
+        req.airdrop_type = Some("streamed".to_string());
+        req.vesting_duration_seconds = Some(-1);
+        req.vesting_schedule = Some(VestingSchedule::TgeLinear { tge_bps: 1_000 });
+        let errors = validate_create_campaign(&req).unwrap_err();
+        assert_eq!(
+            fields(&errors),
+            [("vesting_duration_seconds", None), ("airdrop_type", None)]
+        );
+    }
+
+    #[test]
+    fn test_campaign_listing_query() {
+        let listing = validate_campaign_listing(&ListCampaignsQuery::default()).unwrap();
+        assert_eq!(listing, CampaignListing::default());
+
+        let query = ListCampaignsQuery {
+            limit: Some("5".to_string()),
+            sort: Some("name".to_string()),
+            order: Some("asc".to_string()),
+            asset: Some("token".to_string()),
+            airdrop_type: Some("vested".to_string()),
+            status: Some("closed".to_string()),
+            cursor: None,
+        };
+        let listing = validate_campaign_listing(&query).unwrap();
+        assert_eq!(listing.limit, 5);
+        assert_eq!((listing.sort, listing.order), (CampaignSort::Name, SortOrder::Asc));
+        assert_eq!(
+            listing.filter,
+            CampaignFilter {
+                asset: Some(Asset::Token),
+                airdrop_type: Some("vested".to_string()),
+                status: Some(CampaignStatus::Closed),
+            }
+        );
+
+        let bad = ListCampaignsQuery {
+            limit: Some("0".to_string()),
+            sort: Some("claimed".to_string()),
+            asset: Some("nft".to_string()),
+            airdrop_type: Some("streamed".to_string()),
+            cursor: Some("???".to_string()),
+            ..ListCampaignsQuery::default()
+        };
+        let errors = validate_campaign_listing(&bad).unwrap_err();
+        assert_eq!(
+            fields(&errors),
+            [
+                ("sort", None),
+                ("asset", None),
+                ("airdrop_type", None),
+                ("limit", None),
+                ("cursor", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cursor_must_match_sort() {
+        let cursor = Cursor {
+            sort: CampaignSort::Name,
+            order: SortOrder::Desc,
+            key: crate::repository::SortKey::Name("Airdrop".to_string()),
+            address: key(1),
+        }
+        .encode();
+
+        let query = ListCampaignsQuery {
+            sort: Some("name".to_string()),
+            cursor: Some(cursor.clone()),
+            ..ListCampaignsQuery::default()
+        };
+        assert!(validate_campaign_listing(&query).unwrap().after.is_some());
+
+        let query = ListCampaignsQuery {
+            cursor: Some(cursor),
+            ..ListCampaignsQuery::default()
+        };
+        let errors = validate_campaign_listing(&query).unwrap_err();
+        assert_eq!(fields(&errors), [("cursor", None)]);
+    }
+
+    #[test]
+    fn test_error_response_lists_fields() {
+        let errors = validate_create_campaign(&request(Vec::new(), "x")).unwrap_err();
+        let response = ApiErrorResponse::from(errors);
+        assert_eq!(response.errors.code, 400);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["errors"]["fields"][0]["field"], "total_amount");
+        assert_eq!(json["errors"]["fields"][1]["field"], "recipients");
+        assert!(json["errors"]["fields"][0].get("row").is_none());
+    }
+}
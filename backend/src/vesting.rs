@@ -0,0 +1,364 @@
+//! Per-recipient vesting status
+//!
+//! Evaluated with the program's own vesting math
+//! ([`shadow_drop_wire::vesting`]), so what the API reports as claimable is
+//! what a claim transaction would pay at that time, ZK or not.
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use shadow_drop_wire::vesting::{self as wire, Tranche, Vesting};
+
+use crate::{
+    common::response::ApiErrorResponse,
+    models::{Campaign, Recipient},
+};
+
+/// Vesting status of one recipient (amounts are raw integer strings, times
+/// unix seconds)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VestingStatus {
+    pub wallet: String,
+    /// The recipient's full allocation
+    pub allocation: String,
+    /// Vested at `as_of`
+    pub vested: String,
+    /// Paid out on chain, as reconciled by the indexer
+    pub claimed: String,
+    /// What a claim sent at `as_of` would pay: vested but not yet claimed
+    pub claimable: String,
+    /// When more of the allocation vests; `None` once all of it has
+    pub next_unlock_at: Option<i64>,
+    pub vesting_start: i64,
+    pub vesting_cliff_seconds: i64,
+    pub vesting_duration_seconds: i64,
+    pub vesting_schedule: VestingSchedule,
+    pub schedule: Vec<UnlockPoint>,
+    pub as_of: i64,
+}
+
+/// A point of the unlock schedule
+///
+/// Nothing has vested before the first point. From a point the vested
+/// amount grows linearly to the next one if `linear`, and holds until it
+/// otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnlockPoint {
+    pub at: i64,
+    pub vested: String,
+    pub linear: bool,
+}
+
+/// Shape of a campaign's vesting curve, as stored and submitted (see
+/// [`wire::VestingSchedule`])
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VestingSchedule {
+    /// Linear after the cliff
+    #[default]
+    Linear,
+    /// The linear share, unlocked every `period_seconds`
+    Step { period_seconds: i64 },
+    /// `tge_bps` basis points at the start, the rest linearly after the
+    /// cliff
+    TgeLinear { tge_bps: u16 },
+    /// Cumulative shares unlocked at offsets from the start
+    Tranches { tranches: Vec<VestingTranche> },
+}
+
+/// A row of a tranche table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VestingTranche {
+    pub offset_seconds: i64,
+    /// Vested by then, in basis points of the allocation
+    pub bps: u16,
+}
+
+impl VestingSchedule {
+    /// The program's form; `None` for a tranche table too long to store
+    pub fn to_wire(&self) -> Option<wire::VestingSchedule> {
+        Some(match self {
+            Self::Linear => wire::VestingSchedule::Linear,
+            Self::Step { period_seconds } => wire::VestingSchedule::Step {
+                period: *period_seconds,
+            },
+            Self::TgeLinear { tge_bps } => wire::VestingSchedule::TgeLinear { tge_bps: *tge_bps },
+            Self::Tranches { tranches } => {
+                let rows: Vec<Tranche> = tranches
+                    .iter()
+                    .map(|t| Tranche {
+                        offset: t.offset_seconds,
+                        bps: t.bps,
+                    })
+                    .collect();
+                wire::VestingSchedule::tranches(&rows)?
+            }
+        })
+    }
+}
+
+impl From<wire::VestingSchedule> for VestingSchedule {
+    fn from(schedule: wire::VestingSchedule) -> Self {
+        match schedule {
+            wire::VestingSchedule::Linear => Self::Linear,
+            wire::VestingSchedule::Step { period } => Self::Step {
+                period_seconds: period,
+            },
+            wire::VestingSchedule::TgeLinear { tge_bps } => Self::TgeLinear { tge_bps },
+            wire::VestingSchedule::Tranches { .. } => Self::Tranches {
+                tranches: schedule
+                    .tranche_rows()
+                    .iter()
+                    .map(|t| VestingTranche {
+                        offset_seconds: t.offset,
+                        bps: t.bps,
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Errors computing a vesting status
+#[derive(Debug, thiserror::Error)]
+pub enum VestingError {
+    #[error("Invalid stored {field} for recipient {wallet}")]
+    InvalidAmount { field: &'static str, wallet: String },
+
+    #[error("Invalid stored vesting schedule for campaign {0}")]
+    InvalidSchedule(String),
+}
+
+impl From<VestingError> for ApiErrorResponse {
+    fn from(e: VestingError) -> Self {
+        tracing::error!("Vesting status failed: {}", e);
+        ApiErrorResponse::default()
+            .with_code(StatusCode::INTERNAL_SERVER_ERROR)
+            .with_message("Could not compute the vesting status")
+    }
+}
+
+/// The campaign's vesting schedule
+///
+/// Campaigns are registered with the schedule of their account on chain,
+/// so a stored one always converts.
+pub fn campaign_vesting(campaign: &Campaign) -> Result<Vesting, VestingError> {
+    let schedule = campaign
+        .vesting_schedule
+        .to_wire()
+        .ok_or_else(|| VestingError::InvalidSchedule(campaign.address.clone()))?;
+    Ok(Vesting {
+        start: campaign.vesting_start,
+        cliff: campaign.vesting_cliff_seconds,
+        duration: campaign.vesting_duration_seconds,
+        schedule,
+    })
+}
+
+/// Vesting status of `recipient` at unix time `now`
+///
+/// The program tracks how much of the allocation each recipient has been
+/// paid, and every claim pays what vested since the previous one. Nothing is
+/// claimable once the campaign is closed.
+pub fn vesting_status(
+    campaign: &Campaign,
+    recipient: &Recipient,
+    now: i64,
+) -> Result<VestingStatus, VestingError> {
+    let amount = |field: &'static str, value: &str| {
+        value
+            .parse::<u64>()
+            .map_err(|_| VestingError::InvalidAmount {
+                field,
+                wallet: recipient.wallet.clone(),
+            })
+    };
+    let allocation = amount("amount", &recipient.amount)?;
+    let claimed = amount("claimed_amount", &recipient.claimed_amount)?;
+
+    let vesting = campaign_vesting(campaign)?;
+    let vested = vesting.vested(allocation, now);
+    let claimable = if recipient.fully_claimed() || campaign.closed_at.is_some() {
+        0
+    } else {
+        // Whatever stops a claim (cliff, nothing new vested) pays nothing
+        vesting.claimable(allocation, claimed, now).unwrap_or(0)
+    };
+
+    Ok(VestingStatus {
+        wallet: recipient.wallet.clone(),
+        allocation: allocation.to_string(),
+        vested: vested.to_string(),
+        claimed: claimed.to_string(),
+        claimable: claimable.to_string(),
+        next_unlock_at: vesting.next_unlock(allocation, now),
+        vesting_start: vesting.start,
+        vesting_cliff_seconds: vesting.cliff,
+        vesting_duration_seconds: vesting.duration,
+        vesting_schedule: campaign.vesting_schedule.clone(),
+        schedule: vesting
+            .schedule(allocation)
+            .into_iter()
+            .map(|unlock| UnlockPoint {
+                at: unlock.at,
+                vested: unlock.vested.to_string(),
+                linear: unlock.linear,
+            })
+            .collect(),
+        as_of: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use shadow_drop_wire::vesting::parse_vectors;
+
+    use super::*;
+
+    /// The vectors the program's vesting math is tested against
+    const VECTORS: &str = include_str!("../../wire/fixtures/vesting.txt");
+
+    fn campaign(vesting: Vesting) -> Campaign {
+        Campaign {
+            id: "1".to_string(),
+            address: "Campaign".to_string(),
+            name: "Airdrop".to_string(),
+            merkle_root: String::new(),
+            tree_depth: 8,
+            total_amount: "0".to_string(),
+            creator_wallet: "Creator".to_string(),
+            tx_signature: None,
+            vault_address: None,
+            created_at: Utc::now(),
+            recipients: Vec::new(),
+            airdrop_type: "vested".to_string(),
+            vesting_start: vesting.start,
+            vesting_cliff_seconds: vesting.cliff,
+            vesting_duration_seconds: vesting.duration,
+            vesting_schedule: vesting.schedule.into(),
+            token_mint: None,
+            token_symbol: None,
+            token_decimals: None,
+            closed_at: None,
+        }
+    }
+
+    fn recipient(allocation: u64) -> Recipient {
+        Recipient::new("Wallet".to_string(), allocation.to_string(), [0u8; 32])
+    }
+
+    #[test]
+    fn test_matches_program_vectors() {
+        for v in parse_vectors(VECTORS) {
+            let status =
+                vesting_status(&campaign(v.vesting), &recipient(v.allocation), v.now).unwrap();
+            assert_eq!(status.vested, v.vested.to_string(), "{:?}", v);
+            assert_eq!(status.claimable, v.vested.to_string(), "{:?}", v);
+            assert_eq!(status.next_unlock_at, v.next_unlock, "{:?}", v);
+            assert_eq!(
+                status.schedule.last().map(|p| p.vested.as_str()),
+                Some(v.allocation.to_string().as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_schedule_kinds() {
+        let vesting = Vesting {
+            start: 0,
+            cliff: 0,
+            duration: 3_600,
+            schedule: wire::VestingSchedule::Step { period: 1_200 },
+        };
+        let campaign = campaign(vesting);
+        assert_eq!(
+            serde_json::to_value(&campaign.vesting_schedule).unwrap(),
+            serde_json::json!({ "kind": "step", "period_seconds": 1_200 })
+        );
+
+        let status = vesting_status(&campaign, &recipient(300), 2_000).unwrap();
+        assert_eq!(status.vested, "100");
+        assert_eq!(status.next_unlock_at, Some(2_400));
+        let points: Vec<(i64, &str, bool)> = status
+            .schedule
+            .iter()
+            .map(|p| (p.at, p.vested.as_str(), p.linear))
+            .collect();
+        assert_eq!(
+            points,
+            [(1_200, "100", false), (2_400, "200", false), (3_600, "300", false)]
+        );
+
+        let tranches: VestingSchedule = serde_json::from_value(serde_json::json!({
+            "kind": "tranches",
+            "tranches": [
+                { "offset_seconds": 0, "bps": 1_000 },
+                { "offset_seconds": 3_600, "bps": 10_000 },
+            ],
+        }))
+        .unwrap();
+        let wire = tranches.to_wire().unwrap();
+        assert_eq!(wire.tranche_rows().len(), 2);
+        assert_eq!(VestingSchedule::from(wire), tranches);
+        assert_eq!(
+            VestingSchedule::TgeLinear { tge_bps: 500 }.to_wire(),
+            Some(wire::VestingSchedule::TgeLinear { tge_bps: 500 })
+        );
+
+        let too_long = VestingSchedule::Tranches {
+            tranches: vec![
+                VestingTranche {
+                    offset_seconds: 0,
+                    bps: 10_000,
+                };
+                wire::MAX_TRANCHES + 1
+            ],
+        };
+        assert_eq!(too_long.to_wire(), None);
+        let mut broken = campaign.clone();
+        broken.vesting_schedule = too_long;
+        assert!(vesting_status(&broken, &recipient(1), 0).is_err());
+    }
+
+    #[test]
+    fn test_claimable_is_vested_minus_claimed() {
+        let vesting = Vesting {
+            start: 1_000,
+            cliff: 0,
+            duration: 100,
+            schedule: wire::VestingSchedule::Linear,
+        };
+        let mut claimed = recipient(1_000);
+        claimed.claimed = true;
+        claimed.claimed_amount = "250".to_string();
+
+        let status = vesting_status(&campaign(vesting), &claimed, 1_050).unwrap();
+        assert_eq!(status.vested, "500");
+        assert_eq!(status.claimed, "250");
+        assert_eq!(status.claimable, "250");
+
+        // Nothing new until more vests, everything left at the end
+        let status = vesting_status(&campaign(vesting), &claimed, 1_025).unwrap();
+        assert_eq!(status.claimable, "0");
+        let status = vesting_status(&campaign(vesting), &claimed, 2_000).unwrap();
+        assert_eq!(status.claimable, "750");
+
+        claimed.claimed_amount = "1000".to_string();
+        let status = vesting_status(&campaign(vesting), &claimed, 2_000).unwrap();
+        assert_eq!(status.claimable, "0");
+
+        // Reported claimed, not reconciled yet: only the amounts count
+        claimed.claimed_amount = "0".to_string();
+        let status = vesting_status(&campaign(vesting), &claimed, 2_000).unwrap();
+        assert_eq!(status.claimable, "1000");
+
+        let mut closed = campaign(vesting);
+        closed.closed_at = Some(Utc::now());
+        let status = vesting_status(&closed, &recipient(1_000), 1_050).unwrap();
+        assert_eq!(status.claimable, "0");
+
+        let mut broken = recipient(1);
+        broken.amount = "lots".to_string();
+        assert!(vesting_status(&campaign(vesting), &broken, 0).is_err());
+    }
+}
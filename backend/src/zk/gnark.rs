@@ -0,0 +1,304 @@
+//! gnark binary encodings for BN254 Groth16
+//!
+//! The on-chain verifier consumes gnark's raw (uncompressed) layouts:
+//! - G1: `X || Y`, 32-byte big-endian coordinates
+//! - G2: `X.A1 || X.A0 || Y.A1 || Y.A0`
+//! - proof: `Ar || Bs || Krs || u32 nbCommitments || commitments || CommitmentPok`
+//! - public witness: `u32 nbPublic || u32 nbSecret || u32 len || elements`,
+//!   handled by `shadow_drop_wire::PublicWitness`
+//!
+//! - verifying key: `α₁ β₁ β₂ γ₂ δ₁ δ₂ || K || PublicAndCommitmentCommitted ||
+//!   CommitmentKeys` (slices prefixed with a u32 length)
+
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use shadow_drop_wire::{PublicWitness, WireError};
+
+/// Size of an uncompressed G1 point
+pub const G1_SIZE: usize = 64;
+
+/// Size of an uncompressed G2 point
+pub const G2_SIZE: usize = 128;
+
+/// Uncompressed G1 point; infinity is all zeros
+pub fn g1_bytes(point: &G1Affine) -> [u8; G1_SIZE] {
+    let mut out = [0u8; G1_SIZE];
+    if let Some((x, y)) = point.xy() {
+        out[..32].copy_from_slice(&fq_bytes(&x));
+        out[32..].copy_from_slice(&fq_bytes(&y));
+    }
+    out
+}
+
+/// Uncompressed G2 point; infinity is all zeros
+pub fn g2_bytes(point: &G2Affine) -> [u8; G2_SIZE] {
+    let mut out = [0u8; G2_SIZE];
+    if let Some((x, y)) = point.xy() {
+        out[..64].copy_from_slice(&fq2_bytes(&x));
+        out[64..].copy_from_slice(&fq2_bytes(&y));
+    }
+    out
+}
+
+/// gnark writes the `A1` (imaginary) coefficient first
+fn fq2_bytes(value: &Fq2) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out[..32].copy_from_slice(&fq_bytes(&value.c1));
+    out[32..].copy_from_slice(&fq_bytes(&value.c0));
+    out
+}
+
+fn fq_bytes(value: &Fq) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&value.into_bigint().to_bytes_be());
+    out
+}
+
+/// A gnark Groth16 proof, possibly with BSB22 commitments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GnarkProof {
+    pub ar: G1Affine,
+    pub bs: G2Affine,
+    pub krs: G1Affine,
+    pub commitments: Vec<G1Affine>,
+    pub commitment_pok: G1Affine,
+}
+
+/// Pedersen key used to check the commitment proof of knowledge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitmentKey {
+    pub g: G2Affine,
+    pub g_sigma_neg: G2Affine,
+}
+
+/// A gnark Groth16 verifying key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GnarkVerifyingKey {
+    pub alpha_g1: G1Affine,
+    pub beta_g1: G1Affine,
+    pub beta_g2: G2Affine,
+    pub gamma_g2: G2Affine,
+    pub delta_g1: G1Affine,
+    pub delta_g2: G2Affine,
+    /// One point per public wire, the constant one first and the
+    /// commitment wires last
+    pub k: Vec<G1Affine>,
+    /// Public inputs (1-based) committed to by each commitment
+    pub public_and_commitment_committed: Vec<Vec<u32>>,
+    pub commitment_keys: Vec<CommitmentKey>,
+}
+
+impl GnarkVerifyingKey {
+    /// Number of public inputs in the public witness
+    pub fn nb_public_inputs(&self) -> usize {
+        self.k
+            .len()
+            .saturating_sub(self.public_and_commitment_committed.len() + 1)
+    }
+}
+
+/// Malformed gnark encoding
+#[derive(Debug, thiserror::Error)]
+pub enum EncodingError {
+    #[error("Unexpected end of data")]
+    Truncated,
+
+    #[error("{0} unexpected trailing bytes")]
+    TrailingBytes(usize),
+
+    #[error("Point is not on the curve or not in the subgroup")]
+    InvalidPoint,
+
+    #[error(transparent)]
+    Wire(#[from] WireError),
+}
+
+/// Decode a proof (sunspot `.proof` layout)
+pub fn read_proof(bytes: &[u8]) -> Result<GnarkProof, EncodingError> {
+    let mut r = Reader(bytes);
+    let ar = r.g1()?;
+    let bs = r.g2()?;
+    let krs = r.g1()?;
+    let n = r.u32()? as usize;
+    let commitments = (0..n).map(|_| r.g1()).collect::<Result<_, _>>()?;
+    let commitment_pok = r.g1()?;
+    r.finish()?;
+
+    Ok(GnarkProof {
+        ar,
+        bs,
+        krs,
+        commitments,
+        commitment_pok,
+    })
+}
+
+/// Decode a `.pw` public witness into its field elements
+pub fn read_public_witness(bytes: &[u8]) -> Result<Vec<Fr>, EncodingError> {
+    let witness = PublicWitness::from_bytes(bytes)?;
+    // `from_bytes` rejects non-canonical inputs
+    Ok(witness
+        .inputs()
+        .iter()
+        .map(|input| Fr::from_be_bytes_mod_order(input))
+        .collect())
+}
+
+/// Decode a verifying key (`vk.bin` layout)
+pub fn read_verifying_key(bytes: &[u8]) -> Result<GnarkVerifyingKey, EncodingError> {
+    let mut r = Reader(bytes);
+    let alpha_g1 = r.g1()?;
+    let beta_g1 = r.g1()?;
+    let beta_g2 = r.g2()?;
+    let gamma_g2 = r.g2()?;
+    let delta_g1 = r.g1()?;
+    let delta_g2 = r.g2()?;
+
+    let nb_k = r.u32()? as usize;
+    let k = (0..nb_k).map(|_| r.g1()).collect::<Result<_, _>>()?;
+
+    let nb_committed = r.u32()? as usize;
+    let public_and_commitment_committed = (0..nb_committed)
+        .map(|_| {
+            let n = r.u32()? as usize;
+            (0..n).map(|_| r.u32()).collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<_, _>>()?;
+
+    let nb_keys = r.u32()? as usize;
+    let commitment_keys = (0..nb_keys)
+        .map(|_| {
+            Ok(CommitmentKey {
+                g: r.g2()?,
+                g_sigma_neg: r.g2()?,
+            })
+        })
+        .collect::<Result<_, EncodingError>>()?;
+    r.finish()?;
+
+    Ok(GnarkVerifyingKey {
+        alpha_g1,
+        beta_g1,
+        beta_g2,
+        gamma_g2,
+        delta_g1,
+        delta_g2,
+        k,
+        public_and_commitment_committed,
+        commitment_keys,
+    })
+}
+
+/// Cursor over big-endian gnark data
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], EncodingError> {
+        if self.0.len() < n {
+            return Err(EncodingError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, EncodingError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn fq(&mut self) -> Result<Fq, EncodingError> {
+        canonical(self.take(32)?).ok_or(EncodingError::InvalidPoint)
+    }
+
+    fn g1(&mut self) -> Result<G1Affine, EncodingError> {
+        let (x, y) = (self.fq()?, self.fq()?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G1Affine::identity());
+        }
+        // BN254 G1 has cofactor 1, so on-curve means in the subgroup
+        let point = G1Affine::new_unchecked(x, y);
+        point
+            .is_on_curve()
+            .then_some(point)
+            .ok_or(EncodingError::InvalidPoint)
+    }
+
+    fn g2(&mut self) -> Result<G2Affine, EncodingError> {
+        let x_a1 = self.fq()?;
+        let x = Fq2::new(self.fq()?, x_a1);
+        let y_a1 = self.fq()?;
+        let y = Fq2::new(self.fq()?, y_a1);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G2Affine::identity());
+        }
+        let point = G2Affine::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve())
+            .then_some(point)
+            .ok_or(EncodingError::InvalidPoint)
+    }
+
+    fn finish(self) -> Result<(), EncodingError> {
+        match self.0.len() {
+            0 => Ok(()),
+            n => Err(EncodingError::TrailingBytes(n)),
+        }
+    }
+}
+
+/// Big-endian bytes to a field element, rejecting values >= the modulus
+fn canonical<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let value = F::from_be_bytes_mod_order(bytes);
+    (value.into_bigint().to_bytes_be() == bytes).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sunspot_verifying_key_parses() {
+        // Key of the first circuit, whose inputs were merkle_root,
+        // nullifier_hash and recipient
+        let vk = read_verifying_key(include_bytes!("../../../wire/fixtures/v1/vk.bin")).unwrap();
+
+        // Three public inputs plus one BSB22 commitment
+        assert_eq!(vk.nb_public_inputs(), 3);
+        assert_eq!(vk.public_and_commitment_committed, vec![Vec::<u32>::new()]);
+        assert_eq!(vk.commitment_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_reader_rejects_bad_data() {
+        let witness = PublicWitness {
+            merkle_root: [0; 32],
+            nullifier_hash: [0; 32],
+            recipient: [0; 32],
+            amount: shadow_drop_wire::amount_field(4),
+        }
+        .to_bytes();
+        assert!(matches!(
+            read_public_witness(&witness[..witness.len() - 1]),
+            Err(EncodingError::Wire(WireError::Length { .. }))
+        ));
+
+        let mut point = g1_bytes(&G1Affine::generator());
+        point[63] ^= 1;
+        let mut proof = point.to_vec();
+        proof.extend_from_slice(&[0u8; G2_SIZE + G1_SIZE + 4 + G1_SIZE]);
+        assert!(matches!(
+            read_proof(&proof),
+            Err(EncodingError::InvalidPoint)
+        ));
+    }
+
+    #[test]
+    fn test_g1_generator_encoding() {
+        // The BN254 G1 generator is (1, 2)
+        let bytes = g1_bytes(&G1Affine::generator());
+        assert_eq!(bytes[31], 1);
+        assert_eq!(bytes[63], 2);
+        assert!(bytes[..31].iter().chain(&bytes[32..63]).all(|b| *b == 0));
+    }
+}
@@ -1,9 +1,14 @@
 //! ZK Proof Generation Module
 //!
-//! Handles Noir circuit compilation and Sunspot proof generation
+//! Handles Noir circuit compilation and Groth16 proof generation via Sunspot
 
+pub mod gnark;
+pub mod pool;
 pub mod prover;
 pub mod types;
+pub mod verifier;
+pub mod workspace;
 
-pub use prover::SunspotProver;
+pub use pool::ProverPool;
+pub use prover::{Prover, ProverError, SunspotProver};
 pub use types::*;
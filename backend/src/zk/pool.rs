@@ -0,0 +1,117 @@
+//! Bounded proving pool
+//!
+//! Proving is CPU and memory heavy. `ProverPool` wraps any `Prover` and caps
+//! the number of jobs running at once; the rest wait for a permit instead of
+//! starving the runtime and the rest of the API.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Semaphore;
+
+use super::prover::{Prover, ProverError};
+use super::types::{ZkProofInput, ZkProofOutput};
+
+/// A `Prover` that runs at most `workers` proofs concurrently
+#[derive(Clone)]
+pub struct ProverPool {
+    inner: Arc<dyn Prover>,
+    permits: Arc<Semaphore>,
+}
+
+impl ProverPool {
+    /// Wrap `inner`, allowing `workers` concurrent jobs (at least one)
+    pub fn new(inner: Arc<dyn Prover>, workers: usize) -> Self {
+        Self {
+            inner,
+            permits: Arc::new(Semaphore::new(workers.max(1))),
+        }
+    }
+
+    /// Number of free worker slots
+    pub fn available_workers(&self) -> usize {
+        self.permits.available_permits()
+    }
+}
+
+#[async_trait]
+impl Prover for ProverPool {
+    async fn generate_proof(&self, input: ZkProofInput) -> Result<ZkProofOutput, ProverError> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| ProverError::ConfigError("Prover pool is closed".to_string()))?;
+        self.inner.generate_proof(input).await
+    }
+
+    async fn health_check(&self, depth: usize) -> Result<(), ProverError> {
+        self.inner.health_check(depth).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Records the highest number of overlapping calls
+    #[derive(Default)]
+    struct SlowProver {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Prover for SlowProver {
+        async fn generate_proof(&self, input: ZkProofInput) -> Result<ZkProofOutput, ProverError> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(ZkProofOutput {
+                proof: String::new(),
+                public_inputs: String::new(),
+                merkle_root: input.merkle_root,
+                nullifier_hash: input.nullifier_hash,
+            })
+        }
+
+        async fn health_check(&self, _depth: usize) -> Result<(), ProverError> {
+            Ok(())
+        }
+    }
+
+    fn input() -> ZkProofInput {
+        ZkProofInput {
+            merkle_root: "0x01".to_string(),
+            nullifier_hash: "0x02".to_string(),
+            recipient: "0x03".to_string(),
+            amount: "1".to_string(),
+            secret: "0x04".to_string(),
+            leaf_index: 0,
+            merkle_path: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_limits_concurrency() {
+        let inner = Arc::new(SlowProver::default());
+        let pool = ProverPool::new(inner.clone(), 2);
+
+        let jobs: Vec<_> = (0..6)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { pool.generate_proof(input()).await })
+            })
+            .collect();
+        for job in jobs {
+            job.await.unwrap().unwrap();
+        }
+
+        assert_eq!(inner.peak.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.available_workers(), 2);
+    }
+}
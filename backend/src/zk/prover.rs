@@ -1,15 +1,37 @@
-//! Sunspot Prover - Generates Groth16 proofs for Noir circuits
+//! Prover backends - Generate Groth16 proofs for the claim circuit
+//!
+//! `SunspotProver` shells out to nargo and sunspot, behind the `Prover`
+//! trait so the pool and handlers don't depend on how proofs are made.
 
+use async_trait::async_trait;
 use std::path::PathBuf;
-use std::process::Command;
 use tokio::fs;
+use tokio::process::Command;
 
-use super::types::{ZkProofInput, ZkProofOutput, GROTH16_PROOF_SIZE, PUBLIC_INPUTS_SIZE};
+use shadow_drop_wire as wire;
+
+use super::types::{ZkProofInput, ZkProofOutput};
+use super::verifier;
+use super::workspace::Workspace;
+use crate::common::merkle::DEFAULT_TREE_DEPTH;
+
+/// A Groth16 prover for the claim circuit
+#[async_trait]
+pub trait Prover: Send + Sync {
+    /// Generate a proof for the given inputs
+    /// The circuit is selected by the length of the merkle path.
+    async fn generate_proof(&self, input: ZkProofInput) -> Result<ZkProofOutput, ProverError>;
+
+    /// Check if the prover is properly configured for the given tree depth
+    async fn health_check(&self, depth: usize) -> Result<(), ProverError>;
+}
 
 /// Sunspot Prover configuration
 #[derive(Debug, Clone)]
 pub struct SunspotProver {
     /// Path to circuits directory
+    /// The default-depth circuit lives here; other depths are compiled into
+    /// `depth-<n>/` subdirectories by `just circuit-depth <n>`.
     circuits_dir: PathBuf,
     /// Path to sunspot binary
     sunspot_bin: PathBuf,
@@ -34,10 +56,32 @@ impl SunspotProver {
         self
     }
 
-    /// Generate a Groth16 proof for the given inputs
-    pub async fn generate_proof(&self, input: ZkProofInput) -> Result<ZkProofOutput, ProverError> {
+    /// Verifying key of a circuit package, as consumed by the on-chain verifier
+    fn vk_path(&self, circuit_dir: &std::path::Path) -> PathBuf {
+        circuit_dir.join("sunspot-out/keys/vk.bin")
+    }
+
+    /// Circuit package compiled for the given tree depth
+    pub fn circuit_dir(&self, depth: usize) -> PathBuf {
+        if depth == DEFAULT_TREE_DEPTH {
+            self.circuits_dir.clone()
+        } else {
+            self.circuits_dir.join(format!("depth-{}", depth))
+        }
+    }
+}
+
+#[async_trait]
+impl Prover for SunspotProver {
+    async fn generate_proof(&self, input: ZkProofInput) -> Result<ZkProofOutput, ProverError> {
+        let circuit_dir = self.circuit_dir(input.merkle_path.len());
+
+        // Each job works in its own copy of the package, so concurrent
+        // claims never see each other's Prover.toml, witness or proof
+        let workspace = Workspace::prepare(&circuit_dir).await?;
+
         // Step 1: Write Prover.toml
-        let prover_toml_path = self.circuits_dir.join("Prover.toml");
+        let prover_toml_path = workspace.join("Prover.toml");
         let prover_content = input.to_prover_toml();
         fs::write(&prover_toml_path, &prover_content)
             .await
@@ -49,11 +93,13 @@ impl SunspotProver {
         let witness_name = "shadow_drop_witness";
         let home = std::env::var("HOME").unwrap_or_else(|_| "/root".to_string());
         let nargo_bin = format!("{}/.nargo/bin/nargo", home);
-        
+
         let nargo_result = Command::new(&nargo_bin)
-            .current_dir(&self.circuits_dir)
+            .current_dir(workspace.path())
             .args(["execute", witness_name])
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| ProverError::CommandError(format!("Failed to run nargo: {}", e)))?;
 
         if !nargo_result.status.success() {
@@ -69,22 +115,21 @@ impl SunspotProver {
 
         // Step 3: Run sunspot prove
         // Usage: sunspot prove [acir_file] [witness_file] [ccs_file] [pk_file]
-        let acir_path = self.circuits_dir.join("target/shadow_drop.json");
-        let witness_path = self.circuits_dir.join(format!("target/{}.gz", witness_name));
-        let ccs_path = self.circuits_dir.join("target/shadow_drop.ccs");
-        let pk_path = self.circuits_dir.join("target/shadow_drop.pk");
+        // The CCS and proving key are shared read-only; the ACIR is the
+        // workspace copy so outputs land next to it.
+        let acir_path = workspace.join("target/shadow_drop.json");
+        let witness_path = workspace.join(format!("target/{}.gz", witness_name));
+        let ccs_path = circuit_dir.join("target/shadow_drop.ccs");
+        let pk_path = circuit_dir.join("target/shadow_drop.pk");
         // Sunspot outputs proof to target/shadow_drop.proof and public witness to target/shadow_drop.pw
-        let proof_path = self.circuits_dir.join("target/shadow_drop.proof");
+        let proof_path = workspace.join("target/shadow_drop.proof");
 
         let sunspot_result = Command::new(&self.sunspot_bin)
-            .args([
-                "prove",
-                acir_path.to_str().unwrap(),
-                witness_path.to_str().unwrap(),
-                ccs_path.to_str().unwrap(),
-                pk_path.to_str().unwrap(),
-            ])
+            .arg("prove")
+            .args([&acir_path, &witness_path, &ccs_path, &pk_path])
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| ProverError::CommandError(format!("Failed to run sunspot: {}", e)))?;
 
         if !sunspot_result.status.success() {
@@ -97,60 +142,40 @@ impl SunspotProver {
 
         tracing::info!("Generated Groth16 proof successfully");
 
-        // Step 4: Read proof file (first 256 bytes = Groth16 proof)
+        // Step 4: Read the proof (gnark layout with one BSB22 commitment)
         let proof_bytes = fs::read(&proof_path)
             .await
             .map_err(|e| ProverError::IoError(format!("Failed to read proof: {}", e)))?;
+        let proof = wire::Proof::from_bytes(&proof_bytes)?;
 
-        if proof_bytes.len() < GROTH16_PROOF_SIZE {
-            return Err(ProverError::InvalidProof(format!(
-                "Proof too short: {} bytes, expected {}",
-                proof_bytes.len(),
-                GROTH16_PROOF_SIZE
-            )));
-        }
-
-        // Step 5: Read public witness from .pw file (FULL file, including 12-byte header)
-        // The gnark-solana verifier expects: proof || full_pw_file
-        // It calculates: proof_len = total - (12 + NR_INPUTS * 32)
-        // For 3 inputs: proof_len = total - 108, so we need to send full .pw file
-        let pw_path = self.circuits_dir.join("target/shadow_drop.pw");
+        // Step 5: Read the public witness. The gnark-solana verifier expects
+        // `proof || .pw` including the 12-byte header, so the whole file is sent
+        let pw_path = workspace.join("target/shadow_drop.pw");
         let pw_bytes = fs::read(&pw_path)
             .await
             .map_err(|e| ProverError::IoError(format!("Failed to read public witness: {}", e)))?;
+        let public_witness = wire::PublicWitness::from_bytes(&pw_bytes)?;
 
-        // Expect: 12-byte header + 96 bytes public inputs = 108 bytes total
-        let expected_pw_size = 12 + PUBLIC_INPUTS_SIZE;
-        if pw_bytes.len() < expected_pw_size {
-            return Err(ProverError::InvalidProof(format!(
-                "Public witness too short: {} bytes, expected at least {}",
-                pw_bytes.len(),
-                expected_pw_size
-            )));
-        }
+        // Step 6: Verify locally, so a bad proof never reaches the chain
+        verifier::verify_files(&self.vk_path(&circuit_dir), &proof_bytes, &pw_bytes, &input).await?;
 
-        // Send the FULL .pw file content (108 bytes) for on-chain verification
-        tracing::info!("Read {} bytes of public witness from .pw file", pw_bytes.len());
+        tracing::info!("Verified Groth16 proof");
 
         Ok(ZkProofOutput {
-            // Send FULL proof file (388 bytes used by Gnark verifier with 1 commitment)
-            proof: hex::encode(&proof_bytes),
-            // Send FULL .pw file (108 bytes), not just 96 bytes
-            public_inputs: hex::encode(&pw_bytes[..expected_pw_size]),
+            proof: hex::encode(proof.to_bytes()),
+            public_inputs: hex::encode(public_witness.to_bytes()),
             merkle_root: input.merkle_root,
             nullifier_hash: input.nullifier_hash,
         })
-
-
     }
 
-    /// Check if the prover is properly configured
-    pub async fn health_check(&self) -> Result<(), ProverError> {
-        // Check circuits directory exists
-        if !self.circuits_dir.exists() {
+    async fn health_check(&self, depth: usize) -> Result<(), ProverError> {
+        // Check circuit directory for this depth exists
+        let circuit_dir = self.circuit_dir(depth);
+        if !circuit_dir.exists() {
             return Err(ProverError::ConfigError(format!(
-                "Circuits directory not found: {:?}",
-                self.circuits_dir
+                "Circuit for tree depth {} not found: {:?}. Run 'just circuit-depth {}' first.",
+                depth, circuit_dir, depth
             )));
         }
 
@@ -162,15 +187,17 @@ impl SunspotProver {
             )));
         }
 
-        // Check proving keys exist
-        let pk_path = self.circuits_dir.join("sunspot-out/keys/pk.bin");
-        if !pk_path.exists() {
+        // Check proving and verifying keys exist
+        let pk_path = circuit_dir.join("sunspot-out/keys/pk.bin");
+        let vk_path = self.vk_path(&circuit_dir);
+        if !pk_path.exists() || !vk_path.exists() {
             return Err(ProverError::ConfigError(
                 "Proving keys not found. Run 'just sunspot-setup' first.".to_string(),
             ));
         }
 
-        Ok(())
+        // Check the keys were built from the current circuit
+        verifier::check_verifying_key(&vk_path).await
     }
 }
 
@@ -202,6 +229,16 @@ pub enum ProverError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Proving error: {0}")]
+    ProvingError(String),
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 }
+
+/// sunspot wrote a proof or witness the program would not accept
+impl From<wire::WireError> for ProverError {
+    fn from(e: wire::WireError) -> Self {
+        ProverError::InvalidProof(e.to_string())
+    }
+}
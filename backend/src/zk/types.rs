@@ -2,11 +2,11 @@
 
 use serde::{Deserialize, Serialize};
 
-/// Groth16 proof size: 256 bytes
-pub const GROTH16_PROOF_SIZE: usize = 256;
+/// Groth16 proof size: gnark layout with one BSB22 commitment, 388 bytes
+pub use shadow_drop_wire::PROOF_SIZE as GROTH16_PROOF_SIZE;
 
-/// Public inputs size: 3 Field elements = 96 bytes
-pub const PUBLIC_INPUTS_SIZE: usize = 96;
+/// Public inputs size: 12-byte `.pw` header + 4 Field elements = 140 bytes
+pub use shadow_drop_wire::PUBLIC_WITNESS_SIZE as PUBLIC_INPUTS_SIZE;
 
 /// Input for ZK proof generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,22 +17,23 @@ pub struct ZkProofInput {
     pub nullifier_hash: String,
     /// Recipient address as field element (32 bytes hex)
     pub recipient: String,
-    /// Claim amount (as string for large numbers)
+    /// Claim amount (as string for large numbers); public, so the program
+    /// can check it against what it pays
     pub amount: String,
     /// Secret for nullifier derivation (32 bytes hex)
     pub secret: String,
     /// Leaf index in merkle tree
     pub leaf_index: u64,
-    /// Merkle proof path (8 sibling hashes, each 32 bytes hex)
+    /// Merkle proof path (one sibling hash per tree level, each 32 bytes hex)
     pub merkle_path: Vec<String>,
 }
 
 /// Generated ZK proof data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZkProofOutput {
-    /// Groth16 proof bytes (256 bytes, hex encoded)
+    /// Groth16 proof bytes (`GROTH16_PROOF_SIZE` bytes, hex encoded)
     pub proof: String,
-    /// Public inputs bytes (96 bytes, hex encoded)
+    /// Public witness bytes (`PUBLIC_INPUTS_SIZE` bytes, hex encoded)
     pub public_inputs: String,
     /// Merkle root (for verification)
     pub merkle_root: String,
@@ -51,13 +52,13 @@ impl ZkProofInput {
         toml.push_str(&format!("merkle_root = \"{}\"\n", self.merkle_root));
         toml.push_str(&format!("nullifier_hash = \"{}\"\n", self.nullifier_hash));
         toml.push_str(&format!("recipient = \"{}\"\n", self.recipient));
-        toml.push_str("\n");
+        toml.push_str(&format!("amount = \"{}\"\n", self.amount));
+        toml.push('\n');
 
         // Private inputs
-        toml.push_str(&format!("amount = \"{}\"\n", self.amount));
         toml.push_str(&format!("secret = \"{}\"\n", self.secret));
         toml.push_str(&format!("leaf_index = \"{}\"\n", self.leaf_index));
-        toml.push_str("\n");
+        toml.push('\n');
 
         // Merkle path
         toml.push_str("merkle_path = [\n");
@@ -0,0 +1,398 @@
+//! Local Groth16 verification of gnark proofs
+//!
+//! Mirrors gnark's `groth16/bn254.Verify`, including BSB22 commitments:
+//! each commitment is hashed (with the public inputs it covers) into an
+//! extra public input, its proof of knowledge is checked against the
+//! Pedersen key, and the commitment point is added to the input sum.
+//! Proofs are checked here before they are handed out, so a claimant never
+//! pays for a transaction the on-chain verifier would reject.
+
+use std::path::Path;
+
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+use ark_ff::{Field, PrimeField, Zero};
+use sha2::{Digest, Sha256};
+use shadow_drop_wire as wire;
+
+use super::gnark::{
+    self, GnarkProof, GnarkVerifyingKey, g1_bytes, read_proof, read_public_witness,
+    read_verifying_key,
+};
+use super::prover::ProverError;
+use super::types::ZkProofInput;
+use crate::common::merkle::parse_field_hex;
+
+/// Domain separation tag for hashing a commitment into a public input
+const COMMITMENT_DST: &[u8] = b"bsb22-commitment";
+
+/// Domain separation tag for the commitment folding challenge
+const FOLDING_DST: &[u8] = b"G16-BSB22";
+
+/// Verify a proof against `vk`
+pub fn verify(
+    vk: &GnarkVerifyingKey,
+    proof: &GnarkProof,
+    public_inputs: &[Fr],
+) -> Result<(), ProverError> {
+    let nb_public = vk.nb_public_inputs();
+    if public_inputs.len() != nb_public {
+        return Err(ProverError::InvalidProof(format!(
+            "Expected {} public inputs, got {}",
+            nb_public,
+            public_inputs.len()
+        )));
+    }
+    let nb_commitments = vk.public_and_commitment_committed.len();
+    if proof.commitments.len() != nb_commitments || vk.commitment_keys.len() != nb_commitments {
+        return Err(ProverError::InvalidProof(format!(
+            "Expected {} commitments, got {}",
+            nb_commitments,
+            proof.commitments.len()
+        )));
+    }
+
+    // Hash each commitment into the extra public input it stands for
+    let mut inputs = public_inputs.to_vec();
+    let mut commitments_serialized = Vec::with_capacity(32 * nb_commitments);
+    for (commitment, committed) in proof
+        .commitments
+        .iter()
+        .zip(&vk.public_and_commitment_committed)
+    {
+        let mut prehash = g1_bytes(commitment).to_vec();
+        for &index in committed {
+            let value = (index as usize)
+                .checked_sub(1)
+                .and_then(|i| public_inputs.get(i))
+                .ok_or_else(|| {
+                    ProverError::InvalidProof(format!("Committed input {} out of range", index))
+                })?;
+            prehash.extend_from_slice(&fr_bytes(value));
+        }
+        let hashed = hash_to_field(&prehash, COMMITMENT_DST);
+        commitments_serialized.extend_from_slice(&fr_bytes(&hashed));
+        inputs.push(hashed);
+    }
+
+    // Commitment proofs of knowledge, batched with a random-looking challenge
+    if nb_commitments > 0 {
+        let challenge = hash_to_field(&commitments_serialized, FOLDING_DST);
+        let mut power = Fr::ONE;
+        let mut g1 = Vec::with_capacity(nb_commitments + 1);
+        let mut g2 = Vec::with_capacity(nb_commitments + 1);
+        for (commitment, key) in proof.commitments.iter().zip(&vk.commitment_keys) {
+            g1.push((*commitment * power).into_affine());
+            g2.push(key.g_sigma_neg);
+            power *= challenge;
+        }
+        g1.push(proof.commitment_pok);
+        g2.push(vk.commitment_keys[0].g);
+
+        if !Bn254::multi_pairing(g1, g2).is_zero() {
+            return Err(ProverError::InvalidProof(
+                "Commitment proof of knowledge does not verify".to_string(),
+            ));
+        }
+    }
+
+    // Σ K[i+1]·x_i + K[0] + commitments
+    let mut k_sum = G1Projective::from(vk.k[0]);
+    for (point, input) in vk.k[1..].iter().zip(&inputs) {
+        k_sum += *point * input;
+    }
+    for commitment in &proof.commitments {
+        k_sum += commitment;
+    }
+
+    // e(Ar, Bs) = e(α, β)·e(Σ, γ)·e(Krs, δ)
+    let check = Bn254::multi_pairing(
+        [
+            proof.ar,
+            (-vk.alpha_g1.into_group()).into_affine(),
+            (-k_sum).into_affine(),
+            (-proof.krs.into_group()).into_affine(),
+        ],
+        [proof.bs, vk.beta_g2, vk.gamma_g2, vk.delta_g2],
+    );
+    if !check.is_zero() {
+        return Err(ProverError::InvalidProof(
+            "Groth16 pairing check failed".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verify encoded proof and public witness bytes with the `vk.bin` at
+/// `vk_path`, and check the public inputs are the ones of `input`
+pub async fn verify_files(
+    vk_path: &Path,
+    proof: &[u8],
+    public_witness: &[u8],
+    input: &ZkProofInput,
+) -> Result<(), ProverError> {
+    let vk_bytes = tokio::fs::read(vk_path)
+        .await
+        .map_err(|e| ProverError::ConfigError(format!("Failed to read {:?}: {}", vk_path, e)))?;
+
+    let expected = expected_public_inputs(input)?;
+    let proof = proof.to_vec();
+    let public_witness = public_witness.to_vec();
+    let vk_path = vk_path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let vk = read_verifying_key(&vk_bytes).map_err(|e| {
+            ProverError::ConfigError(format!("Invalid verifying key {:?}: {}", vk_path, e))
+        })?;
+        let proof = read_proof(&proof).map_err(invalid)?;
+        let public_inputs = read_public_witness(&public_witness).map_err(invalid)?;
+
+        if public_inputs != expected {
+            return Err(ProverError::InvalidProof(
+                "Public witness does not match the requested claim".to_string(),
+            ));
+        }
+        verify(&vk, &proof, &public_inputs)
+    })
+    .await
+    .map_err(|e| ProverError::InvalidProof(format!("Verification task failed: {}", e)))?
+}
+
+/// Check the `vk.bin` at `vk_path` belongs to the current claim circuit
+///
+/// Keys from an older circuit still parse, but they take a different number
+/// of public inputs, so every proof would fail verification here and on
+/// chain.
+pub async fn check_verifying_key(vk_path: &Path) -> Result<(), ProverError> {
+    let vk_bytes = tokio::fs::read(vk_path)
+        .await
+        .map_err(|e| ProverError::ConfigError(format!("Failed to read {:?}: {}", vk_path, e)))?;
+    let vk = read_verifying_key(&vk_bytes).map_err(|e| {
+        ProverError::ConfigError(format!("Invalid verifying key {:?}: {}", vk_path, e))
+    })?;
+
+    if vk.nb_public_inputs() != wire::NB_PUBLIC_INPUTS {
+        return Err(ProverError::ConfigError(format!(
+            "Verifying key {:?} takes {} public inputs, the claim circuit has {}. \
+             Rebuild the keys with 'just refresh-verifier'.",
+            vk_path,
+            vk.nb_public_inputs(),
+            wire::NB_PUBLIC_INPUTS
+        )));
+    }
+    Ok(())
+}
+
+/// Public inputs in circuit order: merkle_root, nullifier_hash, recipient,
+/// amount
+fn expected_public_inputs(input: &ZkProofInput) -> Result<Vec<Fr>, ProverError> {
+    let amount = input
+        .amount
+        .parse::<u64>()
+        .map_err(|_| ProverError::InvalidInput(format!("Invalid amount: {}", input.amount)))?;
+
+    let mut inputs = [&input.merkle_root, &input.nullifier_hash, &input.recipient]
+        .into_iter()
+        .map(|value| {
+            parse_field_hex(value)
+                .map(|bytes| Fr::from_be_bytes_mod_order(&bytes))
+                .ok_or_else(|| ProverError::InvalidInput(format!("Invalid field hex: {}", value)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    inputs.push(Fr::from(amount));
+    Ok(inputs)
+}
+
+fn invalid(e: gnark::EncodingError) -> ProverError {
+    ProverError::InvalidProof(e.to_string())
+}
+
+/// gnark `fr.Hash(msg, dst, 1)`: 48 uniform bytes, read big-endian and
+/// reduced mod r
+fn hash_to_field(msg: &[u8], dst: &[u8]) -> Fr {
+    Fr::from_be_bytes_mod_order(&expand_message_xmd(msg, dst, 48))
+}
+
+/// expand_message_xmd with SHA-256 (RFC 9380, section 5.3.1)
+fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let mut dst_prime = dst.to_vec();
+    dst_prime.push(dst.len() as u8);
+
+    let b0 = Sha256::new()
+        .chain_update([0u8; 64])
+        .chain_update(msg)
+        .chain_update((len as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&dst_prime)
+        .finalize();
+
+    let mut out = Vec::with_capacity(len + 32);
+    let mut prev = Sha256::new()
+        .chain_update(b0)
+        .chain_update([1u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    out.extend_from_slice(&prev);
+
+    for i in 2..=len.div_ceil(32) {
+        let xored: Vec<u8> = b0.iter().zip(prev.iter()).map(|(a, b)| a ^ b).collect();
+        prev = Sha256::new()
+            .chain_update(xored)
+            .chain_update([i as u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        out.extend_from_slice(&prev);
+    }
+
+    out.truncate(len);
+    out
+}
+
+fn fr_bytes(value: &Fr) -> Vec<u8> {
+    use ark_ff::BigInteger;
+    value.into_bigint().to_bytes_be()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::gnark::CommitmentKey;
+    use ark_bn254::{G1Affine, G2Affine};
+    use ark_ff::UniformRand;
+
+    #[test]
+    fn test_expand_message_xmd_vectors() {
+        // RFC 9380 appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            hex::encode(expand_message_xmd(b"", dst, 0x20)),
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+        );
+        assert_eq!(
+            hex::encode(expand_message_xmd(b"abc", dst, 0x20)),
+            "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+        );
+        assert_eq!(expand_message_xmd(b"abc", dst, 48).len(), 48);
+    }
+
+    /// A verifying key and a valid proof with one commitment, built from
+    /// known trapdoors instead of a circuit
+    fn synthetic_proof(public_inputs: &[Fr]) -> (GnarkVerifyingKey, GnarkProof) {
+        let rng = &mut ark_std::test_rng();
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let [alpha, beta, gamma, delta, sigma, t, m, a, b] = [(); 9].map(|_| Fr::rand(rng));
+        let k: Vec<Fr> = (0..public_inputs.len() + 2)
+            .map(|_| Fr::rand(rng))
+            .collect();
+
+        let commitment = (g1 * m).into_affine();
+        let key = CommitmentKey {
+            g: (g2 * t).into_affine(),
+            g_sigma_neg: (g2 * (-(t * sigma))).into_affine(),
+        };
+
+        // Discrete log of Σ K·x + commitment
+        let hashed = hash_to_field(&g1_bytes(&commitment), COMMITMENT_DST);
+        let mut s = k[0] + m;
+        for (ki, x) in k[1..].iter().zip(public_inputs.iter().chain([&hashed])) {
+            s += *ki * x;
+        }
+        let c = (a * b - alpha * beta - s * gamma) * delta.inverse().unwrap();
+
+        let vk = GnarkVerifyingKey {
+            alpha_g1: (g1 * alpha).into_affine(),
+            beta_g1: (g1 * beta).into_affine(),
+            beta_g2: (g2 * beta).into_affine(),
+            gamma_g2: (g2 * gamma).into_affine(),
+            delta_g1: (g1 * delta).into_affine(),
+            delta_g2: (g2 * delta).into_affine(),
+            k: k.iter().map(|ki| (g1 * ki).into_affine()).collect(),
+            public_and_commitment_committed: vec![vec![]],
+            commitment_keys: vec![key],
+        };
+        let proof = GnarkProof {
+            ar: (g1 * a).into_affine(),
+            bs: (g2 * b).into_affine(),
+            krs: (g1 * c).into_affine(),
+            commitments: vec![commitment],
+            commitment_pok: (commitment * sigma).into_affine(),
+        };
+        (vk, proof)
+    }
+
+    #[test]
+    fn test_verify_proof_with_commitment() {
+        let inputs = [Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)];
+        let (vk, proof) = synthetic_proof(&inputs);
+        assert!(verify(&vk, &proof, &inputs).is_ok());
+
+        // Wrong public input
+        let wrong = [Fr::from(1u64), Fr::from(2u64), Fr::from(4u64)];
+        assert!(matches!(
+            verify(&vk, &proof, &wrong),
+            Err(ProverError::InvalidProof(_))
+        ));
+
+        // Forged proof of knowledge
+        let mut forged = proof.clone();
+        forged.commitment_pok = G1Affine::generator();
+        assert!(matches!(
+            verify(&vk, &forged, &inputs),
+            Err(ProverError::InvalidProof(_))
+        ));
+
+        // Wrong number of inputs
+        assert!(verify(&vk, &proof, &inputs[..2]).is_err());
+    }
+
+    #[test]
+    fn test_verify_sunspot_proof() {
+        // Real sunspot output for the first circuit (three public inputs,
+        // which the current wire format no longer parses)
+        let vk = read_verifying_key(include_bytes!("../../../wire/fixtures/v1/vk.bin")).unwrap();
+        let proof = read_proof(include_bytes!("../../../wire/fixtures/v1/claim.proof")).unwrap();
+        let public_inputs: Vec<Fr> = include_bytes!("../../../wire/fixtures/v1/claim.pw")[12..]
+            .chunks(32)
+            .map(Fr::from_be_bytes_mod_order)
+            .collect();
+
+        assert!(verify(&vk, &proof, &public_inputs).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_verifying_key_rejects_stale_keys() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        // The first circuit's key has one public input too few
+        let stale = manifest.join("../wire/fixtures/v1/vk.bin");
+        assert!(matches!(
+            check_verifying_key(&stale).await,
+            Err(ProverError::ConfigError(_))
+        ));
+
+        let missing = manifest.join("../wire/fixtures/missing/vk.bin");
+        assert!(matches!(
+            check_verifying_key(&missing).await,
+            Err(ProverError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_encoding_roundtrip() {
+        let inputs = [Fr::from(7u64); 3];
+        let (_, proof) = synthetic_proof(&inputs);
+
+        let mut bytes = g1_bytes(&proof.ar).to_vec();
+        bytes.extend_from_slice(&gnark::g2_bytes(&proof.bs));
+        bytes.extend_from_slice(&g1_bytes(&proof.krs));
+        bytes.extend_from_slice(&1u32.to_be_bytes());
+        bytes.extend_from_slice(&g1_bytes(&proof.commitments[0]));
+        bytes.extend_from_slice(&g1_bytes(&proof.commitment_pok));
+
+        // Same size as the sunspot proofs the program accepts
+        assert_eq!(bytes.len(), 388);
+        assert_eq!(read_proof(&bytes).unwrap(), proof);
+    }
+}
@@ -0,0 +1,113 @@
+//! Per-job working directories for the Sunspot prover
+//!
+//! nargo and sunspot read and write fixed file names (`Prover.toml`,
+//! `target/<witness>.gz`, `target/shadow_drop.proof`, ...), so each proving
+//! job gets a private copy of the circuit package in a temp directory. The
+//! large shared artifacts (CCS, proving key) are only read and stay in place.
+
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+use tokio::fs;
+
+use super::prover::ProverError;
+
+/// Files copied from the circuit package into every workspace
+const PACKAGE_FILES: &[&str] = &["Nargo.toml", "target/shadow_drop.json"];
+
+/// Sources copied recursively
+const PACKAGE_DIRS: &[&str] = &["src"];
+
+/// A temporary copy of a circuit package, removed on drop
+#[derive(Debug)]
+pub struct Workspace {
+    dir: TempDir,
+}
+
+impl Workspace {
+    /// Copy the package at `circuit_dir` into a fresh temp directory
+    pub async fn prepare(circuit_dir: &Path) -> Result<Self, ProverError> {
+        let dir = tempfile::Builder::new()
+            .prefix("shadow-drop-proof-")
+            .tempdir()
+            .map_err(|e| ProverError::IoError(format!("Failed to create workspace: {}", e)))?;
+
+        for file in PACKAGE_FILES {
+            let to = dir.path().join(file);
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent).await.map_err(io_error)?;
+            }
+            fs::copy(circuit_dir.join(file), &to)
+                .await
+                .map_err(|e| ProverError::IoError(format!("Failed to copy {}: {}", file, e)))?;
+        }
+        for sub in PACKAGE_DIRS {
+            copy_dir(&circuit_dir.join(sub), &dir.path().join(sub)).await?;
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Workspace root (the package directory nargo runs in)
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Path of a file inside the workspace
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.dir.path().join(path)
+    }
+}
+
+/// Recursively copy a directory
+async fn copy_dir(from: &Path, to: &Path) -> Result<(), ProverError> {
+    let mut pending = vec![(from.to_path_buf(), to.to_path_buf())];
+
+    while let Some((from, to)) = pending.pop() {
+        fs::create_dir_all(&to).await.map_err(io_error)?;
+        let mut entries = fs::read_dir(&from).await.map_err(io_error)?;
+        while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+            let target = to.join(entry.file_name());
+            if entry.file_type().await.map_err(io_error)?.is_dir() {
+                pending.push((entry.path(), target));
+            } else {
+                fs::copy(entry.path(), target).await.map_err(io_error)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn io_error(e: std::io::Error) -> ProverError {
+    ProverError::IoError(format!("Workspace error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_workspaces_are_isolated_and_cleaned_up() {
+        let package = TempDir::new().unwrap();
+        std::fs::write(package.path().join("Nargo.toml"), "[package]").unwrap();
+        std::fs::create_dir_all(package.path().join("src/lib")).unwrap();
+        std::fs::write(package.path().join("src/main.nr"), "fn main() {}").unwrap();
+        std::fs::write(package.path().join("src/lib/util.nr"), "").unwrap();
+        std::fs::create_dir_all(package.path().join("target")).unwrap();
+        std::fs::write(package.path().join("target/shadow_drop.json"), "{}").unwrap();
+
+        let a = Workspace::prepare(package.path()).await.unwrap();
+        let b = Workspace::prepare(package.path()).await.unwrap();
+        assert_ne!(a.path(), b.path());
+        assert!(a.join("src/lib/util.nr").exists());
+        assert!(b.join("target/shadow_drop.json").exists());
+
+        std::fs::write(a.join("Prover.toml"), "a").unwrap();
+        assert!(!b.join("Prover.toml").exists());
+
+        let root = a.path().to_path_buf();
+        drop(a);
+        assert!(!root.exists());
+    }
+}
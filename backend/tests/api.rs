@@ -0,0 +1,868 @@
+//! HTTP API tests against in-memory storage and chain state
+//!
+//! Runs the real router with `InMemoryCampaignRepository`,
+//! `InMemoryAuthRepository` and `InMemoryRpc`, so no database or validator
+//! is needed. ZK proofs use `PROVER_MODE=mock`.
+
+use std::{path::PathBuf, sync::Arc, time::Duration, time::Instant};
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{Value, json};
+use shadow_drop_api::{
+    auth::{AuthStore, InMemoryAuthRepository},
+    chain::{Account, ChainRpc, InMemoryRpc, OnChainCampaign},
+    common::merkle::{MerkleTree, compute_claim_leaf, compute_nullifier},
+    config::{
+        AuthConfig, ChainConfig, Config, IndexerConfig, LoggingConfig, ProverConfig,
+        ProverMode, SecretsConfig, ServerConfig,
+    },
+    jobs::ProofJobStore,
+    repository::InMemoryCampaignRepository,
+    routes::app_routes,
+    state::AppState,
+    zk::{ProverPool, SunspotProver},
+};
+use shadow_drop_wire::{PublicWitness, amount_field, recipient_field, vesting::VestingSchedule};
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+const PROGRAM_ID: &str = "7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v";
+const DEPTH: usize = 4;
+
+/// A wallet that can sign in
+struct Wallet {
+    key: SigningKey,
+    address: String,
+}
+
+impl Wallet {
+    fn new(seed: u8) -> Self {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let address = bs58::encode(key.verifying_key().as_bytes()).into_string();
+        Self { key, address }
+    }
+}
+
+fn config() -> Config {
+    Config {
+        rust_env: "test".to_string(),
+        is_production: false,
+        server: ServerConfig {
+            port: 0,
+            cors_allowed_origins: Vec::new(),
+        },
+        logging: LoggingConfig {
+            level: "info".to_string(),
+        },
+        secrets: SecretsConfig {
+            encryption_key: [0u8; 32],
+        },
+        auth: AuthConfig {
+            nonce_ttl: Duration::from_secs(60),
+            session_ttl: Duration::from_secs(60),
+        },
+        chain: ChainConfig {
+            rpc_url: String::new(),
+            commitment: "confirmed".to_string(),
+            program_id: PROGRAM_ID.to_string(),
+        },
+        indexer: IndexerConfig {
+            enabled: false,
+            poll_interval: Duration::from_secs(10),
+        },
+        prover: ProverConfig {
+            mode: ProverMode::Mock,
+            circuits_dir: PathBuf::new(),
+            workers: 1,
+        },
+    }
+}
+
+fn state(chain: InMemoryRpc) -> AppState {
+    let config = Arc::new(config());
+    let prover = Arc::new(ProverPool::new(
+        Arc::new(SunspotProver::new(PathBuf::new())),
+        1,
+    ));
+    // Never connected: the job queue is not exercised here
+    let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+
+    AppState {
+        start_time: Instant::now(),
+        auth: AuthStore::new(Arc::new(InMemoryAuthRepository::new()), &config.auth),
+        config,
+        campaign_store: Arc::new(InMemoryCampaignRepository::new()),
+        chain: Arc::new(chain),
+        prover,
+        proof_jobs: ProofJobStore::new(db),
+    }
+}
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+    body: Value,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = if method == "GET" {
+        Body::empty()
+    } else {
+        Body::from(body.to_string())
+    };
+
+    send(app, request.body(body).unwrap()).await
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+/// Upload a campaign and its recipient file to the import endpoint
+async fn upload(
+    app: &Router,
+    token: &str,
+    campaign: &Value,
+    file_name: &str,
+    file: &str,
+) -> (StatusCode, Value) {
+    const BOUNDARY: &str = "shadow-drop-test-boundary";
+    let body = format!(
+        "--{b}\r\n\
+         Content-Disposition: form-data; name=\"campaign\"\r\n\
+         Content-Type: application/json\r\n\r\n\
+         {campaign}\r\n\
+         --{b}\r\n\
+         Content-Disposition: form-data; name=\"recipients\"; filename=\"{file_name}\"\r\n\r\n\
+         {file}\r\n\
+         --{b}--\r\n",
+        b = BOUNDARY,
+    );
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/campaigns/import")
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(body))
+        .unwrap();
+    send(app, request).await
+}
+
+/// Sign in through the challenge endpoints and return the bearer token
+async fn sign_in(app: &Router, wallet: &Wallet) -> String {
+    let (status, body) = call(
+        app,
+        "POST",
+        "/api/v1/auth/nonce",
+        None,
+        json!({ "wallet": wallet.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let challenge = &body["data"];
+    let signature = wallet
+        .key
+        .sign(challenge["message"].as_str().unwrap().as_bytes());
+
+    let (status, body) = call(
+        app,
+        "POST",
+        "/api/v1/auth/verify",
+        None,
+        json!({
+            "wallet": wallet.address,
+            "nonce": challenge["nonce"],
+            "signature": bs58::encode(signature.to_bytes()).into_string(),
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["data"]["token"].as_str().unwrap().to_string()
+}
+
+fn secret(n: u8) -> [u8; 32] {
+    let mut secret = [0u8; 32];
+    secret[31] = n;
+    secret
+}
+
+/// A campaign registered on chain by `creator` for `recipients`, and its
+/// creation request
+fn campaign(
+    chain: &InMemoryRpc,
+    address: &str,
+    creator: &Wallet,
+    recipients: &[(&str, u64)],
+) -> Value {
+    let leaves: Vec<(String, u64, [u8; 32])> = recipients
+        .iter()
+        .enumerate()
+        .map(|(i, (wallet, amount))| (wallet.to_string(), *amount, secret(i as u8 + 1)))
+        .collect();
+    let tree = MerkleTree::from_recipients(&leaves, DEPTH);
+    let allocations: Vec<(String, u64)> = recipients
+        .iter()
+        .map(|(wallet, amount)| (wallet.to_string(), *amount))
+        .collect();
+    let claim_tree = MerkleTree::claim_tree(&allocations, DEPTH);
+    let total: u64 = recipients.iter().map(|(_, amount)| amount).sum();
+
+    let onchain = OnChainCampaign {
+        authority: creator.key.verifying_key().to_bytes(),
+        merkle_root: tree.root(),
+        tree_depth: DEPTH as u8,
+        claim_root: claim_tree.root(),
+        total_amount: total,
+        claimed_amount: 0,
+        total_claims: 0,
+        is_active: true,
+        bump: 255,
+        vault_bump: 255,
+        campaign_id: "test".to_string(),
+        vesting_start: 0,
+        vesting_cliff: 0,
+        vesting_duration: 0,
+        vesting_schedule: VestingSchedule::Linear,
+        token_mint: None,
+        token_vault: None,
+    };
+    chain.set_account(
+        address,
+        Account {
+            owner: PROGRAM_ID.to_string(),
+            lamports: 1,
+            data: onchain.encode(),
+        },
+    );
+
+    json!({
+        "address": address,
+        "name": "Test drop",
+        "merkle_root": hex::encode(tree.root()),
+        "tree_depth": DEPTH,
+        "total_amount": total.to_string(),
+        "creator_wallet": creator.address,
+        "recipients": leaves
+            .iter()
+            .map(|(wallet, amount, secret)| json!({
+                "wallet": wallet,
+                "amount": amount.to_string(),
+                "secret": hex::encode(secret),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[tokio::test]
+async fn test_campaign_lifecycle() {
+    let chain = InMemoryRpc::new();
+    let app = app_routes(state(chain.clone()));
+
+    let creator = Wallet::new(1);
+    let alice = Wallet::new(2);
+    let bob = Wallet::new(3);
+    let address = bs58::encode([9u8; 32]).into_string();
+    let request = campaign(
+        &chain,
+        &address,
+        &creator,
+        &[(&alice.address, 700), (&bob.address, 300)],
+    );
+
+    // Creating requires the creator's session
+    let (status, _) = call(&app, "POST", "/api/v1/campaigns", None, request.clone()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let alice_token = sign_in(&app, &alice).await;
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/v1/campaigns",
+        Some(&alice_token),
+        request.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let creator_token = sign_in(&app, &creator).await;
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/api/v1/campaigns",
+        Some(&creator_token),
+        request.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert_eq!(body["data"]["total_recipients"], 2);
+    assert_eq!(body["data"]["merkle_root"], request["merkle_root"]);
+
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/v1/campaigns",
+        Some(&creator_token),
+        request,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = call(
+        &app,
+        "GET",
+        &format!("/api/v1/campaigns/wallet/{}", creator.address),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"]["items"][0]["total_recipients"], 2);
+    assert_eq!(body["data"]["next_cursor"], Value::Null);
+
+    // Eligibility
+    let check = format!("/api/v1/campaigns/{}/check", address);
+    let (_, body) = call(
+        &app,
+        "POST",
+        &check,
+        None,
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(
+        body["data"],
+        json!({ "eligible": true, "amount": "700", "already_claimed": false })
+    );
+    let (_, body) = call(
+        &app,
+        "POST",
+        &check,
+        None,
+        json!({ "wallet": creator.address }),
+    )
+    .await;
+    assert_eq!(body["data"]["eligible"], false);
+
+    let (_, body) = call(
+        &app,
+        "GET",
+        &format!("/api/v1/campaigns/eligible/{}", alice.address),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(body["data"]["items"][0]["address"], address);
+
+    // Proofs for the recipient's leaf, which carry its secret, need the
+    // recipient's session
+    let generate = format!("/api/v1/proofs/{}/generate", address);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &generate,
+        None,
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &generate,
+        Some(&creator_token),
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = call(
+        &app,
+        "POST",
+        &generate,
+        Some(&alice_token),
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let proof = &body["data"];
+    assert_eq!(proof["amount"], "700");
+    assert_eq!(proof["leaf_index"], 0);
+    assert_eq!(proof["merkle_path"].as_array().unwrap().len(), DEPTH);
+    assert_eq!(
+        proof["nullifier_hash"],
+        hex::encode(compute_nullifier(&secret(1), 0))
+    );
+
+    // Allocation proof for the non-ZK claim instructions
+    let (status, body) = call(
+        &app,
+        "POST",
+        &format!("/api/v1/proofs/{}/claim", address),
+        None,
+        json!({ "wallet": bob.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let proof = &body["data"];
+    assert_eq!(proof["amount"], "300");
+    assert_eq!(proof["leaf_index"], 1);
+    assert_eq!(
+        proof["leaf"],
+        hex::encode(compute_claim_leaf(&bob.address, 300))
+    );
+    assert_eq!(proof["proof"].as_array().unwrap().len(), DEPTH);
+
+    let zk_generate = format!("/api/v1/zk-proofs/{}/generate", address);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &zk_generate,
+        None,
+        json!({ "wallet": bob.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &zk_generate,
+        Some(&alice_token),
+        json!({ "wallet": bob.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let bob_token = sign_in(&app, &bob).await;
+
+    // Proof jobs too, for queueing and for reading the result
+    let jobs = format!("/api/v1/zk-proofs/{}/jobs", address);
+    let (status, _) = call(&app, "POST", &jobs, None, json!({ "wallet": bob.address })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &jobs,
+        Some(&alice_token),
+        json!({ "wallet": bob.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let job = format!("/api/v1/zk-proofs/jobs/{}", uuid::Uuid::nil());
+    let (status, _) = call(&app, "GET", &job, None, Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = call(
+        &app,
+        "POST",
+        &zk_generate,
+        Some(&bob_token),
+        json!({ "wallet": bob.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["mock"], true);
+    assert_eq!(body["data"]["amount"], 300);
+    assert_eq!(body["data"]["leaf_index"], 1);
+    // Mock public inputs still have the verifier's layout
+    let public_inputs = hex::decode(body["data"]["public_inputs"].as_str().unwrap()).unwrap();
+    let witness = PublicWitness::from_bytes(&public_inputs).unwrap();
+    assert_eq!(
+        witness.recipient,
+        recipient_field(&bob.key.verifying_key().to_bytes())
+    );
+    assert_eq!(witness.amount, amount_field(300));
+
+    // Claiming needs the recipient's own session, and works once
+    let claim = format!("/api/v1/campaigns/{}/claim", address);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &claim,
+        Some(&creator_token),
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &claim,
+        Some(&alice_token),
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &claim,
+        Some(&alice_token),
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, body) = call(
+        &app,
+        "POST",
+        &check,
+        None,
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(body["data"]["already_claimed"], true);
+    let (status, _) = call(
+        &app,
+        "POST",
+        &zk_generate,
+        Some(&alice_token),
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, body) = call(
+        &app,
+        "GET",
+        &format!("/api/v1/campaigns/eligible/{}", alice.address),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(body["data"]["items"], json!([]));
+}
+
+#[tokio::test]
+async fn test_create_checks_chain_and_input() {
+    let chain = InMemoryRpc::new();
+    let app = app_routes(state(chain.clone()));
+
+    let creator = Wallet::new(1);
+    let token = sign_in(&app, &creator).await;
+    let recipient = bs58::encode([3u8; 32]).into_string();
+
+    // Not registered on chain
+    let address = bs58::encode([9u8; 32]).into_string();
+    let mut request = campaign(&chain, &address, &creator, &[(&recipient, 100)]);
+    chain.remove_account(&address);
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/api/v1/campaigns",
+        Some(&token),
+        request.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["errors"]["details"]
+            .as_str()
+            .unwrap()
+            .contains("not found on chain")
+    );
+
+    // Registered with another total
+    campaign(&chain, &address, &creator, &[(&recipient, 100)]);
+    request["recipients"][0]["amount"] = json!("90");
+    request["total_amount"] = json!("90");
+    request["merkle_root"] = Value::Null;
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/api/v1/campaigns",
+        Some(&token),
+        request.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["errors"]["details"]
+            .as_str()
+            .unwrap()
+            .contains("total_amount is 100")
+    );
+
+    // Field errors come back per row
+    request["recipients"][0]["wallet"] = json!("nope");
+    let (status, body) = call(&app, "POST", "/api/v1/campaigns", Some(&token), request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errors"]["fields"][0]["row"], 0);
+
+    let (status, _) = call(
+        &app,
+        "GET",
+        &format!("/api/v1/campaigns/{}", address),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_import_campaign() {
+    let chain = InMemoryRpc::new();
+    let app = app_routes(state(chain.clone()));
+
+    let creator = Wallet::new(1);
+    let token = sign_in(&app, &creator).await;
+    let alice = bs58::encode([2u8; 32]).into_string();
+    let bob = Wallet::new(3);
+    let address = bs58::encode([9u8; 32]).into_string();
+    let mut request = campaign(
+        &chain,
+        &address,
+        &creator,
+        &[(&alice, 700), (&bob.address, 300)],
+    );
+    let recipients = request["recipients"].take();
+
+    let csv: String = std::iter::once("wallet,amount,secret".to_string())
+        .chain(recipients.as_array().unwrap().iter().map(|r| {
+            format!(
+                "{},{},{}",
+                r["wallet"].as_str().unwrap(),
+                r["amount"].as_str().unwrap(),
+                r["secret"].as_str().unwrap()
+            )
+        }))
+        .collect::<Vec<_>>()
+        .join("\n");
+    request.as_object_mut().unwrap().remove("recipients");
+
+    // Row errors come back before anything is checked on chain
+    let broken = csv.replace(&bob.address, "nope");
+    let (status, body) = upload(&app, &token, &request, "drop.csv", &broken).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errors"]["fields"][0]["field"], "wallet");
+    assert_eq!(body["errors"]["fields"][0]["row"], 1);
+
+    let (status, _) = upload(&app, &token, &request, "drop.xlsx", &csv).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let (status, body) = upload(&app, &token, &request, "drop.csv", &csv).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert_eq!(body["data"]["total_recipients"], 2);
+    assert_eq!(body["data"]["merkle_root"], request["merkle_root"]);
+
+    let bob_token = sign_in(&app, &bob).await;
+    let (status, body) = call(
+        &app,
+        "POST",
+        &format!("/api/v1/zk-proofs/{}/generate", address),
+        Some(&bob_token),
+        json!({ "wallet": bob.address }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["amount"], 300);
+    assert_eq!(body["data"]["leaf_index"], 1);
+}
+
+#[tokio::test]
+async fn test_campaign_listings() {
+    let chain = InMemoryRpc::new();
+    let app = app_routes(state(chain.clone()));
+
+    let creator = Wallet::new(1);
+    let token = sign_in(&app, &creator).await;
+    let alice = bs58::encode([2u8; 32]).into_string();
+    let bob = bs58::encode([3u8; 32]).into_string();
+
+    // Three campaigns totalling 300, 100 and 200; only the last is vested
+    // and only the first two include alice
+    let mut addresses = Vec::new();
+    for (i, (amount, alice_listed)) in [(300, true), (100, true), (200, false)]
+        .into_iter()
+        .enumerate()
+    {
+        let address = bs58::encode([10 + i as u8; 32]).into_string();
+        let recipients: Vec<(&str, u64)> = if alice_listed {
+            vec![(&alice, amount - 50), (&bob, 50)]
+        } else {
+            vec![(&bob, amount)]
+        };
+        let mut request = campaign(&chain, &address, &creator, &recipients);
+        if i == 2 {
+            request["airdrop_type"] = json!("vested");
+        }
+        let (status, body) = call(&app, "POST", "/api/v1/campaigns", Some(&token), request).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+        addresses.push(address);
+    }
+
+    let list = |query: String| {
+        let app = app.clone();
+        let addresses = addresses.clone();
+        async move {
+            let (status, body) = call(&app, "GET", &query, None, Value::Null).await;
+            assert_eq!(status, StatusCode::OK, "{}", body);
+            // Positions of the listed campaigns in `addresses`
+            let items: Vec<usize> = body["data"]["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|c| addresses.iter().position(|a| c["address"] == *a).unwrap())
+                .collect();
+            (
+                items,
+                body["data"]["next_cursor"].as_str().map(String::from),
+            )
+        }
+    };
+    let by_creator = format!("/api/v1/campaigns/wallet/{}", creator.address);
+
+    // Newest first by default
+    let (items, next) = list(by_creator.clone()).await;
+    assert_eq!(items, [2, 1, 0]);
+    assert_eq!(next, None);
+
+    // Paged by amount
+    let query = format!("{}?sort=total_amount&order=asc&limit=2", by_creator);
+    let (items, next) = list(query.clone()).await;
+    assert_eq!(items, [1, 2]);
+    let cursor = next.unwrap();
+    let (items, next) = list(format!("{}&cursor={}", query, cursor)).await;
+    assert_eq!(items, [0]);
+    assert_eq!(next, None);
+
+    // A cursor only works with the sort it was issued for
+    let (status, body) = call(
+        &app,
+        "GET",
+        &format!("{}?cursor={}", by_creator, cursor),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errors"]["fields"][0]["field"], "cursor");
+
+    let (items, _) = list(format!("{}?airdrop_type=vested", by_creator)).await;
+    assert_eq!(items, [2]);
+    let (items, _) = list(format!("{}?asset=token", by_creator)).await;
+    assert!(items.is_empty());
+    let (items, _) = list(format!("{}?status=active&asset=sol", by_creator)).await;
+    assert_eq!(items.len(), 3);
+
+    let (items, _) = list(format!(
+        "/api/v1/campaigns/eligible/{}?sort=total_amount",
+        alice
+    ))
+    .await;
+    assert_eq!(items, [0, 1]);
+
+    let (status, body) = call(
+        &app,
+        "GET",
+        &format!("{}?limit=1000&status=done", by_creator),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errors"]["fields"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_vesting_status() {
+    let chain = InMemoryRpc::new();
+    let app = app_routes(state(chain.clone()));
+
+    let creator = Wallet::new(1);
+    let token = sign_in(&app, &creator).await;
+    let alice = Wallet::new(2);
+    let address = bs58::encode([9u8; 32]).into_string();
+    let request = campaign(&chain, &address, &creator, &[(&alice.address, 700)]);
+
+    // Vesting over 700s, starting in an hour with a 100s cliff; the
+    // request's (absent) schedule is replaced by the one on chain
+    let start = chrono::Utc::now().timestamp() + 3600;
+    let mut account = chain.get_account(&address).await.unwrap().unwrap();
+    let mut onchain = OnChainCampaign::decode(&account.data).unwrap();
+    onchain.vesting_start = start;
+    onchain.vesting_cliff = 100;
+    onchain.vesting_duration = 700;
+    account.data = onchain.encode();
+    chain.set_account(&address, account);
+
+    let (status, body) = call(&app, "POST", "/api/v1/campaigns", Some(&token), request).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    let (status, body) = call(
+        &app,
+        "GET",
+        &format!("/api/v1/campaigns/{}/vesting/{}", address, alice.address),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["allocation"], "700");
+    assert_eq!(data["vested"], "0");
+    assert_eq!(data["claimable"], "0");
+    assert_eq!(data["claimed"], "0");
+    assert_eq!(data["next_unlock_at"], start + 100);
+    assert_eq!(data["vesting_start"], start);
+    assert_eq!(data["vesting_schedule"], json!({ "kind": "linear" }));
+    assert_eq!(
+        data["schedule"],
+        json!([
+            { "at": start + 100, "vested": "100", "linear": true },
+            { "at": start + 700, "vested": "700", "linear": false },
+        ])
+    );
+
+    let (status, _) = call(
+        &app,
+        "GET",
+        &format!("/api/v1/campaigns/{}/vesting/{}", address, creator.address),
+        None,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A vested allocation takes several claims, each reported as it lands,
+    // and stays eligible until the indexer sees all of it paid
+    let alice_token = sign_in(&app, &alice).await;
+    for _ in 0..2 {
+        let (status, body) = call(
+            &app,
+            "POST",
+            &format!("/api/v1/campaigns/{}/claim", address),
+            Some(&alice_token),
+            json!({ "wallet": alice.address }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+    let (_, body) = call(
+        &app,
+        "POST",
+        &format!("/api/v1/campaigns/{}/check", address),
+        None,
+        json!({ "wallet": alice.address }),
+    )
+    .await;
+    assert_eq!(
+        body["data"],
+        json!({ "eligible": true, "amount": "700", "already_claimed": false })
+    );
+}
@@ -17,15 +17,27 @@ use light_sdk::{
     LightDiscriminator,
     PackedAddressTreeInfoExt,
 };
+use shadow_drop_wire::{
+    self as wire,
+    vesting::{ClaimError, VestingSchedule},
+    PublicWitness,
+};
 
 declare_id!("7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v");
 
 /// Groth16 proof size: 256 (A,B,C) + 4 (num_com) + 64 (commitment) + 64 (pok) = 388 bytes
-pub const GROTH16_PROOF_SIZE: usize = 388;
+pub const GROTH16_PROOF_SIZE: usize = wire::PROOF_SIZE;
+
+/// Public inputs size: 12-byte header + 4 Field elements (merkle_root, nullifier_hash, recipient, amount)
+/// gnark-solana verifier expects full .pw file format: 12 + (4 * 32) = 140 bytes
+pub const PUBLIC_INPUTS_SIZE: usize = wire::PUBLIC_WITNESS_SIZE;
 
-/// Public inputs size: 12-byte header + 3 Field elements (merkle_root, nullifier_hash, recipient)
-/// gnark-solana verifier expects full .pw file format: 12 + (3 * 32) = 108 bytes
-pub const PUBLIC_INPUTS_SIZE: usize = 108;
+/// Sunspot Groth16 verifiers of the claim circuit (deployed by `sunspot deploy`),
+/// one per tree depth in `wire::ZK_TREE_DEPTHS`; the ZK claims' recipient and
+/// amount checks only hold if the campaign's verifier did the verifying
+pub const ZK_VERIFIERS: [Pubkey; wire::ZK_TREE_DEPTHS.len()] = [
+    anchor_lang::solana_program::pubkey!("5C5x84vdrZi1h89u4g7VBsKyrBk5AQ1RjnrFFd5KvWuj"), // depth 8
+];
 
 /// Light CPI Signer for this program
 pub const LIGHT_CPI_SIGNER: CpiSigner =
@@ -46,17 +58,24 @@ pub mod shadow_drop {
         ctx: Context<CreateCampaign>,
         campaign_id: String,
         merkle_root: [u8; 32],
+        tree_depth: u8,
+        claim_root: [u8; 32],
         total_amount: u64,
         vesting_start: i64,
         vesting_cliff: i64,
         vesting_duration: i64,
+        vesting_schedule: [u8; wire::vesting::SCHEDULE_SIZE],
     ) -> Result<()> {
         require!(campaign_id.len() <= 32, ShadowDropError::CampaignIdTooLong);
         require!(total_amount > 0, ShadowDropError::InvalidAmount);
 
         let campaign = &mut ctx.accounts.campaign;
+        campaign.version = wire::CAMPAIGN_VERSION;
         campaign.authority = ctx.accounts.authority.key();
         campaign.merkle_root = merkle_root;
+        campaign.tree_depth = tree_depth;
+        require!(campaign.zk_verifier().is_some(), ShadowDropError::UnsupportedTreeDepth);
+        campaign.claim_root = claim_root;
         campaign.total_amount = total_amount;
         campaign.claimed_amount = 0;
         campaign.total_claims = 0;
@@ -72,6 +91,8 @@ pub mod shadow_drop {
         };
         campaign.vesting_cliff = vesting_cliff;
         campaign.vesting_duration = vesting_duration;
+        campaign.vesting_schedule = vesting_schedule;
+        campaign.check_vesting()?;
         
         // Store campaign_id for vault PDA derivation during claims
         let mut id_bytes = [0u8; 32];
@@ -96,117 +117,36 @@ pub mod shadow_drop {
             total_amount,
         )?;
 
-        msg!("SOL Campaign created with {} lamports, vesting_duration: {}s", total_amount, vesting_duration);
-        Ok(())
-    }
-
-    /// Claim tokens using compressed account (cheap!)
-    /// Creates a compressed nullifier to prevent double-claiming
-    pub fn claim_compressed<'info>(
-        ctx: Context<'_, '_, '_, 'info, ClaimCompressed<'info>>,
-        proof: ValidityProof,
-        address_tree_info: PackedAddressTreeInfo,
-        output_state_tree_index: u8,
-        nullifier: [u8; 32],
-        claim_amount: u64,
-    ) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        
-        require!(campaign.is_active, ShadowDropError::CampaignNotActive);
-        require!(
-            campaign.claimed_amount + claim_amount <= campaign.total_amount,
-            ShadowDropError::InsufficientFunds
-        );
-
-        // Setup Light CPI accounts
-        let light_cpi_accounts = CpiAccounts::new(
-            ctx.accounts.claimer.as_ref(),
-            ctx.remaining_accounts,
-            crate::LIGHT_CPI_SIGNER,
-        );
-
-        // Validate address tree (get pubkey for deriving nullifier address)
-        let address_tree_pubkey = address_tree_info
-            .get_tree_pubkey(&light_cpi_accounts)
-            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
-
-        // Note: For production, add validation here
-        // For localnet/hackathon, accept any valid tree
-        msg!("Using address tree: {}", address_tree_pubkey);
-
-        // Derive nullifier address - if it exists, claim already happened
-        let (address, address_seed) = derive_address(
-            &[b"nullifier", &nullifier],
-            &address_tree_pubkey,
-            &crate::ID,
-        );
-
-        // Create compressed nullifier account (will fail if exists = already claimed)
-        let nullifier_account = LightAccount::<CompressedNullifier>::new_init(
-            &crate::ID,
-            Some(address),
-            output_state_tree_index,
-        );
-
-        msg!("Creating nullifier: {:?}", nullifier);
-
-        // Execute Light CPI to create nullifier
-        LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
-            .with_light_account(nullifier_account)?
-            .with_new_addresses(&[
-                address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))
-            ])
-            .invoke(light_cpi_accounts)?;
-
-        // Update campaign stats
-        campaign.claimed_amount += claim_amount;
-        campaign.total_claims += 1;
-
-        // Transfer from vault to claimer using PDA signer
-        let authority_key = campaign.authority;
-        let id_len = campaign.campaign_id_len as usize;
-        let campaign_id_bytes = &campaign.campaign_id[..id_len];
-        let vault_bump = campaign.vault_bump;
-        
-        let vault_seeds: &[&[u8]] = &[
-            b"vault",
-            authority_key.as_ref(),
-            campaign_id_bytes,
-            &[vault_bump],
-        ];
-        let signer = &[vault_seeds];
-
-        let vault = &ctx.accounts.vault;
-        let claimer = &ctx.accounts.claimer;
-
-        // Use invoke_signed to transfer from vault PDA
-        anchor_lang::solana_program::program::invoke_signed(
-            &anchor_lang::solana_program::system_instruction::transfer(
-                vault.key,
-                claimer.key,
-                claim_amount,
-            ),
-            &[
-                vault.to_account_info(),
-                claimer.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-            signer,
-        )?;
+        emit!(CampaignCreated {
+            campaign: campaign.key(),
+            authority: campaign.authority,
+            campaign_id,
+            merkle_root,
+            tree_depth,
+            total_amount,
+            token_mint: None,
+            vesting_start: campaign.vesting_start,
+            vesting_cliff,
+            vesting_duration,
+            vesting_schedule,
+        });
 
-        msg!("Compressed claim successful: {} lamports to {}", claim_amount, claimer.key());
+        msg!("SOL Campaign created with {} lamports, vesting_duration: {}s", total_amount, vesting_duration);
         Ok(())
     }
 
     /// Claim with full ZK proof verification (Hybrid: Sunspot + Light Protocol)
     /// 1. Verifies Groth16 proof via CPI to Sunspot verifier
-    /// 2. Creates compressed nullifier via Light Protocol
-    /// 3. Transfers SOL to claimer
+    /// 2. Records the claim in the recipient's claim record
+    /// 3. Creates compressed nullifier via Light Protocol (first claim only)
+    /// 4. Transfers SOL to claimer
+    /// `claim_amount` is the proven allocation; each claim pays what vested of
+    /// it since the previous one, so the Light params only matter on the first
     pub fn claim_zk_verified<'info>(
         ctx: Context<'_, '_, '_, 'info, ClaimZkVerified<'info>>,
-        // Groth16 proof (256 bytes)
+        // Groth16 proof (388 bytes)
         groth16_proof: [u8; GROTH16_PROOF_SIZE],
-        // Public inputs: merkle_root (32) + nullifier_hash (32) + recipient (32)
+        // Public inputs: header(12) + merkle_root(32) + nullifier_hash(32) + recipient(32) + amount(32)
         public_inputs: [u8; PUBLIC_INPUTS_SIZE],
         // Light Protocol params
         light_proof: ValidityProof,
@@ -219,25 +159,19 @@ pub mod shadow_drop {
         let campaign = &mut ctx.accounts.campaign;
 
         require!(campaign.is_active, ShadowDropError::CampaignNotActive);
-        require!(
-            campaign.claimed_amount + claim_amount <= campaign.total_amount,
-            ShadowDropError::InsufficientFunds
-        );
 
         // =======================================================================
         // Step 1: Verify Groth16 proof via CPI to Sunspot verifier
         // =======================================================================
 
-        // Build verification data: proof || public_inputs
-        let mut verify_data = Vec::with_capacity(GROTH16_PROOF_SIZE + PUBLIC_INPUTS_SIZE);
-        verify_data.extend_from_slice(&groth16_proof);
-        verify_data.extend_from_slice(&public_inputs);
+        // Parse proof and public inputs (rejects malformed headers up front)
+        let (proof, witness) = parse_zk_inputs(&groth16_proof, &public_inputs)?;
 
-        // Create CPI instruction to verifier program
+        // Create CPI instruction to verifier program: proof || public_inputs
         let verify_ix = Instruction {
             program_id: ctx.accounts.zk_verifier.key(),
             accounts: vec![],  // Sunspot verifier doesn't need accounts
-            data: verify_data,
+            data: wire::verifier_instruction_data(&proof, &witness),
         };
 
         // Invoke the verifier - will fail if proof is invalid
@@ -252,61 +186,77 @@ pub mod shadow_drop {
         // Step 2: Validate public inputs match campaign data
         // =======================================================================
 
-        // Extract merkle_root from public inputs (first 32 bytes)
-        let proof_merkle_root: [u8; 32] = public_inputs[0..32].try_into().unwrap();
         require!(
-            proof_merkle_root == campaign.merkle_root,
+            witness.merkle_root == campaign.merkle_root,
             ShadowDropError::InvalidMerkleRoot
         );
-
-        // Extract nullifier_hash from public inputs (bytes 32-64)
-        let proof_nullifier: [u8; 32] = public_inputs[32..64].try_into().unwrap();
         require!(
-            proof_nullifier == nullifier,
+            witness.nullifier_hash == nullifier,
             ShadowDropError::InvalidNullifier
         );
+        // The proof only pays its own recipient, out of its proven allocation
+        check_claim_binding(&witness, &ctx.accounts.claimer.key(), claim_amount)?;
 
         // =======================================================================
-        // Step 3: Create compressed nullifier via Light Protocol
+        // Step 3: Record the claim against the recipient's allocation
         // =======================================================================
 
-        let light_cpi_accounts = CpiAccounts::new(
-            ctx.accounts.claimer.as_ref(),
-            ctx.remaining_accounts,
-            crate::LIGHT_CPI_SIGNER,
-        );
-
-        let address_tree_pubkey = address_tree_info
-            .get_tree_pubkey(&light_cpi_accounts)
-            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
-
-        let (address, address_seed) = derive_address(
-            &[b"nullifier", &nullifier],
-            &address_tree_pubkey,
-            &crate::ID,
-        );
+        let claim_record = &mut ctx.accounts.claim_record;
+        let first_claim = claim_record.amount_claimed == 0;
+        claim_record.record(campaign.key(), ctx.accounts.claimer.key(), claim_amount)?;
+        let now = Clock::get()?.unix_timestamp;
+        let payout = campaign.take_claimable(&mut claim_record.amount_claimed, claim_amount, now)?;
+        let vested_amount = campaign.vesting()?.vested(claim_amount, now);
+        claim_record.claimed_at = now;
 
-        let nullifier_account = LightAccount::<CompressedNullifier>::new_init(
-            &crate::ID,
-            Some(address),
-            output_state_tree_index,
+        require!(
+            campaign.claimed_amount + payout <= campaign.total_amount,
+            ShadowDropError::InsufficientFunds
         );
 
-        msg!("Creating compressed nullifier: {:?}", nullifier);
+        // =======================================================================
+        // Step 4: Create compressed nullifier via Light Protocol (first claim)
+        // =======================================================================
 
-        // Execute Light CPI to create nullifier (prevents double-claiming)
-        light_sdk::cpi::v2::LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, light_proof)
-            .with_light_account(nullifier_account)?
-            .with_new_addresses(&[
-                address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))
-            ])
-            .invoke(light_cpi_accounts)?;
+        if first_claim {
+            let light_cpi_accounts = CpiAccounts::new(
+                ctx.accounts.claimer.as_ref(),
+                ctx.remaining_accounts,
+                crate::LIGHT_CPI_SIGNER,
+            );
+
+            let address_tree_pubkey = address_tree_info
+                .get_tree_pubkey(&light_cpi_accounts)
+                .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+
+            let (address, address_seed) = derive_address(
+                &[b"nullifier", &nullifier],
+                &address_tree_pubkey,
+                &crate::ID,
+            );
+
+            let nullifier_account = LightAccount::<CompressedNullifier>::new_init(
+                &crate::ID,
+                Some(address),
+                output_state_tree_index,
+            );
+
+            msg!("Creating compressed nullifier: {:?}", nullifier);
+
+            // Execute Light CPI to create nullifier (prevents double-claiming)
+            LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, light_proof)
+                .with_light_account(nullifier_account)?
+                .with_new_addresses(&[
+                    address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))
+                ])
+                .invoke(light_cpi_accounts)?;
+        }
 
         // =======================================================================
-        // Step 4: Update campaign and transfer SOL
+        // Step 5: Update campaign and transfer SOL
         // =======================================================================
 
-        campaign.claimed_amount += claim_amount;
+        campaign.claimed_amount += payout;
         campaign.total_claims += 1;
 
         let authority_key = campaign.authority;
@@ -326,7 +276,7 @@ pub mod shadow_drop {
             &anchor_lang::solana_program::system_instruction::transfer(
                 ctx.accounts.vault.key,
                 ctx.accounts.claimer.key,
-                claim_amount,
+                payout,
             ),
             &[
                 ctx.accounts.vault.to_account_info(),
@@ -336,17 +286,29 @@ pub mod shadow_drop {
             signer,
         )?;
 
-        msg!("ZK-verified claim successful: {} lamports to {}", claim_amount, ctx.accounts.claimer.key());
+        emit!(Claimed {
+            campaign: ctx.accounts.campaign.key(),
+            claimer: ctx.accounts.claimer.key(),
+            nullifier: Some(nullifier),
+            amount: payout,
+            allocation: claim_amount,
+            vested_bps: vested_bps(vested_amount, claim_amount),
+            token_mint: None,
+            claimed_at: ctx.accounts.claim_record.claimed_at,
+        });
+
+        msg!("ZK-verified claim successful: {} lamports to {}", payout, ctx.accounts.claimer.key());
         Ok(())
     }
 
     /// Simplified ZK claim - verifies Groth16 proof without Light Protocol
-    /// Uses PDA-based nullifier (simpler, still on-chain verified)
+    /// Records the claim in the recipient's claim record (simpler, still
+    /// on-chain verified), which tracks `claim_amount` and how much of it is paid
     pub fn claim_zk_simple(
         ctx: Context<ClaimZkSimple>,
-        // Groth16 proof (256 bytes)
+        // Groth16 proof (388 bytes)
         groth16_proof: [u8; GROTH16_PROOF_SIZE],
-        // Public inputs: header(12) + merkle_root(32) + nullifier_hash(32) + recipient(32)
+        // Public inputs: header(12) + merkle_root(32) + nullifier_hash(32) + recipient(32) + amount(32)
         public_inputs: [u8; PUBLIC_INPUTS_SIZE],
         // Nullifier for double-claim prevention
         nullifier: [u8; 32],
@@ -355,25 +317,19 @@ pub mod shadow_drop {
         let campaign = &mut ctx.accounts.campaign;
 
         require!(campaign.is_active, ShadowDropError::CampaignNotActive);
-        require!(
-            campaign.claimed_amount + claim_amount <= campaign.total_amount,
-            ShadowDropError::InsufficientFunds
-        );
 
         // =======================================================================
         // Step 1: Verify Groth16 proof via CPI to Sunspot verifier
         // =======================================================================
 
-        // Build verification data: proof || public_inputs
-        let mut verify_data = Vec::with_capacity(GROTH16_PROOF_SIZE + PUBLIC_INPUTS_SIZE);
-        verify_data.extend_from_slice(&groth16_proof);
-        verify_data.extend_from_slice(&public_inputs);
+        // Parse proof and public inputs (rejects malformed headers up front)
+        let (proof, witness) = parse_zk_inputs(&groth16_proof, &public_inputs)?;
 
-        // Create CPI instruction to verifier program
+        // Create CPI instruction to verifier program: proof || public_inputs
         let verify_ix = Instruction {
             program_id: ctx.accounts.zk_verifier.key(),
             accounts: vec![],  // Sunspot verifier doesn't need accounts
-            data: verify_data,
+            data: wire::verifier_instruction_data(&proof, &witness),
         };
 
         // Invoke the verifier - will fail if proof is invalid
@@ -388,45 +344,42 @@ pub mod shadow_drop {
         // Step 2: Validate public inputs match campaign data
         // =======================================================================
 
-        // Public inputs layout from .pw file:
-        // [0..12]   : Header (4 bytes num inputs + 8 bytes padding)
-        // [12..44]  : Merkle Root
-        // [44..76]  : Nullifier Hash
-        // [76..108] : Recipient (Wallet)
-
-        // Extract merkle_root from public inputs (skip 12-byte header)
-        let proof_merkle_root: [u8; 32] = public_inputs[12..44].try_into().unwrap();
         require!(
-            proof_merkle_root == campaign.merkle_root,
+            witness.merkle_root == campaign.merkle_root,
             ShadowDropError::InvalidMerkleRoot
         );
-
-        // Extract nullifier_hash from public inputs
-        let proof_nullifier: [u8; 32] = public_inputs[44..76].try_into().unwrap();
         require!(
-            proof_nullifier == nullifier,
+            witness.nullifier_hash == nullifier,
             ShadowDropError::InvalidNullifier
         );
+        // The proof only pays its own recipient, out of its proven allocation
+        check_claim_binding(&witness, &ctx.accounts.claimer.key(), claim_amount)?;
 
         msg!("✅ Public inputs validated against campaign");
 
         // =======================================================================
-        // Step 3: Record nullifier (PDA-based, prevents double-claim)
+        // Step 3: Record the claim against the recipient's allocation (PDA-based)
         // =======================================================================
 
-        let nullifier_record = &mut ctx.accounts.nullifier_record;
-        nullifier_record.campaign = campaign.key();
-        nullifier_record.nullifier = nullifier;
-        nullifier_record.claimer = ctx.accounts.claimer.key();
-        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.record(campaign.key(), ctx.accounts.claimer.key(), claim_amount)?;
+        let now = Clock::get()?.unix_timestamp;
+        let payout = campaign.take_claimable(&mut claim_record.amount_claimed, claim_amount, now)?;
+        let vested_amount = campaign.vesting()?.vested(claim_amount, now);
+        claim_record.claimed_at = now;
+
+        require!(
+            campaign.claimed_amount + payout <= campaign.total_amount,
+            ShadowDropError::InsufficientFunds
+        );
 
-        msg!("✅ Nullifier recorded: {:?}", &nullifier[..8]);
+        msg!("✅ Claim recorded for nullifier: {:?}", &nullifier[..8]);
 
         // =======================================================================
         // Step 4: Update campaign and transfer SOL
         // =======================================================================
 
-        campaign.claimed_amount += claim_amount;
+        campaign.claimed_amount += payout;
         campaign.total_claims += 1;
 
         let authority_key = campaign.authority;
@@ -446,7 +399,7 @@ pub mod shadow_drop {
             &anchor_lang::solana_program::system_instruction::transfer(
                 ctx.accounts.vault.key,
                 ctx.accounts.claimer.key,
-                claim_amount,
+                payout,
             ),
             &[
                 ctx.accounts.vault.to_account_info(),
@@ -456,42 +409,46 @@ pub mod shadow_drop {
             signer,
         )?;
 
-        msg!("🎉 ZK-verified claim successful: {} lamports to {}", claim_amount, ctx.accounts.claimer.key());
+        emit!(Claimed {
+            campaign: ctx.accounts.campaign.key(),
+            claimer: ctx.accounts.claimer.key(),
+            nullifier: Some(nullifier),
+            amount: payout,
+            allocation: claim_amount,
+            vested_bps: vested_bps(vested_amount, claim_amount),
+            token_mint: None,
+            claimed_at: ctx.accounts.claim_record.claimed_at,
+        });
+
+        msg!("🎉 ZK-verified claim successful: {} lamports to {}", payout, ctx.accounts.claimer.key());
         Ok(())
     }
 
-    /// Claim SPL tokens using ZK Proof (Simple Nullified, no Vesting for MVP)
+    /// Claim SPL tokens using ZK Proof (Simple Nullified, vested like `claim_token`)
+    /// The claim record tracks the allocation like `claim_zk_simple`
     pub fn claim_zk_token(
         ctx: Context<ClaimZkToken>,
-        groth16_proof: [u8; 388],
-        public_inputs: [u8; 108],
+        groth16_proof: [u8; GROTH16_PROOF_SIZE],
+        public_inputs: [u8; PUBLIC_INPUTS_SIZE],
         nullifier: [u8; 32],
         claim_amount: u64,
     ) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
-        let nullifier_record = &mut ctx.accounts.nullifier_record;
+        let claim_record = &mut ctx.accounts.claim_record;
 
         // 1. Basic Checks
         require!(campaign.is_active, ShadowDropError::CampaignNotActive);
-        // require!(!nullifier_record.claimed, ShadowDropError::AlreadyClaimed); // Implied by account init
-        require!(
-            campaign.claimed_amount + claim_amount <= campaign.total_amount,
-            ShadowDropError::InsufficientFunds
-        );
 
         // 2. Verify ZK Proof (CPI to Sunspot Verifier)
         // Public inputs must be validated against campaign state to prevent replay
         
+        let (proof, witness) = parse_zk_inputs(&groth16_proof, &public_inputs)?;
+
         // Create CPI instruction to verifier program
         let verify_ix = Instruction {
             program_id: ctx.accounts.zk_verifier.key(),
             accounts: vec![],  // Sunspot verifier doesn't need accounts
-            data: {
-                 let mut verify_data = Vec::with_capacity(388 + 108);
-                 verify_data.extend_from_slice(&groth16_proof);
-                 verify_data.extend_from_slice(&public_inputs);
-                 verify_data
-            },
+            data: wire::verifier_instruction_data(&proof, &witness),
         };
 
         anchor_lang::solana_program::program::invoke(
@@ -502,45 +459,31 @@ pub mod shadow_drop {
         msg!("✅ Groth16 ZK proof verified on-chain!");
 
         // 3. Verify Public Inputs
-        // [0..12] Header
-        // [12..44] Merkle Root
-        // [44..76] Nullifier Hash
-        // [76..108] Recipient
-        
+
         // Check Merkle Root
-        let proof_merkle_root: [u8; 32] = public_inputs[12..44].try_into().unwrap();
-        require!(proof_merkle_root == campaign.merkle_root, ShadowDropError::InvalidMerkleRoot);
+        require!(witness.merkle_root == campaign.merkle_root, ShadowDropError::InvalidMerkleRoot);
 
         // Check Nullifier Hash (Matches passed nullifier)
-        let proof_nullifier: [u8; 32] = public_inputs[44..76].try_into().unwrap();
-        require!(proof_nullifier == nullifier, ShadowDropError::InvalidNullifier);
-
-        // Check Recipient (Prevent stealing proof)
-        // Convert signer key to field element bytes (BE padding at start of 32 bytes)
-        // Same logic as backend: take first 31 bytes, place at end (Big Endian) 
-        // Backend: `field_bytes[32-len..].copy_from_slice(&bytes[..len])` (bytes at END)
-        let mut claimer_field_bytes = [0u8; 32];
-        let claimer_pubkey_bytes = ctx.accounts.claimer.key().to_bytes();
-        // Use first 31 bytes of pubkey
-        claimer_field_bytes[1..].copy_from_slice(&claimer_pubkey_bytes[0..31]);
-        
-        let proof_recipient: [u8; 32] = public_inputs[76..108].try_into().unwrap();
-        // STRICT CHECK:
+        require!(witness.nullifier_hash == nullifier, ShadowDropError::InvalidNullifier);
+
+        // Check Recipient and Allocation (Prevent stealing or inflating proof)
+        check_claim_binding(&witness, &ctx.accounts.claimer.key(), claim_amount)?;
+
+        // 4. Update State (the record tracks the recipient's allocation across claims)
+        claim_record.record(campaign.key(), ctx.accounts.claimer.key(), claim_amount)?;
+        let now = Clock::get()?.unix_timestamp;
+        let payout = campaign.take_claimable(&mut claim_record.amount_claimed, claim_amount, now)?;
+        let vested_amount = campaign.vesting()?.vested(claim_amount, now);
+        claim_record.claimed_at = now;
+
         require!(
-            proof_recipient == claimer_field_bytes, 
-            ShadowDropError::Unauthorized // Proof recipient does not match signer
+            campaign.claimed_amount + payout <= campaign.total_amount,
+            ShadowDropError::InsufficientFunds
         );
 
-        // 4. Update State
-        campaign.claimed_amount += claim_amount;
+        campaign.claimed_amount += payout;
         campaign.total_claims += 1;
 
-        nullifier_record.campaign = campaign.key();
-        nullifier_record.nullifier = nullifier;
-        nullifier_record.claimer = ctx.accounts.claimer.key();
-        nullifier_record.claimed_at = Clock::get()?.unix_timestamp;
-        // nullifier_record.claimed = true; // Implied by existence
-
         // 5. Transfer Tokens
         let seeds = &[
             b"campaign",
@@ -560,68 +503,61 @@ pub mod shadow_drop {
                 },
                 signer,
             ),
-            claim_amount,
+            payout,
         )?;
 
-        msg!("🎉 ZK-verified Token Claim successful: {} tokens to {}", claim_amount, ctx.accounts.claimer.key());
+        emit!(Claimed {
+            campaign: campaign.key(),
+            claimer: ctx.accounts.claimer.key(),
+            nullifier: Some(nullifier),
+            amount: payout,
+            allocation: claim_amount,
+            vested_bps: vested_bps(vested_amount, claim_amount),
+            token_mint: campaign.token_mint,
+            claimed_at: claim_record.claimed_at,
+        });
+
+        msg!("🎉 ZK-verified Token Claim successful: {} tokens to {}", payout, ctx.accounts.claimer.key());
         Ok(())
     }
 
     /// Legacy claim (for backwards compatibility)
     /// Uses regular PDA claim records
-    /// Supports vesting: calculates claimable amount based on time
+    /// Supports vesting: pays what has vested since the previous claim, so a
+    /// recipient claims again as more of the allocation vests
+    /// `amount` must be the claimer's allocation in `claim_root`, proven by
+    /// `leaf_index` and `proof` (sibling hashes, leaf level first)
     pub fn claim(
         ctx: Context<Claim>,
-        claim_amount: u64,
+        amount: u64,
+        leaf_index: u32,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
         
         require!(campaign.is_active, ShadowDropError::CampaignNotActive);
+        let allocation = campaign.proven_allocation(
+            &ctx.accounts.claimer.key(),
+            amount,
+            leaf_index,
+            &proof,
+        )?;
 
-        // Calculate claimable amount based on vesting schedule
+        // Pay what vested since the previous claim (created on the first one)
         let now = Clock::get()?.unix_timestamp;
-        let vested_amount = if campaign.vesting_duration == 0 {
-            // Instant: 100% vested immediately
-            claim_amount
-        } else {
-            // Check if still in cliff period
-            let cliff_end = campaign.vesting_start + campaign.vesting_cliff;
-            if now < cliff_end {
-                return Err(ShadowDropError::VestingCliffNotReached.into());
-            }
-            
-            // Calculate linear vesting
-            let vesting_end = campaign.vesting_start + campaign.vesting_duration;
-            let elapsed = now - campaign.vesting_start;
-            
-            if now >= vesting_end {
-                // Fully vested
-                claim_amount
-            } else {
-                // Partially vested: (elapsed / duration) * claim_amount
-                let vested = (claim_amount as i128 * elapsed as i128 / campaign.vesting_duration as i128) as u64;
-                vested
-            }
-        };
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.record(campaign.key(), ctx.accounts.claimer.key(), allocation)?;
+        let payout = campaign.take_claimable(&mut claim_record.amount_claimed, allocation, now)?;
+        let vested_amount = campaign.vesting()?.vested(allocation, now);
+        claim_record.claimed_at = now;
 
-        require!(vested_amount > 0, ShadowDropError::NothingToVest);
         require!(
-            campaign.claimed_amount + vested_amount <= campaign.total_amount,
+            campaign.claimed_amount + payout <= campaign.total_amount,
             ShadowDropError::InsufficientFunds
         );
 
-        // Mark claim in claim record
-        let claim_record = &mut ctx.accounts.claim_record;
-        require!(!claim_record.claimed, ShadowDropError::AlreadyClaimed);
-        
-        claim_record.campaign = campaign.key();
-        claim_record.claimer = ctx.accounts.claimer.key();
-        claim_record.amount = vested_amount;
-        claim_record.claimed = true;
-        claim_record.claimed_at = now;
-
         // Update campaign stats
-        campaign.claimed_amount += vested_amount;
+        campaign.claimed_amount += payout;
         campaign.total_claims += 1;
 
         // Transfer from vault to claimer using PDA signer
@@ -646,7 +582,7 @@ pub mod shadow_drop {
             &anchor_lang::solana_program::system_instruction::transfer(
                 vault.key,
                 claimer.key,
-                vested_amount,
+                payout,
             ),
             &[
                 vault.to_account_info(),
@@ -656,7 +592,18 @@ pub mod shadow_drop {
             signer,
         )?;
 
-        msg!("Claim successful: {} lamports (vested) to {}", vested_amount, claimer.key());
+        emit!(Claimed {
+            campaign: ctx.accounts.campaign.key(),
+            claimer: claimer.key(),
+            nullifier: None,
+            amount: payout,
+            allocation,
+            vested_bps: vested_bps(vested_amount, allocation),
+            token_mint: None,
+            claimed_at: now,
+        });
+
+        msg!("Claim successful: {} lamports (vested) to {}", payout, claimer.key());
         Ok(())
     }
 
@@ -676,6 +623,15 @@ pub mod shadow_drop {
         **vault.try_borrow_mut_lamports()? -= remaining;
         **authority.try_borrow_mut_lamports()? += remaining;
 
+        emit!(CampaignClosed {
+            campaign: campaign.key(),
+            authority: authority.key(),
+            returned_amount: remaining,
+            claimed_amount: campaign.claimed_amount,
+            total_claims: campaign.total_claims,
+            closed_at: Clock::get()?.unix_timestamp,
+        });
+
         msg!("Campaign closed, {} lamports returned", remaining);
         Ok(())
     }
@@ -686,17 +642,24 @@ pub mod shadow_drop {
         ctx: Context<CreateTokenCampaign>,
         campaign_id: String,
         merkle_root: [u8; 32],
+        tree_depth: u8,
+        claim_root: [u8; 32],
         total_amount: u64,
         vesting_start: i64,
         vesting_cliff: i64,
         vesting_duration: i64,
+        vesting_schedule: [u8; wire::vesting::SCHEDULE_SIZE],
     ) -> Result<()> {
         require!(campaign_id.len() <= 32, ShadowDropError::CampaignIdTooLong);
         require!(total_amount > 0, ShadowDropError::InvalidAmount);
 
         let campaign = &mut ctx.accounts.campaign;
+        campaign.version = wire::CAMPAIGN_VERSION;
         campaign.authority = ctx.accounts.authority.key();
         campaign.merkle_root = merkle_root;
+        campaign.tree_depth = tree_depth;
+        require!(campaign.zk_verifier().is_some(), ShadowDropError::UnsupportedTreeDepth);
+        campaign.claim_root = claim_root;
         campaign.total_amount = total_amount;
         campaign.claimed_amount = 0;
         campaign.total_claims = 0;
@@ -712,6 +675,8 @@ pub mod shadow_drop {
         };
         campaign.vesting_cliff = vesting_cliff;
         campaign.vesting_duration = vesting_duration;
+        campaign.vesting_schedule = vesting_schedule;
+        campaign.check_vesting()?;
         
         // Store campaign_id
         let mut id_bytes = [0u8; 32];
@@ -737,55 +702,58 @@ pub mod shadow_drop {
             total_amount,
         )?;
 
+        emit!(CampaignCreated {
+            campaign: ctx.accounts.campaign.key(),
+            authority: ctx.accounts.authority.key(),
+            campaign_id,
+            merkle_root,
+            tree_depth,
+            total_amount,
+            token_mint: Some(ctx.accounts.token_mint.key()),
+            vesting_start: ctx.accounts.campaign.vesting_start,
+            vesting_cliff,
+            vesting_duration,
+            vesting_schedule,
+        });
+
         msg!("Token Campaign created with {} tokens, mint: {}", total_amount, ctx.accounts.token_mint.key());
         Ok(())
     }
 
     /// Claim tokens from a token campaign (legacy flow)
-    pub fn claim_token(ctx: Context<ClaimToken>, claim_amount: u64) -> Result<()> {
+    /// Proves the allocation and pays the vested increment like `claim`
+    pub fn claim_token(
+        ctx: Context<ClaimToken>,
+        amount: u64,
+        leaf_index: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
         
         require!(campaign.is_active, ShadowDropError::CampaignNotActive);
         require!(campaign.token_mint.is_some(), ShadowDropError::NotTokenCampaign);
+        let allocation = campaign.proven_allocation(
+            &ctx.accounts.claimer.key(),
+            amount,
+            leaf_index,
+            &proof,
+        )?;
 
-        // Calculate claimable amount based on vesting schedule
+        // Pay what vested since the previous claim (created on the first one)
         let now = Clock::get()?.unix_timestamp;
-        let vested_amount = if campaign.vesting_duration == 0 {
-            claim_amount
-        } else {
-            let cliff_end = campaign.vesting_start + campaign.vesting_cliff;
-            if now < cliff_end {
-                return Err(ShadowDropError::VestingCliffNotReached.into());
-            }
-            
-            let vesting_end = campaign.vesting_start + campaign.vesting_duration;
-            let elapsed = now - campaign.vesting_start;
-            
-            if now >= vesting_end {
-                claim_amount
-            } else {
-                (claim_amount as i128 * elapsed as i128 / campaign.vesting_duration as i128) as u64
-            }
-        };
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.record(campaign.key(), ctx.accounts.claimer.key(), allocation)?;
+        let payout = campaign.take_claimable(&mut claim_record.amount_claimed, allocation, now)?;
+        let vested_amount = campaign.vesting()?.vested(allocation, now);
+        claim_record.claimed_at = now;
 
-        require!(vested_amount > 0, ShadowDropError::NothingToVest);
         require!(
-            campaign.claimed_amount + vested_amount <= campaign.total_amount,
+            campaign.claimed_amount + payout <= campaign.total_amount,
             ShadowDropError::InsufficientFunds
         );
 
-        // Mark claim in claim record
-        let claim_record = &mut ctx.accounts.claim_record;
-        require!(!claim_record.claimed, ShadowDropError::AlreadyClaimed);
-        
-        claim_record.campaign = campaign.key();
-        claim_record.claimer = ctx.accounts.claimer.key();
-        claim_record.amount = vested_amount;
-        claim_record.claimed = true;
-        claim_record.claimed_at = now;
-
         // Update campaign stats
-        campaign.claimed_amount += vested_amount;
+        campaign.claimed_amount += payout;
         campaign.total_claims += 1;
 
         // Transfer tokens from vault to claimer using campaign PDA as signer
@@ -813,14 +781,67 @@ pub mod shadow_drop {
                 },
                 &[campaign_seeds],
             ),
-            vested_amount,
+            payout,
         )?;
 
-        msg!("Token claim successful: {} tokens to {}", vested_amount, ctx.accounts.claimer.key());
+        emit!(Claimed {
+            campaign: ctx.accounts.campaign.key(),
+            claimer: ctx.accounts.claimer.key(),
+            nullifier: None,
+            amount: payout,
+            allocation,
+            vested_bps: vested_bps(vested_amount, allocation),
+            token_mint: ctx.accounts.campaign.token_mint,
+            claimed_at: now,
+        });
+
+        msg!("Token claim successful: {} tokens to {}", payout, ctx.accounts.claimer.key());
         Ok(())
     }
 }
 
+/// Parse the ZK instruction arguments with the shared wire format
+fn parse_zk_inputs(
+    groth16_proof: &[u8; GROTH16_PROOF_SIZE],
+    public_inputs: &[u8; PUBLIC_INPUTS_SIZE],
+) -> Result<(wire::Proof, PublicWitness)> {
+    let proof = wire::Proof::from_bytes(groth16_proof)
+        .map_err(|_| error!(ShadowDropError::InvalidPublicInputs))?;
+    let witness = PublicWitness::from_bytes(public_inputs)
+        .map_err(|_| error!(ShadowDropError::InvalidPublicInputs))?;
+    Ok((proof, witness))
+}
+
+/// Require the proof's public recipient and amount to be the signer and
+/// the allocation claimed against
+///
+/// Same encodings as the backend: the first 31 bytes of the pubkey and the
+/// amount, both big-endian.
+fn check_claim_binding(witness: &PublicWitness, claimer: &Pubkey, allocation: u64) -> Result<()> {
+    require!(
+        witness.recipient == wire::recipient_field(&claimer.to_bytes()),
+        ShadowDropError::InvalidRecipient
+    );
+    require!(
+        witness.amount == wire::amount_field(allocation),
+        ShadowDropError::InvalidClaimAmount
+    );
+    Ok(())
+}
+
+/// SHA-256 over the concatenated slices (syscall), for `wire::claim_tree`
+fn sha256v(parts: &[&[u8]]) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(parts).to_bytes()
+}
+
+/// Share of `allocation` that `amount` is, in basis points (10_000 = all of it)
+fn vested_bps(amount: u64, allocation: u64) -> u16 {
+    if allocation == 0 {
+        return 0;
+    }
+    (amount as u128 * 10_000 / allocation as u128).min(10_000) as u16
+}
+
 // ============================================================================
 // Compressed Account Structures (Light Protocol)
 // ============================================================================
@@ -837,8 +858,11 @@ pub struct CompressedNullifier {}
 
 #[account]
 pub struct Campaign {
+    pub version: u8,                // Account layout, wire::CAMPAIGN_VERSION
     pub authority: Pubkey,
     pub merkle_root: [u8; 32],
+    pub tree_depth: u8,             // Depth of the merkle_root tree, picks the ZK verifier
+    pub claim_root: [u8; 32],       // Allocation tree for claim/claim_token (wire::claim_tree)
     pub total_amount: u64,
     pub claimed_amount: u64,
     pub total_claims: u64,
@@ -851,27 +875,139 @@ pub struct Campaign {
     pub vesting_start: i64,      // Unix timestamp when vesting starts (0 = instant)
     pub vesting_cliff: i64,      // Cliff period in seconds
     pub vesting_duration: i64,   // Total vesting duration in seconds
+    pub vesting_schedule: [u8; wire::vesting::SCHEDULE_SIZE], // Encoded VestingSchedule
     // Token fields (None = SOL campaign)
     pub token_mint: Option<Pubkey>,    // Token mint address (None = SOL)
     pub token_vault: Option<Pubkey>,   // Token vault ATA address
 }
 
+impl Campaign {
+    /// Verifier of the claim circuit for this campaign's tree depth
+    pub fn zk_verifier(&self) -> Option<Pubkey> {
+        wire::ZK_TREE_DEPTHS
+            .iter()
+            .position(|depth| *depth == self.tree_depth)
+            .map(|i| ZK_VERIFIERS[i])
+    }
+
+    /// Vesting schedule claims are paid by (shared with the API)
+    pub fn vesting(&self) -> Result<wire::vesting::Vesting> {
+        let schedule = VestingSchedule::from_bytes(&self.vesting_schedule)
+            .map_err(|_| error!(ShadowDropError::InvalidVestingSchedule))?;
+        Ok(wire::vesting::Vesting {
+            start: self.vesting_start,
+            cliff: self.vesting_cliff,
+            duration: self.vesting_duration,
+            schedule,
+        })
+    }
+
+    /// Reject vesting parameters claims could not be paid by
+    pub fn check_vesting(&self) -> Result<()> {
+        self.vesting()?.validate().map_err(|e| {
+            msg!("{}", e);
+            error!(ShadowDropError::InvalidVestingSchedule)
+        })
+    }
+
+    /// What a claim at `now` pays out of `allocation` by the vesting schedule:
+    /// the vested part beyond `amount_claimed`, which is moved up to it
+    pub fn take_claimable(&self, amount_claimed: &mut u64, allocation: u64, now: i64) -> Result<u64> {
+        let payout = self
+            .vesting()?
+            .claimable(allocation, *amount_claimed, now)
+            .map_err(|e| match e {
+                ClaimError::BeforeCliff => error!(ShadowDropError::VestingCliffNotReached),
+                ClaimError::FullyClaimed => error!(ShadowDropError::AlreadyClaimed),
+                ClaimError::NothingVested => error!(ShadowDropError::NothingToVest),
+            })?;
+        *amount_claimed += payout;
+        Ok(payout)
+    }
+
+    /// `amount`, once proven to be `claimer`'s allocation in `claim_root`
+    pub fn proven_allocation(
+        &self,
+        claimer: &Pubkey,
+        amount: u64,
+        leaf_index: u32,
+        proof: &[[u8; 32]],
+    ) -> Result<u64> {
+        let leaf = wire::claim_tree::leaf(sha256v, &claimer.to_bytes(), amount);
+        let root = wire::claim_tree::compute_root(sha256v, leaf, leaf_index, proof);
+        require!(root == Some(self.claim_root), ShadowDropError::InvalidAllocationProof);
+        Ok(amount)
+    }
+}
+
+/// Claims of one recipient in a campaign - shared by every claim path
+/// (legacy and ZK), so the allocation is never paid out twice
 #[account]
 pub struct ClaimRecord {
     pub campaign: Pubkey,
     pub claimer: Pubkey,
-    pub amount: u64,
-    pub claimed: bool,
+    pub allocation: u64,       // Proven allocation the vesting applies to
+    pub amount_claimed: u64,   // Paid out so far, across claims
+    pub claimed_at: i64,       // When last claimed
+}
+
+impl ClaimRecord {
+    /// Start or continue `claimer`'s claims of `allocation`; once anything
+    /// is paid, later claims (on any path) must prove the same allocation
+    pub fn record(&mut self, campaign: Pubkey, claimer: Pubkey, allocation: u64) -> Result<()> {
+        require!(
+            self.amount_claimed == 0 || self.allocation == allocation,
+            ShadowDropError::AllocationMismatch
+        );
+        self.campaign = campaign;
+        self.claimer = claimer;
+        self.allocation = allocation;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// A campaign was created and funded (`create_campaign`, `create_token_campaign`)
+#[event]
+pub struct CampaignCreated {
+    pub campaign: Pubkey,
+    pub authority: Pubkey,
+    pub campaign_id: String,
+    pub merkle_root: [u8; 32],
+    pub tree_depth: u8,
+    pub total_amount: u64,
+    pub token_mint: Option<Pubkey>,  // None = SOL campaign
+    pub vesting_start: i64,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub vesting_schedule: [u8; wire::vesting::SCHEDULE_SIZE],
+}
+
+/// A claim was paid out (every `claim*` instruction)
+#[event]
+pub struct Claimed {
+    pub campaign: Pubkey,
+    pub claimer: Pubkey,
+    pub nullifier: Option<[u8; 32]>,  // None for non-ZK claims
+    pub amount: u64,                  // Paid out by this claim
+    pub allocation: u64,              // Proven allocation the vesting applies to
+    pub vested_bps: u16,              // Vested share of allocation in basis points
+    pub token_mint: Option<Pubkey>,   // None = SOL
     pub claimed_at: i64,
 }
 
-/// ZK Nullifier record - prevents double-claim using ZK proof
-#[account]
-pub struct NullifierRecord {
-    pub campaign: Pubkey,      // Campaign this nullifier belongs to
-    pub nullifier: [u8; 32],   // The nullifier hash from ZK proof
-    pub claimer: Pubkey,       // Who claimed (for auditing)
-    pub claimed_at: i64,       // When claimed
+/// A campaign was closed and its remaining vault lamports returned
+#[event]
+pub struct CampaignClosed {
+    pub campaign: Pubkey,
+    pub authority: Pubkey,
+    pub returned_amount: u64,
+    pub claimed_amount: u64,
+    pub total_claims: u64,
+    pub closed_at: i64,
 }
 
 // ============================================================================
@@ -887,11 +1023,13 @@ pub struct CreateCampaign<'info> {
     #[account(
         init,
         payer = authority,
-        // 8 discriminator + 32 authority + 32 merkle_root + 8 total + 8 claimed + 8 claims
+        // 8 discriminator + 1 version + 32 authority + 32 merkle_root + 1 tree_depth + 32 claim_root
+        // + 8 total + 8 claimed + 8 claims
         // + 1 is_active + 1 bump + 1 vault_bump + 32 campaign_id + 1 id_len
-        // + 8 vesting_start + 8 cliff + 8 duration
+        // + 8 vesting_start + 8 cliff + 8 duration + SCHEDULE_SIZE vesting_schedule
         // + 33 token_mint (Option<Pubkey>) + 33 token_vault (Option<Pubkey>)
-        space = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 32 + 1 + 8 + 8 + 8 + 33 + 33,
+        space = 8 + 1 + 32 + 32 + 1 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 32 + 1 + 8 + 8 + 8
+            + wire::vesting::SCHEDULE_SIZE + 33 + 33,
         seeds = [b"campaign", authority.key().as_ref(), campaign_id.as_bytes()],
         bump
     )]
@@ -908,24 +1046,6 @@ pub struct CreateCampaign<'info> {
     pub system_program: Program<'info, System>,
 }
 
-/// Compressed claim context - uses Light Protocol
-#[derive(Accounts)]
-pub struct ClaimCompressed<'info> {
-    #[account(mut)]
-    pub claimer: Signer<'info>,
-
-    #[account(mut)]
-    pub campaign: Account<'info, Campaign>,
-
-    /// CHECK: Vault PDA - validated by seeds
-    #[account(mut)]
-    pub vault: AccountInfo<'info>,
-
-    pub system_program: Program<'info, System>,
-
-    // Note: Light Protocol accounts come via remaining_accounts
-}
-
 /// ZK-verified claim context - uses Sunspot verifier + Light Protocol
 #[derive(Accounts)]
 pub struct ClaimZkVerified<'info> {
@@ -941,16 +1061,26 @@ pub struct ClaimZkVerified<'info> {
 
     /// CHECK: Sunspot Groth16 verifier program
     /// This is the deployed verifier from `sunspot deploy`
+    #[account(constraint = Some(zk_verifier.key()) == campaign.zk_verifier() @ ShadowDropError::InvalidVerifier)]
     pub zk_verifier: AccountInfo<'info>,
 
+    /// Recipient's claim record, shared with the non-ZK claims
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        space = 8 + 32 + 32 + 8 + 8 + 8,
+        seeds = [b"claim", campaign.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub claim_record: Account<'info, ClaimRecord>,
+
     pub system_program: Program<'info, System>,
 
     // Note: Light Protocol accounts come via remaining_accounts
 }
 
-/// Simplified ZK claim context - uses Sunspot verifier + PDA nullifier
+/// Simplified ZK claim context - uses Sunspot verifier + PDA claim record
 #[derive(Accounts)]
-#[instruction(groth16_proof: [u8; 388], public_inputs: [u8; 108], nullifier: [u8; 32])]
 pub struct ClaimZkSimple<'info> {
     #[account(mut)]
     pub claimer: Signer<'info>,
@@ -963,24 +1093,24 @@ pub struct ClaimZkSimple<'info> {
     pub vault: AccountInfo<'info>,
 
     /// CHECK: Sunspot Groth16 verifier program
+    #[account(constraint = Some(zk_verifier.key()) == campaign.zk_verifier() @ ShadowDropError::InvalidVerifier)]
     pub zk_verifier: AccountInfo<'info>,
 
-    /// PDA-based nullifier record (prevents double-claim)
+    /// Recipient's claim record, shared with the non-ZK claims
     #[account(
-        init,
+        init_if_needed,
         payer = claimer,
-        space = 8 + 32 + 32 + 32 + 8,  // discriminator + campaign + nullifier + claimer + timestamp
-        seeds = [b"nullifier", campaign.key().as_ref(), &nullifier],
+        space = 8 + 32 + 32 + 8 + 8 + 8,
+        seeds = [b"claim", campaign.key().as_ref(), claimer.key().as_ref()],
         bump
     )]
-    pub nullifier_record: Account<'info, NullifierRecord>,
+    pub claim_record: Account<'info, ClaimRecord>,
 
     pub system_program: Program<'info, System>,
 }
 
 /// Simplified ZK claim context for Token Campaign
 #[derive(Accounts)]
-#[instruction(groth16_proof: [u8; 388], public_inputs: [u8; 108], nullifier: [u8; 32])]
 pub struct ClaimZkToken<'info> {
     #[account(mut)]
     pub claimer: Signer<'info>,
@@ -1014,17 +1144,18 @@ pub struct ClaimZkToken<'info> {
     pub token_mint: Account<'info, Mint>,
 
     /// CHECK: Sunspot Groth16 verifier program
+    #[account(constraint = Some(zk_verifier.key()) == campaign.zk_verifier() @ ShadowDropError::InvalidVerifier)]
     pub zk_verifier: AccountInfo<'info>,
 
-    /// PDA-based nullifier record (prevents double-claim)
+    /// Recipient's claim record, shared with the non-ZK claims
     #[account(
-        init,
+        init_if_needed,
         payer = claimer,
-        space = 8 + 32 + 32 + 32 + 8,
-        seeds = [b"nullifier", campaign.key().as_ref(), &nullifier],
+        space = 8 + 32 + 32 + 8 + 8 + 8,
+        seeds = [b"claim", campaign.key().as_ref(), claimer.key().as_ref()],
         bump
     )]
-    pub nullifier_record: Account<'info, NullifierRecord>,
+    pub claim_record: Account<'info, ClaimRecord>,
 
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -1045,9 +1176,9 @@ pub struct Claim<'info> {
     pub vault: AccountInfo<'info>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = claimer,
-        space = 8 + 32 + 32 + 8 + 1 + 8,
+        space = 8 + 32 + 32 + 8 + 8 + 8,
         seeds = [b"claim", campaign.key().as_ref(), claimer.key().as_ref()],
         bump
     )]
@@ -1083,7 +1214,8 @@ pub struct CreateTokenCampaign<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 32 + 1 + 8 + 8 + 8 + 33 + 33,
+        space = 8 + 1 + 32 + 32 + 1 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 32 + 1 + 8 + 8 + 8
+            + wire::vesting::SCHEDULE_SIZE + 33 + 33,
         seeds = [b"campaign", authority.key().as_ref(), campaign_id.as_bytes()],
         bump
     )]
@@ -1149,9 +1281,9 @@ pub struct ClaimToken<'info> {
     pub token_mint: Account<'info, Mint>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = claimer,
-        space = 8 + 32 + 32 + 8 + 1 + 8,
+        space = 8 + 32 + 32 + 8 + 8 + 8,
         seeds = [b"claim", campaign.key().as_ref(), claimer.key().as_ref()],
         bump
     )]
@@ -1184,6 +1316,8 @@ pub enum ShadowDropError {
     VestingCliffNotReached,
     #[msg("Nothing to vest yet")]
     NothingToVest,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
     #[msg("Not a token campaign")]
     NotTokenCampaign,
     #[msg("Invalid merkle root - proof doesn't match campaign")]
@@ -1192,4 +1326,18 @@ pub enum ShadowDropError {
     InvalidNullifier,
     #[msg("ZK proof verification failed")]
     ZkProofVerificationFailed,
+    #[msg("Malformed Groth16 proof or public inputs")]
+    InvalidPublicInputs,
+    #[msg("Allocation proof doesn't match the campaign's claim root")]
+    InvalidAllocationProof,
+    #[msg("Invalid recipient - proof was generated for another wallet")]
+    InvalidRecipient,
+    #[msg("Invalid claim amount - proof doesn't match the amount claimed")]
+    InvalidClaimAmount,
+    #[msg("Invalid ZK verifier - not the claim circuit's verifier for the campaign's tree depth")]
+    InvalidVerifier,
+    #[msg("Allocation doesn't match the one already being claimed")]
+    AllocationMismatch,
+    #[msg("No ZK verifier is deployed for this merkle tree depth")]
+    UnsupportedTreeDepth,
 }
@@ -17,6 +17,8 @@ import {
     Sparkles, Lock, AlertCircle, Loader2,
     Calendar, ChevronDown, Coins, X, ChevronRight, Search
 } from "lucide-react";
+import { RELEASE_PERIODS, encodeVestingSchedule, type VestingSchedule } from "../lib/vesting";
+import { TREE_DEPTH } from "../lib/merkle";
 import { useNetwork } from "../providers/NetworkProvider";
 import { Toaster, toast } from 'sonner';
 
@@ -420,7 +422,7 @@ function CreateAirdrop({ balance }: { balance: number | null }) {
     const [loadingTokens, setLoadingTokens] = useState(false);
     const [showTokenModal, setShowTokenModal] = useState(false);
     const [tokenSearch, setTokenSearch] = useState("");
-    const { publicKey, program, connection } = useShadowDrop();
+    const { publicKey, program, connection, wallet } = useShadowDrop();
     const { network } = useNetwork();
 
     // Fetch wallet token accounts
@@ -689,7 +691,7 @@ function CreateAirdrop({ balance }: { balance: number | null }) {
             const [vaultPDA] = deriveVaultPDA(publicKey, campaignId);
 
             // Generate merkle root from recipients
-            const merkleRoot = await generateMerkleRoot(recipientList);
+            const { root: merkleRoot, claimRoot, secrets } = await generateMerkleRoot(recipientList);
 
             // Convert amount to lamports
             const lamports = new BN(Math.floor(amountToSend * LAMPORTS_PER_SOL));
@@ -697,10 +699,19 @@ function CreateAirdrop({ balance }: { balance: number | null }) {
             // Calculate vesting params
             const vestingStartTs = vestingEnabled ? new BN(0) : new BN(0); // 0 = use current time on-chain
             const vestingCliffSeconds = vestingEnabled ? new BN(parseInt(vestingCliffDays || "0") * 86400) : new BN(0);
-            const vestingDurationSeconds = vestingEnabled ? new BN(parseInt(vestingDurationDays || "30") * 86400) : new BN(0);
+            // Released in steps of the chosen frequency, which must divide the duration
+            const releasePeriod = RELEASE_PERIODS[vestingFrequency];
+            const vestingSchedule: VestingSchedule = vestingEnabled
+                ? { kind: "step", period_seconds: releasePeriod }
+                : { kind: "linear" };
+            const durationSeconds = vestingEnabled
+                ? Math.ceil(parseInt(vestingDurationDays || "30") * 86400 / releasePeriod) * releasePeriod
+                : 0;
+            const vestingDurationSeconds = new BN(durationSeconds);
 
             // Call smart contract instruction based on token type
             let tx;
+            let totalRaw = lamports; // base units, as stored on-chain
 
             if (tokenType === "sol") {
                 // SOL Campaign
@@ -708,10 +719,13 @@ function CreateAirdrop({ balance }: { balance: number | null }) {
                     .createCampaign(
                         campaignId,
                         Array.from(merkleRoot),
+                        TREE_DEPTH,
+                        Array.from(claimRoot),
                         lamports,
                         vestingStartTs,
                         vestingCliffSeconds,
-                        vestingDurationSeconds
+                        vestingDurationSeconds,
+                        encodeVestingSchedule(vestingSchedule)
                     )
                     .accounts({
                         authority: publicKey,
@@ -732,15 +746,19 @@ function CreateAirdrop({ balance }: { balance: number | null }) {
 
                 // Calculate token amount with decimals
                 const tokenAmountBN = new BN(parseFloat(tokenAmount || "0") * Math.pow(10, tokenDecimals));
+                totalRaw = tokenAmountBN;
 
                 tx = await program.methods
                     .createTokenCampaign(
                         campaignId,
                         Array.from(merkleRoot),
+                        TREE_DEPTH,
+                        Array.from(claimRoot),
                         tokenAmountBN,
                         vestingStartTs,
                         vestingCliffSeconds,
-                        vestingDurationSeconds
+                        vestingDurationSeconds,
+                        encodeVestingSchedule(vestingSchedule)
                     )
                     .accounts({
                         authority: publicKey,
@@ -764,25 +782,32 @@ function CreateAirdrop({ balance }: { balance: number | null }) {
 
             // Save to backend API for off-chain data (recipient list)
             const { createCampaign } = await import("../lib/api");
+            if (!wallet.signMessage) throw new Error("Wallet does not support message signing");
             await createCampaign({
                 address: campaignAddress,
                 name: campaignName,
                 merkle_root: Buffer.from(merkleRoot).toString('hex'),
-                total_amount: amountToSend.toString(),
+                tree_depth: TREE_DEPTH, // stored on chain too, picks the ZK verifier
+                total_amount: totalRaw.toString(), // checked against the on-chain campaign
                 creator_wallet: publicKey.toBase58(),
                 tx_signature: tx,
                 vault_address: vaultPDA.toBase58(), // Store vault for claims
-                recipients: recipientList.map(r => ({ ...r, amount: r.amount.toString() })),
+                recipients: recipientList.map((r, i) => ({
+                    ...r,
+                    amount: r.amount.toString(),
+                    secret: Buffer.from(secrets[i]).toString('hex'),
+                })),
                 // Vesting params for backend
                 airdrop_type: vestingEnabled ? "vested" : "instant",
                 vesting_start: Math.floor(Date.now() / 1000),  // Current timestamp as integer
                 vesting_cliff_seconds: parseInt(vestingCliffDays || "0") * 86400,
-                vesting_duration_seconds: parseInt(vestingDurationDays || "30") * 86400,
+                vesting_duration_seconds: durationSeconds,
+                vesting_schedule: vestingSchedule,
                 // Token params (only for SPL tokens)
                 token_mint: tokenType === "spl" ? tokenMint : undefined,
                 token_symbol: tokenType === "spl" ? tokenSymbol : undefined,
                 token_decimals: tokenType === "spl" ? tokenDecimals : undefined,
-            });
+            }, wallet.signMessage);
 
             console.log("Campaign created on-chain and saved to backend");
             setSuccess(true);
@@ -1502,7 +1527,7 @@ function ClaimAirdrop() {
     const [success, setSuccess] = useState(false);
     const [lastTx, setLastTx] = useState("");
     const [lastCampaignName, setLastCampaignName] = useState("");
-    const { publicKey, program } = useShadowDrop();
+    const { publicKey, program, wallet } = useShadowDrop();
 
     // Fetch eligible campaigns on mount and when wallet changes
     useEffect(() => {
@@ -1538,9 +1563,13 @@ function ClaimAirdrop() {
             // Step 1: Generate ZK proof from backend
             console.log("🔐 Generating ZK proof for campaign:", campaign.name);
             const { generateZkProof, markClaimed } = await import("../lib/api");
-            const { deriveNullifierRecordPDA, ZK_VERIFIER_PROGRAM_ID } = await import("../lib/pda");
+            const { deriveClaimRecordPDA, ZK_VERIFIER_PROGRAM_ID } = await import("../lib/pda");
 
-            const proofData = await generateZkProof(campaign.address, publicKey.toBase58());
+            if (!wallet.signMessage) throw new Error("Wallet does not support message signing");
+            const proofData = await generateZkProof(campaign.address, publicKey.toBase58(), wallet.signMessage);
+            if (proofData.mock) {
+                throw new Error("Backend returned a mock proof (PROVER_MODE=mock); it cannot be verified on-chain.");
+            }
             console.log("✅ ZK Proof generated:", {
                 groth16_proof_length: proofData.groth16_proof.length,
                 public_inputs_length: proofData.public_inputs.length,
@@ -1578,9 +1607,9 @@ function ClaimAirdrop() {
                 nullifier_bytes: nullifier.length,
             });
 
-            // Derive nullifier record PDA
-            const [nullifierRecordPDA] = deriveNullifierRecordPDA(campaignPDA, nullifier);
-            console.log("🔑 Nullifier Record PDA:", nullifierRecordPDA.toBase58());
+            // Derive claim record PDA (one per recipient, whichever claim path)
+            const [claimRecordPDA] = deriveClaimRecordPDA(campaignPDA, publicKey);
+            console.log("🔑 Claim Record PDA:", claimRecordPDA.toBase58());
 
             // Use ZK-verified claim
             let tx;
@@ -1595,15 +1624,16 @@ function ClaimAirdrop() {
                 // Derive User ATA
                 const claimerTokenAccount = await getAssociatedTokenAddress(mintPubkey, publicKey);
 
-                // Derive claim_record PDA
-                const [claimRecordPDA] = PublicKey.findProgramAddressSync(
-                    [Buffer.from("claim"), campaignPDA.toBuffer(), publicKey.toBuffer()],
-                    program.programId
-                );
-
                 // Use claimToken (legacy) - ZK token claim not yet implemented in contract
+                // It pays the allocation proven against the campaign's claim root
+                const { getClaimProof } = await import("../lib/api");
+                const allocation = await getClaimProof(campaign.address, publicKey.toBase58());
                 tx = await program.methods
-                    .claimToken(claimAmount)
+                    .claimToken(
+                        new BN(allocation.amount),
+                        allocation.leaf_index,
+                        allocation.proof.map(hex => Array.from(hexToBytes(hex))) as any
+                    )
                     .accounts({
                         claimer: publicKey,
                         campaign: campaignPDA,
@@ -1625,8 +1655,8 @@ function ClaimAirdrop() {
 
                 tx = await program.methods
                     .claimZkSimple(
-                        Array.from(groth16Proof) as any,     // [u8; 256]
-                        Array.from(publicInputs) as any,     // [u8; 96]
+                        Array.from(groth16Proof) as any,     // [u8; 388] (GROTH16_PROOF_SIZE)
+                        Array.from(publicInputs) as any,     // [u8; 140]
                         Array.from(nullifier) as any,        // [u8; 32]
                         claimAmount
                     )
@@ -1635,7 +1665,7 @@ function ClaimAirdrop() {
                         campaign: campaignPDA,
                         vault: vaultAddress,
                         zkVerifier: ZK_VERIFIER_PROGRAM_ID,
-                        nullifierRecord: nullifierRecordPDA,
+                        claimRecord: claimRecordPDA,
                         systemProgram: SystemProgram.programId,
                     })
                     .preInstructions([
@@ -1649,7 +1679,8 @@ function ClaimAirdrop() {
             console.log("⚡ ZK Proof verified on-chain! Nullifier:", proofData.nullifier.slice(0, 16) + "...");
 
             // Mark as claimed in backend
-            await markClaimed(campaign.address, publicKey.toBase58());
+            if (!wallet.signMessage) throw new Error("Wallet does not support message signing");
+            await markClaimed(campaign.address, publicKey.toBase58(), wallet.signMessage);
 
             // Remove from eligible list
             setEligibleCampaigns(prev => prev.filter(c => c.address !== campaign.address));
@@ -1,14 +1,34 @@
+import { utils } from '@coral-xyz/anchor';
+import type { VestingSchedule } from './vesting';
+
 const API_BASE = import.meta.env.VITE_API_URL || 'http://localhost:8000';
 
+/** Wallet adapter `signMessage` */
+export type SignMessage = (message: Uint8Array) => Promise<Uint8Array>;
+
+export interface AuthSession {
+    token: string;
+    wallet: string;
+    expires_at: string;
+}
+
+interface AuthChallenge {
+    nonce: string;
+    message: string;
+    expires_at: string;
+}
+
 export interface RecipientInput {
     wallet: string;
     amount: number | string;
+    secret?: string; // Hex claim secret used in the merkle leaf
 }
 
 export interface CreateCampaignRequest {
     address: string;
     name: string;
     merkle_root: string;
+    tree_depth?: number; // Merkle tree depth (backend default: 8)
     total_amount: number | string;
     creator_wallet: string;
     tx_signature?: string;
@@ -19,6 +39,7 @@ export interface CreateCampaignRequest {
     vesting_start?: number;
     vesting_cliff_seconds?: number;
     vesting_duration_seconds?: number;
+    vesting_schedule?: VestingSchedule; // Default linear
     // Token fields (optional, None = SOL campaign)
     token_mint?: string;
     token_symbol?: string;
@@ -29,6 +50,8 @@ export interface CampaignInfo {
     id: string;
     address: string;
     name: string;
+    merkle_root: string;
+    tree_depth: number;
     total_amount: number;
     total_recipients: number;
     claimed_count: number;
@@ -36,6 +59,7 @@ export interface CampaignInfo {
     vault_address?: string; // PDA vault address for claims
     tx_signature?: string;
     created_at: string;
+    closed_at?: string | null; // Set once the program closed the campaign
     // Vesting fields
     airdrop_type?: string;
     vesting_start?: number;
@@ -62,18 +86,30 @@ export interface ProofResponse {
     secret: string;
 }
 
+/**
+ * Allocation proof for the non-ZK `claim` / `claim_token` instructions
+ */
+export interface ClaimProofResponse {
+    claim_root: string;  // 32 bytes hex
+    leaf: string;        // 32 bytes hex
+    leaf_index: number;
+    proof: string[];     // sibling hashes, leaf level first
+    amount: string;      // allocation in base units
+}
+
 /**
  * ZK Proof response from Sunspot prover
  */
 export interface ZkProofResponse {
     groth16_proof: string;      // 388 bytes hex (Proof + 1 Commitment)
-    public_inputs: string;       // 108 bytes hex (12 header + 96 data)
+    public_inputs: string;       // 140 bytes hex (12 header + 128 data)
     nullifier_hash: string;      // 32 bytes hex
     nullifier: string;           // 32 bytes hex (for contract)
     amount: number;              // in lamports
     secret: string;              // 32 bytes hex
     merkle_root: string;         // 32 bytes hex
     leaf_index: number;
+    mock: boolean;               // true when produced by PROVER_MODE=mock (not a real proof)
 }
 
 export interface EligibleCampaign {
@@ -84,11 +120,60 @@ export interface EligibleCampaign {
     total_recipients: number;
     vault_address?: string;
     created_at: string;
+    airdrop_type: string;
+    closed_at?: string | null;
     token_mint?: string;
     token_symbol?: string;
     token_decimals?: number;
 }
 
+/** One page of a campaign listing */
+export interface Page<T> {
+    items: T[];
+    next_cursor: string | null;
+}
+
+/** Filters, sorting and position of a campaign listing */
+export interface ListOptions {
+    limit?: number;
+    cursor?: string;
+    sort?: 'created_at' | 'name' | 'total_amount';
+    order?: 'asc' | 'desc';
+    asset?: 'sol' | 'token';
+    airdrop_type?: string;
+    status?: 'active' | 'closed';
+}
+
+function listQuery(options: ListOptions): string {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(options)) {
+        if (value !== undefined) params.set(key, String(value));
+    }
+    const query = params.toString();
+    return query ? `?${query}` : '';
+}
+
+async function fetchPage<T>(url: string, options: ListOptions): Promise<Page<T>> {
+    const response = await fetch(`${url}${listQuery(options)}`);
+    const result: ApiResponse<Page<T>> = await response.json();
+    if (!result.success || !result.data) {
+        throw new Error(result.message || 'Failed to list campaigns');
+    }
+    return result.data;
+}
+
+/** Every item of a listing, following its cursors */
+async function fetchAll<T>(url: string, options: ListOptions): Promise<T[]> {
+    const items: T[] = [];
+    let cursor: string | undefined;
+    do {
+        const page = await fetchPage<T>(url, { ...options, limit: 100, cursor });
+        items.push(...page.items);
+        cursor = page.next_cursor ?? undefined;
+    } while (cursor);
+    return items;
+}
+
 interface ApiResponse<T> {
     success: boolean;
     code: number;
@@ -96,13 +181,65 @@ interface ApiResponse<T> {
     message: string;
 }
 
+const sessions = new Map<string, AuthSession>();
+
 /**
- * Create a new campaign
+ * Sign in with a wallet: sign a server challenge and get a session token
  */
-export async function createCampaign(data: CreateCampaignRequest): Promise<CampaignInfo> {
-    const response = await fetch(`${API_BASE}/api/v1/campaigns`, {
+export async function signIn(wallet: string, signMessage: SignMessage): Promise<AuthSession> {
+    const nonceResponse = await fetch(`${API_BASE}/api/v1/auth/nonce`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ wallet }),
+    });
+    const challenge: ApiResponse<AuthChallenge> = await nonceResponse.json();
+    if (!challenge.success || !challenge.data) {
+        throw new Error(challenge.message || 'Failed to get sign-in challenge');
+    }
+
+    const signature = await signMessage(new TextEncoder().encode(challenge.data.message));
+    const verifyResponse = await fetch(`${API_BASE}/api/v1/auth/verify`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            wallet,
+            nonce: challenge.data.nonce,
+            signature: utils.bytes.bs58.encode(signature),
+        }),
+    });
+    const result: ApiResponse<AuthSession> = await verifyResponse.json();
+    if (!result.success || !result.data) {
+        throw new Error(result.message || 'Failed to sign in');
+    }
+    sessions.set(wallet, result.data);
+    return result.data;
+}
+
+/**
+ * Authorization header for a wallet, signing in again when the session
+ * is missing or about to expire
+ */
+async function authHeaders(wallet: string, signMessage: SignMessage): Promise<Record<string, string>> {
+    let session = sessions.get(wallet);
+    if (!session || Date.parse(session.expires_at) - Date.now() < 30_000) {
+        session = await signIn(wallet, signMessage);
+    }
+    return { Authorization: `Bearer ${session.token}` };
+}
+
+/**
+ * Create a new campaign (signed in as `creator_wallet`)
+ */
+export async function createCampaign(
+    data: CreateCampaignRequest,
+    signMessage: SignMessage,
+): Promise<CampaignInfo> {
+    const response = await fetch(`${API_BASE}/api/v1/campaigns`, {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+            ...(await authHeaders(data.creator_wallet, signMessage)),
+        },
         body: JSON.stringify(data),
     });
     const result: ApiResponse<CampaignInfo> = await response.json();
@@ -124,16 +261,28 @@ export async function getCampaign(address: string): Promise<CampaignInfo | null>
     return result.data || null;
 }
 
+/**
+ * Get one page of the campaigns created by a wallet
+ */
+export async function listCampaignsByWallet(
+    wallet: string,
+    options: ListOptions = {},
+): Promise<Page<CampaignInfo>> {
+    return fetchPage(`${API_BASE}/api/v1/campaigns/wallet/${wallet}`, options);
+}
+
 /**
  * Get all campaigns created by a wallet
  */
-export async function getCampaignsByWallet(wallet: string): Promise<CampaignInfo[]> {
-    const response = await fetch(`${API_BASE}/api/v1/campaigns/wallet/${wallet}`);
-    const result: ApiResponse<CampaignInfo[]> = await response.json();
-    if (!result.success || !result.data) {
+export async function getCampaignsByWallet(
+    wallet: string,
+    options: Omit<ListOptions, 'limit' | 'cursor'> = {},
+): Promise<CampaignInfo[]> {
+    try {
+        return await fetchAll(`${API_BASE}/api/v1/campaigns/wallet/${wallet}`, options);
+    } catch {
         return [];
     }
-    return result.data;
 }
 
 /**
@@ -153,12 +302,19 @@ export async function checkEligibility(address: string, wallet: string): Promise
 }
 
 /**
- * Mark a claim as completed
+ * Mark a claim as completed (signed in as the claiming wallet)
  */
-export async function markClaimed(address: string, wallet: string): Promise<void> {
+export async function markClaimed(
+    address: string,
+    wallet: string,
+    signMessage: SignMessage,
+): Promise<void> {
     const response = await fetch(`${API_BASE}/api/v1/campaigns/${address}/claim`, {
         method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
+        headers: {
+            'Content-Type': 'application/json',
+            ...(await authHeaders(wallet, signMessage)),
+        },
         body: JSON.stringify({ wallet }),
     });
     const result: ApiResponse<void> = await response.json();
@@ -168,12 +324,19 @@ export async function markClaimed(address: string, wallet: string): Promise<void
 }
 
 /**
- * Generate ZK proof for a claim
+ * Generate ZK proof for a claim (signed in as the claiming wallet)
  */
-export async function generateProof(address: string, wallet: string): Promise<ProofResponse> {
+export async function generateProof(
+    address: string,
+    wallet: string,
+    signMessage: SignMessage,
+): Promise<ProofResponse> {
     const response = await fetch(`${API_BASE}/api/v1/proofs/${address}/generate`, {
         method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
+        headers: {
+            'Content-Type': 'application/json',
+            ...(await authHeaders(wallet, signMessage)),
+        },
         body: JSON.stringify({ wallet }),
     });
     const result: ApiResponse<ProofResponse> = await response.json();
@@ -184,30 +347,154 @@ export async function generateProof(address: string, wallet: string): Promise<Pr
 }
 
 /**
- * Get all campaigns where wallet is eligible to claim
+ * Get the allocation proof `claim` / `claim_token` need
  */
-export async function getEligibleCampaigns(wallet: string): Promise<EligibleCampaign[]> {
-    const response = await fetch(`${API_BASE}/api/v1/campaigns/eligible/${wallet}`);
-    const result: ApiResponse<EligibleCampaign[]> = await response.json();
+export async function getClaimProof(address: string, wallet: string): Promise<ClaimProofResponse> {
+    const response = await fetch(`${API_BASE}/api/v1/proofs/${address}/claim`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ wallet }),
+    });
+    const result: ApiResponse<ClaimProofResponse> = await response.json();
     if (!result.success || !result.data) {
+        throw new Error(result.message || 'Failed to generate claim proof');
+    }
+    return result.data;
+}
+
+/**
+ * Get one page of the campaigns where wallet is eligible to claim
+ */
+export async function listEligibleCampaigns(
+    wallet: string,
+    options: ListOptions = {},
+): Promise<Page<EligibleCampaign>> {
+    return fetchPage(`${API_BASE}/api/v1/campaigns/eligible/${wallet}`, options);
+}
+
+/**
+ * Get all campaigns where wallet is eligible to claim
+ */
+export async function getEligibleCampaigns(
+    wallet: string,
+    options: Omit<ListOptions, 'limit' | 'cursor'> = {},
+): Promise<EligibleCampaign[]> {
+    try {
+        return await fetchAll(`${API_BASE}/api/v1/campaigns/eligible/${wallet}`, options);
+    } catch {
         return [];
     }
+}
+
+/** A point of a recipient's unlock schedule (unix seconds) */
+export interface UnlockPoint {
+    at: number;
+    vested: string;
+    linear: boolean; // Grows linearly to the next point, else holds until it
+}
+
+/** What a recipient has vested, claimed and can claim now */
+export interface VestingStatus {
+    wallet: string;
+    allocation: string;
+    vested: string;
+    claimed: string;
+    claimable: string;
+    next_unlock_at: number | null;
+    vesting_start: number;
+    vesting_cliff_seconds: number;
+    vesting_duration_seconds: number;
+    vesting_schedule: VestingSchedule;
+    schedule: UnlockPoint[];
+    as_of: number;
+}
+
+/**
+ * Get a recipient's vesting status in a campaign
+ */
+export async function getVestingStatus(address: string, wallet: string): Promise<VestingStatus> {
+    const response = await fetch(`${API_BASE}/api/v1/campaigns/${address}/vesting/${wallet}`);
+    const result: ApiResponse<VestingStatus> = await response.json();
+    if (!result.success || !result.data) {
+        throw new Error(result.message || 'Failed to get vesting status');
+    }
     return result.data;
 }
 
+export type ProofJobStatus = 'queued' | 'running' | 'succeeded' | 'failed';
+
+export interface ProofJob {
+    id: string;
+    campaign_address: string;
+    wallet: string;
+    status: ProofJobStatus;
+    attempts: number;
+    result?: ZkProofResponse;
+    error?: string;
+    created_at: string;
+    updated_at: string;
+}
+
 /**
- * Generate Sunspot ZK proof for a claim (Groth16)
- * This proof can be verified on-chain by the Sunspot verifier program
+ * Queue Groth16 proof generation for a claim (signed in as the claiming wallet)
+ * Returns immediately; poll the job with `getZkProofJob`
  */
-export async function generateZkProof(address: string, wallet: string): Promise<ZkProofResponse> {
-    const response = await fetch(`${API_BASE}/api/v1/zk-proofs/${address}/generate`, {
+export async function submitZkProofJob(
+    address: string,
+    wallet: string,
+    signMessage: SignMessage,
+): Promise<ProofJob> {
+    const response = await fetch(`${API_BASE}/api/v1/zk-proofs/${address}/jobs`, {
         method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
+        headers: {
+            'Content-Type': 'application/json',
+            ...(await authHeaders(wallet, signMessage)),
+        },
         body: JSON.stringify({ wallet }),
     });
-    const result: ApiResponse<ZkProofResponse> = await response.json();
+    const result: ApiResponse<ProofJob> = await response.json();
     if (!result.success || !result.data) {
-        throw new Error(result.message || 'Failed to generate ZK proof');
+        throw new Error(result.message || 'Failed to queue ZK proof');
     }
     return result.data;
 }
+
+/**
+ * Get the status of a proof job (signed in as the job's wallet)
+ */
+export async function getZkProofJob(
+    id: string,
+    wallet: string,
+    signMessage: SignMessage,
+): Promise<ProofJob> {
+    const response = await fetch(`${API_BASE}/api/v1/zk-proofs/jobs/${id}`, {
+        headers: await authHeaders(wallet, signMessage),
+    });
+    const result: ApiResponse<ProofJob> = await response.json();
+    if (!result.success || !result.data) {
+        throw new Error(result.message || 'Failed to get ZK proof job');
+    }
+    return result.data;
+}
+
+/**
+ * Generate Sunspot ZK proof for a claim (Groth16)
+ * This proof can be verified on-chain by the Sunspot verifier program.
+ * Queues a proof job and polls until it finishes.
+ */
+export async function generateZkProof(
+    address: string,
+    wallet: string,
+    signMessage: SignMessage,
+    pollIntervalMs = 1000,
+): Promise<ZkProofResponse> {
+    let job = await submitZkProofJob(address, wallet, signMessage);
+    while (job.status === 'queued' || job.status === 'running') {
+        await new Promise((resolve) => setTimeout(resolve, pollIntervalMs));
+        job = await getZkProofJob(job.id, wallet, signMessage);
+    }
+    if (job.status === 'failed' || !job.result) {
+        throw new Error(job.error || 'Failed to generate ZK proof');
+    }
+    return job.result;
+}
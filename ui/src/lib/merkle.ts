@@ -1,7 +1,7 @@
 import { PublicKey } from "@solana/web3.js";
 
 const API_URL = import.meta.env.VITE_API_URL || "http://localhost:8000";
-const TREE_DEPTH = 8;
+export const TREE_DEPTH = 8;
 
 /**
  * Async Poseidon hash via Backend API
@@ -41,15 +41,14 @@ export interface Recipient {
 export function generateSecret(): Uint8Array {
     const secret = new Uint8Array(32);
     crypto.getRandomValues(secret);
-    secret[31] &= 0x1f; // Mask to fit field (~253 bits)
+    secret[0] &= 0x1f; // Mask top (big-endian) byte to fit field (~253 bits)
     return secret;
 }
 
 export async function computeLeafHash(wallet: string, amount: bigint, secret: Uint8Array): Promise<Uint8Array> {
     const walletPubkey = new PublicKey(wallet);
-    const walletBn = BigInt("0x" + walletPubkey.toBuffer().toString("hex"));
-    // Send full wallet address (Backend/Noir will handle Field Modulo reduction)
-    // const walletMasked = walletBn & ((1n << 253n) - 1n); 
+    // First 31 bytes of the pubkey, matching the backend leaf and on-chain recipient check
+    const walletBn = BigInt("0x" + walletPubkey.toBuffer().subarray(0, 31).toString("hex"));
 
     const amountLamports = amount;
     const secretBn = BigInt("0x" + Buffer.from(secret).toString("hex"));
@@ -68,6 +67,8 @@ export async function computeNullifier(secret: Uint8Array, leafIndex: number): P
 export async function buildMerkleTree(recipients: Recipient[]): Promise<{
     root: Uint8Array;
     leaves: Uint8Array[];
+    // Secrets in recipient order, sent to the backend so it can rebuild the tree
+    secrets: Uint8Array[];
     // We don't return full tree usually, just root and computed leaves
 }> {
     // 1. Assign secrets
@@ -117,10 +118,51 @@ export async function buildMerkleTree(recipients: Recipient[]): Promise<{
 
     return {
         root: bigIntToBytes(rootBn),
-        leaves: leavesBytes
+        leaves: leavesBytes,
+        secrets: recipientsWithSecrets.map(r => r.secret!)
     };
 }
 
+async function sha256(...parts: Uint8Array[]): Promise<Uint8Array> {
+    const data = new Uint8Array(parts.reduce((len, p) => len + p.length, 0));
+    let offset = 0;
+    for (const part of parts) {
+        data.set(part, offset);
+        offset += part.length;
+    }
+    return new Uint8Array(await crypto.subtle.digest("SHA-256", data));
+}
+
+/**
+ * Leaf checked on chain by `claim` / `claim_token`:
+ * sha256(0x00 || claimer || amount as u64 little-endian)
+ */
+export async function computeClaimLeaf(wallet: string, amount: bigint): Promise<Uint8Array> {
+    const amountLe = new Uint8Array(8);
+    new DataView(amountLe.buffer).setBigUint64(0, amount, true);
+    return sha256(Uint8Array.of(0x00), new PublicKey(wallet).toBytes(), amountLe);
+}
+
+/**
+ * Root of the allocation tree `claim` / `claim_token` prove against
+ * (nodes are sha256(0x01 || left || right), leaves in recipient order)
+ */
+export async function buildClaimTree(recipients: Recipient[]): Promise<Uint8Array> {
+    let level = await Promise.all(recipients.map(r => computeClaimLeaf(r.wallet, r.amount)));
+    let zero = new Uint8Array(32);
+
+    for (let i = 0; i < TREE_DEPTH; i++) {
+        const next: Promise<Uint8Array>[] = [];
+        for (let j = 0; j < level.length; j += 2) {
+            next.push(sha256(Uint8Array.of(0x01), level[j], level[j + 1] ?? zero));
+        }
+        level = await Promise.all(next);
+        zero = await sha256(Uint8Array.of(0x01), zero, zero);
+    }
+
+    return level[0] ?? zero;
+}
+
 // Helper
 function bigIntToBytes(bn: bigint): Uint8Array {
     let hex = bn.toString(16);
@@ -191,8 +191,9 @@ export function formatProofForSolana(proof: ZkProof): {
   groth16Proof: number[];
   publicInputs: number[];
 } {
-  // Groth16 proof is 256 bytes (2 G1 + 1 G2 point)
-  const proofBytes = Array.from(proof.proof.slice(0, 256));
+  // gnark Groth16 proof is 388 bytes (GROTH16_PROOF_SIZE): Ar, Bs, Krs,
+  // commitment count, one BSB22 commitment and its proof of knowledge
+  const proofBytes = Array.from(proof.proof.slice(0, 388));
 
   // Public inputs: merkle_root (32) + nullifier_hash (32) + recipient (32)
   const publicInputBytes: number[] = [];
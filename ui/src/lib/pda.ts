@@ -1,10 +1,11 @@
 import { PublicKey } from "@solana/web3.js";
-import { buildMerkleTree, toHex } from "./merkle";
+import { buildClaimTree, buildMerkleTree, toHex } from "./merkle";
 
 // Program ID
 export const PROGRAM_ID = new PublicKey("7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v");
 
-// Sunspot Verifier Program ID (deployed on devnet)
+// Sunspot Verifier Program ID (deployed on devnet) of the TREE_DEPTH circuit;
+// the program checks it against the campaign's tree depth
 export const ZK_VERIFIER_PROGRAM_ID = new PublicKey("5C5x84vdrZi1h89u4g7VBsKyrBk5AQ1RjnrFFd5KvWuj");
 
 /**
@@ -36,7 +37,7 @@ export function deriveVaultPDA(authority: PublicKey, campaignId: string): [Publi
 }
 
 /**
- * Derive Claim Record PDA address
+ * Derive Claim Record PDA address (shared by the legacy and ZK claims)
  */
 export function deriveClaimRecordPDA(campaign: PublicKey, claimer: PublicKey): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
@@ -49,20 +50,6 @@ export function deriveClaimRecordPDA(campaign: PublicKey, claimer: PublicKey): [
     );
 }
 
-/**
- * Derive Nullifier Record PDA address (for ZK claims)
- */
-export function deriveNullifierRecordPDA(campaign: PublicKey, nullifier: Uint8Array): [PublicKey, number] {
-    return PublicKey.findProgramAddressSync(
-        [
-            Buffer.from("nullifier"),
-            campaign.toBuffer(),
-            nullifier,
-        ],
-        PROGRAM_ID
-    );
-}
-
 /**
  * Generate a unique campaign ID (short, URL-friendly)
  */
@@ -74,12 +61,15 @@ export function generateCampaignId(): string {
 
 /**
  * Generate a proper merkle root from recipient list using Poseidon hashing (async)
+ * Returns the per-recipient secrets too; the backend needs them to rebuild the tree.
+ * `claimRoot` is the SHA-256 allocation root the non-ZK claims are checked against.
  */
-export async function generateMerkleRoot(recipients: { wallet: string; amount: bigint }[]): Promise<Uint8Array> {
+export async function generateMerkleRoot(recipients: { wallet: string; amount: bigint }[]): Promise<{ root: Uint8Array; claimRoot: Uint8Array; secrets: Uint8Array[] }> {
     // Build merkle tree (secrets handled inside)
-    const { root } = await buildMerkleTree(recipients);
+    const { root, secrets } = await buildMerkleTree(recipients);
+    const claimRoot = await buildClaimTree(recipients);
 
-    return root;
+    return { root, claimRoot, secrets };
 }
 
 /**
@@ -0,0 +1,63 @@
+/**
+ * Vesting schedules, as the backend takes them and the program stores them
+ * (wire/src/vesting.rs `VestingSchedule`)
+ */
+
+/** Size of an encoded schedule in the Campaign account */
+export const VESTING_SCHEDULE_SIZE = 82;
+
+/** Most rows of a tranche table */
+export const MAX_TRANCHES = 8;
+
+/** Seconds between unlocks for each release frequency */
+export const RELEASE_PERIODS = {
+    daily: 86400,
+    weekly: 7 * 86400,
+    monthly: 30 * 86400,
+} as const;
+
+/** A row of a tranche table: `bps` of the allocation vested by `offset_seconds` */
+export interface VestingTranche {
+    offset_seconds: number;
+    bps: number;
+}
+
+export type VestingSchedule =
+    | { kind: "linear" }
+    | { kind: "step"; period_seconds: number }
+    | { kind: "tge_linear"; tge_bps: number }
+    | { kind: "tranches"; tranches: VestingTranche[] };
+
+/**
+ * Encode a schedule for `createCampaign` / `createTokenCampaign`
+ *
+ * Tag byte (0 linear, 1 step, 2 TGE + linear, 3 tranches), tranche count,
+ * then the variant's little-endian fields, zero-padded.
+ */
+export function encodeVestingSchedule(schedule: VestingSchedule): number[] {
+    const bytes = Buffer.alloc(VESTING_SCHEDULE_SIZE);
+    switch (schedule.kind) {
+        case "linear":
+            break;
+        case "step":
+            bytes[0] = 1;
+            bytes.writeBigInt64LE(BigInt(schedule.period_seconds), 2);
+            break;
+        case "tge_linear":
+            bytes[0] = 2;
+            bytes.writeUInt16LE(schedule.tge_bps, 2);
+            break;
+        case "tranches":
+            if (schedule.tranches.length > MAX_TRANCHES) {
+                throw new Error(`At most ${MAX_TRANCHES} vesting tranches`);
+            }
+            bytes[0] = 3;
+            bytes[1] = schedule.tranches.length;
+            schedule.tranches.forEach((t, i) => {
+                bytes.writeBigInt64LE(BigInt(t.offset_seconds), 2 + i * 10);
+                bytes.writeUInt16LE(t.bps, 2 + i * 10 + 8);
+            });
+            break;
+    }
+    return Array.from(bytes);
+}
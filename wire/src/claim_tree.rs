@@ -0,0 +1,131 @@
+//! Merkle tree of `(claimer, amount)` allocations for `claim` and
+//! `claim_token`
+//!
+//! The ZK claims commit to Poseidon2 leaves that only the circuit can
+//! afford. The legacy instructions check inclusion on chain instead, so
+//! their tree is hashed with SHA-256, which the program gets as a syscall:
+//! - leaf: `sha256(0x00 || claimer || amount as u64 LE)`
+//! - node: `sha256(0x01 || left || right)`
+//!
+//! The prefixes keep a leaf from ever passing for a node. Leaves are
+//! ordered by index: at each level the low bit of the index says whether
+//! the current node is the right child. Unused leaves are `[0; 32]`.
+//!
+//! The hash function is passed in, so the program can use its syscall and
+//! the API any SHA-256 implementation, over the same byte layout.
+
+/// A 32-byte hash
+pub type Hash = [u8; 32];
+
+/// SHA-256 over the concatenation of the given slices
+pub type Hasher = fn(&[&[u8]]) -> Hash;
+
+/// Prefix of leaf preimages
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// Prefix of node preimages
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// Deepest tree a proof is accepted for (2^32 leaves)
+pub const MAX_DEPTH: usize = 32;
+
+/// Leaf committing to `claimer` (a 32-byte public key) being owed `amount`
+pub fn leaf(hash: Hasher, claimer: &[u8; 32], amount: u64) -> Hash {
+    hash(&[&[LEAF_PREFIX], claimer, &amount.to_le_bytes()])
+}
+
+/// Parent of two nodes
+pub fn node(hash: Hasher, left: &Hash, right: &Hash) -> Hash {
+    hash(&[&[NODE_PREFIX], left, right])
+}
+
+/// Root reached from `leaf` at `index` through `siblings` (leaf level first)
+///
+/// `None` if the path is deeper than `MAX_DEPTH` or `index` does not fit a
+/// tree of its depth, so one leaf has exactly one valid proof.
+pub fn compute_root(hash: Hasher, leaf: Hash, index: u32, siblings: &[Hash]) -> Option<Hash> {
+    if siblings.len() > MAX_DEPTH || (index as u64) >> siblings.len() != 0 {
+        return None;
+    }
+
+    let mut current = leaf;
+    let mut idx = index;
+    for sibling in siblings {
+        current = if idx % 2 == 1 {
+            node(hash, sibling, &current)
+        } else {
+            node(hash, &current, sibling)
+        };
+        idx /= 2;
+    }
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Not SHA-256, but enough to tell inputs and their order apart
+    fn toy_hash(parts: &[&[u8]]) -> Hash {
+        let mut out = [0u8; 32];
+        let mut state = 0xcbf2_9ce4_8422_2325u64;
+        for byte in parts.iter().flat_map(|p| p.iter()) {
+            state = (state ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+        for chunk in out.chunks_mut(8) {
+            state = state.wrapping_mul(0x0100_0000_01b3) ^ 0x9e37_79b9;
+            chunk.copy_from_slice(&state.to_le_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn test_compute_root() {
+        let leaves: Vec<Hash> = (0..4u64)
+            .map(|i| leaf(toy_hash, &[i as u8; 32], i))
+            .collect();
+        let left = node(toy_hash, &leaves[0], &leaves[1]);
+        let right = node(toy_hash, &leaves[2], &leaves[3]);
+        let root = node(toy_hash, &left, &right);
+
+        assert_eq!(
+            compute_root(toy_hash, leaves[0], 0, &[leaves[1], right]),
+            Some(root)
+        );
+        assert_eq!(
+            compute_root(toy_hash, leaves[3], 3, &[leaves[2], left]),
+            Some(root)
+        );
+        // Wrong position or sibling
+        assert_ne!(
+            compute_root(toy_hash, leaves[3], 2, &[leaves[2], left]),
+            Some(root)
+        );
+        assert_ne!(
+            compute_root(toy_hash, leaves[0], 0, &[leaves[2], right]),
+            Some(root)
+        );
+
+        // An index past the tree would alias a valid one
+        assert_eq!(
+            compute_root(toy_hash, leaves[3], 7, &[leaves[2], left]),
+            None
+        );
+        assert_eq!(
+            compute_root(toy_hash, leaves[0], 0, &[[0u8; 32]; MAX_DEPTH + 1]),
+            None
+        );
+        assert!(compute_root(toy_hash, leaves[0], u32::MAX, &[[0u8; 32]; MAX_DEPTH]).is_some());
+        assert_eq!(compute_root(toy_hash, leaves[0], 0, &[]), Some(leaves[0]));
+    }
+
+    #[test]
+    fn test_leaves_and_nodes_are_separated() {
+        let a = leaf(toy_hash, &[1u8; 32], 5);
+        assert_ne!(a, leaf(toy_hash, &[1u8; 32], 6));
+        assert_ne!(a, leaf(toy_hash, &[2u8; 32], 5));
+
+        let (x, y) = ([3u8; 32], [4u8; 32]);
+        assert_ne!(node(toy_hash, &x, &y), node(toy_hash, &y, &x));
+    }
+}
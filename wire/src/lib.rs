@@ -0,0 +1,397 @@
+//! Wire format of claim proofs
+//!
+//! The backend hands out, and the program consumes, gnark's raw Groth16
+//! encodings for the claim circuit (integers and field elements big-endian):
+//! - proof: `Ar || Bs || Krs || u32 nbCommitments || Commitment || CommitmentPok`,
+//!   388 bytes with the single BSB22 commitment sunspot emits
+//! - public witness (`.pw`): `u32 nbPublic || u32 nbSecret || u32 len ||
+//!   merkle_root || nullifier_hash || recipient || amount`, 140 bytes
+//!
+//! The zk verifier program takes `proof || public witness` as instruction
+//! data. Curve points are kept as raw bytes here; checking them is the
+//! verifier's job.
+//!
+//! [`vesting`] holds the vesting math claims are paid by, for the same
+//! reason: the API must report exactly what the program pays. Likewise
+//! [`claim_tree`] is the allocation tree `claim` and `claim_token` check
+//! proofs against, which the API builds.
+
+use std::fmt;
+
+pub mod claim_tree;
+pub mod vesting;
+
+/// Size of a BN254 field element
+pub const FIELD_SIZE: usize = 32;
+
+/// Size of an uncompressed G1 point
+pub const G1_SIZE: usize = 64;
+
+/// Size of an uncompressed G2 point
+pub const G2_SIZE: usize = 128;
+
+/// BSB22 commitments in a claim proof
+pub const NB_COMMITMENTS: usize = 1;
+
+/// Proof size: Ar, Bs, Krs, commitment count, commitment, proof of knowledge
+pub const PROOF_SIZE: usize = G1_SIZE + G2_SIZE + G1_SIZE + 4 + NB_COMMITMENTS * G1_SIZE + G1_SIZE;
+
+/// Public inputs of the claim circuit: merkle_root, nullifier_hash,
+/// recipient, amount
+pub const NB_PUBLIC_INPUTS: usize = 4;
+
+/// Merkle tree depths the claim circuit has a deployed verifier for
+/// (`just circuit-depth <n>` builds the others); the program keeps the
+/// verifier of each in `ZK_VERIFIERS`, in this order
+pub const ZK_TREE_DEPTHS: [u8; 1] = [8];
+
+/// Layout version of the program's `Campaign` account, its first field
+///
+/// The first deployment's accounts have no version byte (nor tree_depth,
+/// claim_root or vesting_schedule) and cannot be read by the current
+/// program; those campaigns need a fresh deployment.
+pub const CAMPAIGN_VERSION: u8 = 2;
+
+/// Public witness header: nbPublic, nbSecret, vector length
+pub const PUBLIC_WITNESS_HEADER_SIZE: usize = 12;
+
+/// Public witness size: header plus the public inputs
+pub const PUBLIC_WITNESS_SIZE: usize = PUBLIC_WITNESS_HEADER_SIZE + NB_PUBLIC_INPUTS * FIELD_SIZE;
+
+/// A big-endian BN254 scalar
+pub type Field = [u8; FIELD_SIZE];
+
+/// BN254 scalar field modulus r, big-endian
+const FR_MODULUS: Field = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// Malformed proof or public witness
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireError {
+    /// Input has the wrong size
+    Length { expected: usize, actual: usize },
+    /// The proof does not carry exactly `NB_COMMITMENTS` commitments
+    Commitments(u32),
+    /// The public witness header does not describe the claim circuit
+    Header {
+        nb_public: u32,
+        nb_secret: u32,
+        len: u32,
+    },
+    /// The public input at this index is not below the field modulus
+    NonCanonical(usize),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Length { expected, actual } => {
+                write!(f, "Expected {} bytes, got {}", expected, actual)
+            }
+            Self::Commitments(n) => {
+                write!(f, "Expected {} commitments, got {}", NB_COMMITMENTS, n)
+            }
+            Self::Header {
+                nb_public,
+                nb_secret,
+                len,
+            } => write!(
+                f,
+                "Public witness header says {} public, {} secret, {} elements",
+                nb_public, nb_secret, len
+            ),
+            Self::NonCanonical(i) => write!(f, "Public input {} is not a canonical field element", i),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+/// A claim circuit Groth16 proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proof {
+    pub ar: [u8; G1_SIZE],
+    pub bs: [u8; G2_SIZE],
+    pub krs: [u8; G1_SIZE],
+    pub commitment: [u8; G1_SIZE],
+    pub commitment_pok: [u8; G1_SIZE],
+}
+
+impl Proof {
+    /// Parse a sunspot `.proof`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut r = Reader::new(bytes, PROOF_SIZE)?;
+        let ar = r.array();
+        let bs = r.array();
+        let krs = r.array();
+        let nb_commitments = r.u32();
+        if nb_commitments as usize != NB_COMMITMENTS {
+            return Err(WireError::Commitments(nb_commitments));
+        }
+
+        Ok(Self {
+            ar,
+            bs,
+            krs,
+            commitment: r.array(),
+            commitment_pok: r.array(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; PROOF_SIZE] {
+        let mut w = Writer::<PROOF_SIZE>::new();
+        w.put(&self.ar);
+        w.put(&self.bs);
+        w.put(&self.krs);
+        w.put(&(NB_COMMITMENTS as u32).to_be_bytes());
+        w.put(&self.commitment);
+        w.put(&self.commitment_pok);
+        w.finish()
+    }
+}
+
+/// Public inputs of a claim proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicWitness {
+    pub merkle_root: Field,
+    pub nullifier_hash: Field,
+    /// Claimer wallet, see `recipient_field`
+    pub recipient: Field,
+    /// Allocation in the proven leaf, see `amount_field`
+    pub amount: Field,
+}
+
+impl PublicWitness {
+    /// Parse a sunspot `.pw`, checking the header and that every input is
+    /// a canonical field element
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut r = Reader::new(bytes, PUBLIC_WITNESS_SIZE)?;
+        let (nb_public, nb_secret, len) = (r.u32(), r.u32(), r.u32());
+        if nb_public as usize != NB_PUBLIC_INPUTS || nb_secret != 0 || len != nb_public {
+            return Err(WireError::Header {
+                nb_public,
+                nb_secret,
+                len,
+            });
+        }
+
+        let witness = Self {
+            merkle_root: r.array(),
+            nullifier_hash: r.array(),
+            recipient: r.array(),
+            amount: r.array(),
+        };
+        match witness.inputs().iter().position(|input| *input >= FR_MODULUS) {
+            Some(i) => Err(WireError::NonCanonical(i)),
+            None => Ok(witness),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLIC_WITNESS_SIZE] {
+        let len = (NB_PUBLIC_INPUTS as u32).to_be_bytes();
+        let mut w = Writer::<PUBLIC_WITNESS_SIZE>::new();
+        w.put(&len);
+        w.put(&0u32.to_be_bytes());
+        w.put(&len);
+        for input in self.inputs() {
+            w.put(&input);
+        }
+        w.finish()
+    }
+
+    /// Public inputs in circuit order
+    pub fn inputs(&self) -> [Field; NB_PUBLIC_INPUTS] {
+        [
+            self.merkle_root,
+            self.nullifier_hash,
+            self.recipient,
+            self.amount,
+        ]
+    }
+}
+
+/// Field element a wallet is bound to in the circuit: the first 31 bytes
+/// of the public key, right-aligned so the value stays below the modulus
+pub fn recipient_field(pubkey: &[u8; 32]) -> Field {
+    let mut field = [0u8; FIELD_SIZE];
+    field[1..].copy_from_slice(&pubkey[..31]);
+    field
+}
+
+/// Field element an amount is proven as: the integer, big-endian
+pub fn amount_field(amount: u64) -> Field {
+    let mut field = [0u8; FIELD_SIZE];
+    field[FIELD_SIZE - 8..].copy_from_slice(&amount.to_be_bytes());
+    field
+}
+
+/// Instruction data for the zk verifier program: `proof || public witness`
+pub fn verifier_instruction_data(proof: &Proof, witness: &PublicWitness) -> Vec<u8> {
+    let mut data = Vec::with_capacity(PROOF_SIZE + PUBLIC_WITNESS_SIZE);
+    data.extend_from_slice(&proof.to_bytes());
+    data.extend_from_slice(&witness.to_bytes());
+    data
+}
+
+/// Cursor over input whose length has been checked up front
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], expected: usize) -> Result<Self, WireError> {
+        if bytes.len() != expected {
+            return Err(WireError::Length {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        Ok(Self(bytes))
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let (head, tail) = self.0.split_at(N);
+        self.0 = tail;
+        head.try_into().unwrap()
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_be_bytes(self.array())
+    }
+}
+
+/// Fixed-size output buffer
+struct Writer<const N: usize> {
+    out: [u8; N],
+    pos: usize,
+}
+
+impl<const N: usize> Writer<N> {
+    fn new() -> Self {
+        Self { out: [0u8; N], pos: 0 }
+    }
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.out[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    fn finish(self) -> [u8; N] {
+        debug_assert_eq!(self.pos, N);
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of `just prove` for the sample `Prover.toml`, from the first
+    /// circuit (no public amount); the proof layout is unchanged since
+    const SAMPLE_PROOF: &[u8; PROOF_SIZE] = include_bytes!("../fixtures/v1/claim.proof");
+    const V1_WITNESS: &[u8] = include_bytes!("../fixtures/v1/claim.pw");
+
+    /// Public witness of the sample `Prover.toml`
+    const SAMPLE_WITNESS: &[u8; PUBLIC_WITNESS_SIZE] = include_bytes!("../fixtures/claim.pw");
+
+    fn field(last: u8) -> Field {
+        let mut f = [0u8; FIELD_SIZE];
+        f[31] = last;
+        f
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(PROOF_SIZE, 388);
+        assert_eq!(PUBLIC_WITNESS_SIZE, 140);
+    }
+
+    #[test]
+    fn test_sunspot_output_round_trips() {
+        let proof = Proof::from_bytes(SAMPLE_PROOF).unwrap();
+        assert_eq!(&proof.to_bytes(), SAMPLE_PROOF);
+
+        let witness = PublicWitness::from_bytes(SAMPLE_WITNESS).unwrap();
+        assert_eq!(&witness.to_bytes(), SAMPLE_WITNESS);
+        assert_eq!(&witness.merkle_root, &SAMPLE_WITNESS[12..44]);
+        assert_eq!(&witness.recipient, &SAMPLE_WITNESS[76..108]);
+        assert_eq!(witness.amount, amount_field(391_000_000_000));
+        // The first circuit's inputs, plus the amount
+        assert_eq!(&SAMPLE_WITNESS[12..108], &V1_WITNESS[12..]);
+
+        let data = verifier_instruction_data(&proof, &witness);
+        assert_eq!(&data[..PROOF_SIZE], SAMPLE_PROOF);
+        assert_eq!(&data[PROOF_SIZE..], SAMPLE_WITNESS);
+    }
+
+    #[test]
+    fn test_public_witness_layout() {
+        let witness = PublicWitness {
+            merkle_root: field(1),
+            nullifier_hash: field(2),
+            recipient: field(3),
+            amount: field(4),
+        };
+        let bytes = witness.to_bytes();
+        assert_eq!(&bytes[..12], &[0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 4]);
+        assert_eq!(bytes[12 + 31], 1);
+        assert_eq!(bytes[12 + 63], 2);
+        assert_eq!(bytes[12 + 95], 3);
+        assert_eq!(bytes[12 + 127], 4);
+        assert_eq!(PublicWitness::from_bytes(&bytes), Ok(witness));
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        assert_eq!(
+            Proof::from_bytes(&SAMPLE_PROOF[..256]),
+            Err(WireError::Length {
+                expected: PROOF_SIZE,
+                actual: 256
+            })
+        );
+        let mut proof = *SAMPLE_PROOF;
+        proof[259] = 0;
+        assert_eq!(Proof::from_bytes(&proof), Err(WireError::Commitments(0)));
+
+        // The old backend layout: inputs without the header
+        assert!(matches!(
+            PublicWitness::from_bytes(&SAMPLE_WITNESS[12..]),
+            Err(WireError::Length { .. })
+        ));
+        // A proof from the first circuit, which left the amount private
+        assert!(matches!(
+            PublicWitness::from_bytes(V1_WITNESS),
+            Err(WireError::Length { .. })
+        ));
+        let mut witness = *SAMPLE_WITNESS;
+        witness[3] = 3;
+        assert!(matches!(
+            PublicWitness::from_bytes(&witness),
+            Err(WireError::Header { nb_public: 3, .. })
+        ));
+        let mut witness = *SAMPLE_WITNESS;
+        witness[12 + 32..12 + 64].copy_from_slice(&FR_MODULUS);
+        assert_eq!(
+            PublicWitness::from_bytes(&witness),
+            Err(WireError::NonCanonical(1))
+        );
+    }
+
+    #[test]
+    fn test_amount_field() {
+        let field = amount_field(0x0102_0304_0506_0708);
+        assert_eq!(&field[24..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(field[..24].iter().all(|b| *b == 0));
+        assert!(amount_field(u64::MAX) < FR_MODULUS);
+    }
+
+    #[test]
+    fn test_recipient_field_drops_last_byte() {
+        let pubkey: [u8; 32] = std::array::from_fn(|i| i as u8 + 1);
+        let field = recipient_field(&pubkey);
+        assert_eq!(field[0], 0);
+        assert_eq!(&field[1..], &pubkey[..31]);
+        assert!(field < FR_MODULUS);
+    }
+}
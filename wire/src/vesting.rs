@@ -0,0 +1,892 @@
+//! Vesting of claim allocations
+//!
+//! The schedule every claim instruction of the program pays by, and the one
+//! the API reports to recipients. A campaign vests from `start` over
+//! `duration` seconds, with nothing before the cliff, along one of these
+//! curves ([`VestingSchedule`]):
+//! - linear: the share of the allocation proportional to the time since
+//!   `start`, rounded down;
+//! - step: the linear share at the last whole `period` since `start`;
+//! - TGE + linear: a share unlocked at `start`, before any cliff, and the
+//!   rest linearly;
+//! - tranches: a table of cumulative shares unlocked at offsets from
+//!   `start`.
+//!
+//! A zero duration vests everything at once, cliff or not. A claim pays
+//! what has vested and not been claimed yet ([`Vesting::claimable`]).
+//!
+//! The program stores a schedule as its fixed-size encoding
+//! ([`VestingSchedule::to_bytes`]) and checks it with
+//! [`Vesting::validate`] when a campaign is created.
+//!
+//! `fixtures/vesting.txt` holds test vectors both sides check against.
+
+use std::fmt;
+
+/// Basis points of a whole allocation
+pub const BPS: u16 = 10_000;
+
+/// Most rows of a tranche table
+pub const MAX_TRANCHES: usize = 8;
+
+/// Most unlocks of a step schedule (daily for ten years)
+pub const MAX_STEPS: i64 = 3_660;
+
+/// Size of an encoded `VestingSchedule`: tag, tranche count, then the
+/// variant's fields, zero-padded
+pub const SCHEDULE_SIZE: usize = 2 + MAX_TRANCHES * 10;
+
+/// A row of a tranche table: `bps` of the allocation (cumulative) has
+/// vested `offset` seconds after the start
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tranche {
+    pub offset: i64,
+    pub bps: u16,
+}
+
+/// Shape of a vesting curve
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VestingSchedule {
+    /// Linear after the cliff
+    #[default]
+    Linear,
+    /// Linear, but only unlocked at each whole `period` (seconds) since the
+    /// start; the duration is a multiple of it
+    Step { period: i64 },
+    /// `tge_bps` of the allocation at the start, cliff or not, then the
+    /// rest linearly after the cliff
+    TgeLinear { tge_bps: u16 },
+    /// The first `len` rows of `tranches`; the last one vests everything,
+    /// at `duration`. Use [`VestingSchedule::tranches`] to build one.
+    Tranches {
+        len: u8,
+        tranches: [Tranche; MAX_TRANCHES],
+    },
+}
+
+/// A campaign's vesting parameters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Vesting {
+    /// Unix timestamp vesting starts at
+    pub start: i64,
+    /// Seconds after `start` before anything vests
+    pub cliff: i64,
+    /// Seconds after `start` until everything has vested (0 = instant)
+    pub duration: i64,
+    pub schedule: VestingSchedule,
+}
+
+/// Why a claim would pay nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimError {
+    /// The cliff has not ended
+    BeforeCliff,
+    /// The whole allocation has been paid out
+    FullyClaimed,
+    /// Nothing vested since the previous claim
+    NothingVested,
+}
+
+impl fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BeforeCliff => write!(f, "Vesting cliff not reached"),
+            Self::FullyClaimed => write!(f, "Allocation fully claimed"),
+            Self::NothingVested => write!(f, "Nothing vested since the last claim"),
+        }
+    }
+}
+
+impl std::error::Error for ClaimError {}
+
+/// Why a schedule is refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// Unknown tag or non-zero padding in an encoded schedule
+    Malformed,
+    /// Negative cliff or duration
+    NegativeTime,
+    /// Step period not positive, not dividing the duration, or making more
+    /// than `MAX_STEPS` steps
+    InvalidPeriod,
+    /// TGE share above 100%
+    InvalidTge,
+    /// Tranche table empty, too long, not strictly increasing, not ending
+    /// at 100% at the duration, or combined with a cliff
+    InvalidTranches,
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "Malformed vesting schedule"),
+            Self::NegativeTime => write!(f, "Vesting cliff and duration must not be negative"),
+            Self::InvalidPeriod => write!(
+                f,
+                "Step period must be positive, divide the duration and make at most {} steps",
+                MAX_STEPS
+            ),
+            Self::InvalidTge => write!(f, "TGE share must be at most {} bps", BPS),
+            Self::InvalidTranches => write!(
+                f,
+                "Tranches must be 1 to {} rows with increasing offsets and shares, \
+                 ending at {} bps at the duration, without a cliff",
+                MAX_TRANCHES, BPS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// A point of an unlock schedule: `vested` of the allocation at `at`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unlock {
+    pub at: i64,
+    pub vested: u64,
+    /// Whether the vested amount grows linearly from here to the next point,
+    /// rather than holding until it
+    pub linear: bool,
+}
+
+impl VestingSchedule {
+    /// A tranche table; `None` if it has more than `MAX_TRANCHES` rows
+    pub fn tranches(rows: &[Tranche]) -> Option<Self> {
+        if rows.len() > MAX_TRANCHES {
+            return None;
+        }
+        let mut tranches = [Tranche::default(); MAX_TRANCHES];
+        tranches[..rows.len()].copy_from_slice(rows);
+        Some(Self::Tranches {
+            len: rows.len() as u8,
+            tranches,
+        })
+    }
+
+    /// Rows of a tranche table; empty for the other schedules
+    pub fn tranche_rows(&self) -> &[Tranche] {
+        match self {
+            Self::Tranches { len, tranches } => &tranches[..(*len as usize).min(MAX_TRANCHES)],
+            _ => &[],
+        }
+    }
+
+    /// Fixed-size encoding, as stored by the program
+    ///
+    /// Byte 0 is the tag (0 linear, 1 step, 2 TGE + linear, 3 tranches).
+    /// Step stores its period (i64 LE) and TGE its share (u16 LE) from
+    /// byte 2; tranches store their count in byte 1 and then
+    /// `MAX_TRANCHES` rows of offset (i64 LE) and share (u16 LE).
+    pub fn to_bytes(&self) -> [u8; SCHEDULE_SIZE] {
+        let mut out = [0u8; SCHEDULE_SIZE];
+        match self {
+            Self::Linear => {}
+            Self::Step { period } => {
+                out[0] = 1;
+                out[2..10].copy_from_slice(&period.to_le_bytes());
+            }
+            Self::TgeLinear { tge_bps } => {
+                out[0] = 2;
+                out[2..4].copy_from_slice(&tge_bps.to_le_bytes());
+            }
+            Self::Tranches { len, tranches } => {
+                out[0] = 3;
+                out[1] = *len;
+                for (row, tranche) in out[2..].chunks_exact_mut(10).zip(tranches) {
+                    row[..8].copy_from_slice(&tranche.offset.to_le_bytes());
+                    row[8..].copy_from_slice(&tranche.bps.to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+
+    /// Decode `to_bytes` output; any other encoding is `Malformed`
+    pub fn from_bytes(bytes: &[u8; SCHEDULE_SIZE]) -> Result<Self, ScheduleError> {
+        let i64_at = |i: usize| i64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        let u16_at = |i: usize| u16::from_le_bytes(bytes[i..i + 2].try_into().unwrap());
+
+        let schedule = match bytes[0] {
+            0 => Self::Linear,
+            1 => Self::Step { period: i64_at(2) },
+            2 => Self::TgeLinear { tge_bps: u16_at(2) },
+            3 if bytes[1] as usize <= MAX_TRANCHES => {
+                let mut tranches = [Tranche::default(); MAX_TRANCHES];
+                for (i, tranche) in tranches.iter_mut().enumerate() {
+                    let row = 2 + i * 10;
+                    *tranche = Tranche {
+                        offset: i64_at(row),
+                        bps: u16_at(row + 8),
+                    };
+                }
+                Self::Tranches {
+                    len: bytes[1],
+                    tranches,
+                }
+            }
+            _ => return Err(ScheduleError::Malformed),
+        };
+        // Padding and unused rows must be zero, so each schedule has one
+        // encoding
+        if schedule.to_bytes() != *bytes {
+            return Err(ScheduleError::Malformed);
+        }
+        Ok(schedule)
+    }
+}
+
+impl Vesting {
+    /// Reject parameters the vesting math does not accept
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        if self.cliff < 0 || self.duration < 0 {
+            return Err(ScheduleError::NegativeTime);
+        }
+        match self.schedule {
+            VestingSchedule::Linear => Ok(()),
+            VestingSchedule::Step { period } => {
+                if period <= 0
+                    || self.duration == 0
+                    || self.duration % period != 0
+                    || self.duration / period > MAX_STEPS
+                {
+                    return Err(ScheduleError::InvalidPeriod);
+                }
+                Ok(())
+            }
+            VestingSchedule::TgeLinear { tge_bps } => {
+                if tge_bps > BPS {
+                    return Err(ScheduleError::InvalidTge);
+                }
+                Ok(())
+            }
+            VestingSchedule::Tranches { len, .. } => {
+                let rows = self.schedule.tranche_rows();
+                let increasing = rows
+                    .windows(2)
+                    .all(|pair| pair[0].offset < pair[1].offset && pair[0].bps < pair[1].bps);
+                let valid = (1..=MAX_TRANCHES).contains(&(len as usize))
+                    && increasing
+                    && rows[0].offset >= 0
+                    && rows[0].bps > 0
+                    && rows[rows.len() - 1].bps == BPS
+                    && rows[rows.len() - 1].offset == self.duration
+                    && self.cliff == 0;
+                if !valid {
+                    return Err(ScheduleError::InvalidTranches);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_instant(&self) -> bool {
+        self.duration <= 0
+    }
+
+    /// When the cliff ends
+    pub fn cliff_end(&self) -> i64 {
+        self.start.saturating_add(self.cliff)
+    }
+
+    /// When the whole allocation has vested
+    pub fn end(&self) -> i64 {
+        if self.is_instant() {
+            self.start
+        } else {
+            self.start.saturating_add(self.duration)
+        }
+    }
+
+    /// Whether `now` is before the cliff ends (a TGE share is claimable
+    /// before that)
+    pub fn before_cliff(&self, now: i64) -> bool {
+        !self.is_instant() && now < self.cliff_end()
+    }
+
+    /// Amount of `allocation` vested at `now`
+    pub fn vested(&self, allocation: u64, now: i64) -> u64 {
+        if self.is_instant() {
+            return allocation;
+        }
+        let elapsed = now.saturating_sub(self.start);
+        match self.schedule {
+            VestingSchedule::Linear => self.after_tge(allocation, 0, now),
+            VestingSchedule::TgeLinear { tge_bps } => {
+                if elapsed < 0 {
+                    return 0;
+                }
+                let tge = share(allocation, tge_bps as i128, BPS as i128);
+                self.after_tge(allocation, tge, now)
+            }
+            VestingSchedule::Step { period } => {
+                if self.before_cliff(now) {
+                    return 0;
+                }
+                // Past the cliff, so elapsed >= 0
+                self.linear(allocation, elapsed - elapsed % period)
+            }
+            VestingSchedule::Tranches { .. } => {
+                let bps = self
+                    .schedule
+                    .tranche_rows()
+                    .iter()
+                    .take_while(|tranche| tranche.offset <= elapsed)
+                    .last()
+                    .map_or(0, |tranche| tranche.bps);
+                share(allocation, bps as i128, BPS as i128)
+            }
+        }
+    }
+
+    /// `tge` of `allocation`, plus the rest linearly once the cliff has ended
+    fn after_tge(&self, allocation: u64, tge: u64, now: i64) -> u64 {
+        if self.before_cliff(now) {
+            return tge;
+        }
+        tge + self.linear(allocation - tge, now.saturating_sub(self.start))
+    }
+
+    /// Share of `amount` vested linearly `elapsed` seconds after the start
+    fn linear(&self, amount: u64, elapsed: i64) -> u64 {
+        if elapsed >= self.duration {
+            return amount;
+        }
+        // elapsed < duration, so this is below amount
+        share(amount, elapsed.max(0) as i128, self.duration as i128)
+    }
+
+    /// What a claim at `now` pays out of `allocation`, `claimed` of which
+    /// has been paid already
+    pub fn claimable(&self, allocation: u64, claimed: u64, now: i64) -> Result<u64, ClaimError> {
+        if claimed >= allocation {
+            return Err(ClaimError::FullyClaimed);
+        }
+        let vested = self.vested(allocation, now);
+        if vested == 0 && self.before_cliff(now) {
+            return Err(ClaimError::BeforeCliff);
+        }
+        match vested.saturating_sub(claimed) {
+            0 => Err(ClaimError::NothingVested),
+            amount => Ok(amount),
+        }
+    }
+
+    /// First time after `now` at which more of `allocation` has vested;
+    /// `None` once all of it has
+    pub fn next_unlock(&self, allocation: u64, now: i64) -> Option<i64> {
+        let vested = self.vested(allocation, now);
+        if vested >= allocation {
+            return None;
+        }
+
+        let offset = match self.schedule {
+            VestingSchedule::Linear => linear_offset(allocation, vested, self.duration),
+            VestingSchedule::Step { period } => {
+                let offset = linear_offset(allocation, vested, self.duration);
+                // Round up to the step it is reached at
+                (offset + period as i128 - 1) / period as i128 * period as i128
+            }
+            VestingSchedule::TgeLinear { tge_bps } => {
+                let tge = share(allocation, tge_bps as i128, BPS as i128);
+                if vested < tge {
+                    // Before the start
+                    return Some(self.start);
+                }
+                linear_offset(allocation - tge, vested - tge, self.duration)
+            }
+            VestingSchedule::Tranches { .. } => {
+                let tranche =
+                    self.schedule.tranche_rows().iter().find(|tranche| {
+                        share(allocation, tranche.bps as i128, BPS as i128) > vested
+                    })?;
+                tranche.offset as i128
+            }
+        };
+        // Nothing unlocks before the cliff, even past the end
+        let at = (self.start as i128 + offset)
+            .min(self.end() as i128)
+            .max(self.cliff_end() as i128);
+        Some(at as i64)
+    }
+
+    /// The unlock curve of `allocation`
+    ///
+    /// Nothing has vested before the first point. At each point the vested
+    /// amount is the point's; it then grows linearly (rounded down) to the
+    /// next point's if the point is `linear`, and holds until it otherwise.
+    pub fn schedule(&self, allocation: u64) -> Vec<Unlock> {
+        let point = |at: i64, linear: bool| Unlock {
+            at,
+            vested: self.vested(allocation, at),
+            linear,
+        };
+        let cliff_end = self.cliff_end();
+        if self.is_instant() {
+            return vec![Unlock {
+                at: self.start,
+                vested: allocation,
+                linear: false,
+            }];
+        }
+
+        let mut points = Vec::new();
+        match self.schedule {
+            VestingSchedule::Linear | VestingSchedule::TgeLinear { .. } => {
+                if cliff_end > self.start {
+                    // The TGE share, if any
+                    points.push(point(self.start, false));
+                }
+                if cliff_end >= self.end() {
+                    // All at once at a cliff past the end
+                    points.push(point(cliff_end, false));
+                } else {
+                    points.push(point(cliff_end, true));
+                    points.push(point(self.end(), false));
+                }
+            }
+            VestingSchedule::Step { period } => {
+                // The cliff, then every step after it
+                points.push(point(cliff_end, false));
+                let first = (self.cliff / period + 1).max(1);
+                for step in first..=self.duration / period {
+                    points.push(point(self.start.saturating_add(step * period), false));
+                }
+            }
+            VestingSchedule::Tranches { .. } => {
+                for tranche in self.schedule.tranche_rows() {
+                    points.push(point(self.start.saturating_add(tranche.offset), false));
+                }
+            }
+        }
+
+        // Keep the points where something changes, and the last one
+        let last = points.len() - 1;
+        let mut vested = 0;
+        let mut kept = Vec::with_capacity(points.len());
+        for (i, unlock) in points.into_iter().enumerate() {
+            if unlock.linear || unlock.vested > vested || i == last {
+                vested = unlock.vested;
+                kept.push(unlock);
+            }
+        }
+        kept
+    }
+}
+
+/// `amount * numerator / denominator`, rounded down
+fn share(amount: u64, numerator: i128, denominator: i128) -> u64 {
+    (amount as i128 * numerator / denominator) as u64
+}
+
+/// Smallest offset from the start at which `amount`, vesting linearly over
+/// `duration`, has more than `vested` vested
+fn linear_offset(amount: u64, vested: u64, duration: i64) -> i128 {
+    let (target, amount) = (vested as i128 + 1, amount as i128);
+    (target * duration as i128 + amount - 1) / amount
+}
+
+/// A test vector from `fixtures/vesting.txt`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VestingVector {
+    pub vesting: Vesting,
+    pub allocation: u64,
+    pub now: i64,
+    pub vested: u64,
+    pub next_unlock: Option<i64>,
+}
+
+/// Parse test vectors: one per line, whitespace-separated
+/// `start cliff duration allocation now vested next_unlock [schedule]`,
+/// with `-` for no next unlock; `#` starts a comment
+///
+/// The schedule is `linear` (the default), `step:<period>`, `tge:<bps>` or
+/// `tranches:<offset>=<bps>,...`.
+pub fn parse_vectors(text: &str) -> Vec<VestingVector> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (fields, schedule) = match fields[..] {
+                [.., schedule] if fields.len() == 8 => (&fields[..7], schedule),
+                _ => (&fields[..], "linear"),
+            };
+            let [start, cliff, duration, allocation, now, vested, next_unlock] = fields[..] else {
+                panic!("Malformed vesting vector: {}", line);
+            };
+            let int = |s: &str| s.parse::<i64>().expect("integer field");
+            VestingVector {
+                vesting: Vesting {
+                    start: int(start),
+                    cliff: int(cliff),
+                    duration: int(duration),
+                    schedule: parse_schedule(schedule),
+                },
+                allocation: allocation.parse().expect("allocation"),
+                now: int(now),
+                vested: vested.parse().expect("vested"),
+                next_unlock: (next_unlock != "-").then(|| int(next_unlock)),
+            }
+        })
+        .collect()
+}
+
+fn parse_schedule(text: &str) -> VestingSchedule {
+    let (kind, params) = text.split_once(':').unwrap_or((text, ""));
+    match kind {
+        "linear" => VestingSchedule::Linear,
+        "step" => VestingSchedule::Step {
+            period: params.parse().expect("step period"),
+        },
+        "tge" => VestingSchedule::TgeLinear {
+            tge_bps: params.parse().expect("TGE bps"),
+        },
+        "tranches" => {
+            let rows: Vec<Tranche> = params
+                .split(',')
+                .map(|row| {
+                    let (offset, bps) = row.split_once('=').expect("tranche offset=bps");
+                    Tranche {
+                        offset: offset.parse().expect("tranche offset"),
+                        bps: bps.parse().expect("tranche bps"),
+                    }
+                })
+                .collect();
+            VestingSchedule::tranches(&rows).expect("tranche count")
+        }
+        _ => panic!("Unknown vesting schedule: {}", text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VECTORS: &str = include_str!("../fixtures/vesting.txt");
+
+    fn linear(start: i64, cliff: i64, duration: i64) -> Vesting {
+        Vesting {
+            start,
+            cliff,
+            duration,
+            schedule: VestingSchedule::Linear,
+        }
+    }
+
+    fn tranches(rows: &[(i64, u16)]) -> VestingSchedule {
+        let rows: Vec<Tranche> = rows
+            .iter()
+            .map(|&(offset, bps)| Tranche { offset, bps })
+            .collect();
+        VestingSchedule::tranches(&rows).unwrap()
+    }
+
+    #[test]
+    fn test_vectors() {
+        let vectors = parse_vectors(VECTORS);
+        assert!(vectors.len() >= 10);
+        for v in vectors {
+            assert_eq!(v.vesting.validate(), Ok(()), "{:?}", v);
+            assert_eq!(v.vesting.vested(v.allocation, v.now), v.vested, "{:?}", v);
+            assert_eq!(
+                v.vesting.next_unlock(v.allocation, v.now),
+                v.next_unlock,
+                "{:?}",
+                v
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_unlock_is_the_first_increase() {
+        let schedules = [
+            VestingSchedule::Linear,
+            VestingSchedule::Step { period: 97 },
+            VestingSchedule::TgeLinear { tge_bps: 1_500 },
+            tranches(&[(5, 1), (300, 2_000), (485, BPS)]),
+        ];
+        for (schedule, cliff) in schedules.into_iter().zip([10, 150, 10, 0]) {
+            let vesting = Vesting {
+                start: 1_000,
+                cliff,
+                duration: 485,
+                schedule,
+            };
+            assert_eq!(vesting.validate(), Ok(()));
+            for allocation in [1, 3, 50, 1_000_000] {
+                let mut now = 900;
+                while let Some(at) = vesting.next_unlock(allocation, now) {
+                    let vested = vesting.vested(allocation, now);
+                    assert!(at > now, "{:?} {}", schedule, allocation);
+                    assert_eq!(vesting.vested(allocation, at - 1), vested, "{:?}", schedule);
+                    assert!(vesting.vested(allocation, at) > vested, "{:?}", schedule);
+                    now = at;
+                }
+                assert_eq!(vesting.vested(allocation, now), allocation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_claimable() {
+        let vesting = linear(1_000, 30, 100);
+
+        // Cliff
+        assert_eq!(
+            vesting.claimable(1_000, 0, 1_029),
+            Err(ClaimError::BeforeCliff)
+        );
+        assert_eq!(vesting.claimable(1_000, 0, 1_030), Ok(300));
+
+        // Mid-vest: only what vested since the last claim
+        assert_eq!(vesting.claimable(1_000, 300, 1_050), Ok(200));
+        assert_eq!(
+            vesting.claimable(1_000, 500, 1_050),
+            Err(ClaimError::NothingVested)
+        );
+        assert_eq!(vesting.claimable(1_000, 500, 1_051), Ok(10));
+
+        // Post-vest: the rest, then nothing
+        assert_eq!(vesting.claimable(1_000, 510, 1_099), Ok(480));
+        assert_eq!(vesting.claimable(1_000, 510, 1_100), Ok(490));
+        assert_eq!(vesting.claimable(1_000, 0, i64::MAX), Ok(1_000));
+        assert_eq!(
+            vesting.claimable(1_000, 1_000, 1_100),
+            Err(ClaimError::FullyClaimed)
+        );
+        assert_eq!(
+            vesting.claimable(0, 0, 1_100),
+            Err(ClaimError::FullyClaimed)
+        );
+
+        // Instant: everything, cliff or not
+        let instant = linear(1_000, 30, 0);
+        assert_eq!(instant.claimable(1_000, 0, 0), Ok(1_000));
+        assert_eq!(instant.claimable(1_000, 400, 0), Ok(600));
+
+        // The TGE share is claimable before the cliff
+        let tge = Vesting {
+            schedule: VestingSchedule::TgeLinear { tge_bps: 2_500 },
+            ..vesting
+        };
+        assert_eq!(tge.claimable(1_000, 0, 999), Err(ClaimError::BeforeCliff));
+        assert_eq!(tge.claimable(1_000, 0, 1_000), Ok(250));
+        assert_eq!(
+            tge.claimable(1_000, 250, 1_029),
+            Err(ClaimError::NothingVested)
+        );
+        assert_eq!(tge.claimable(1_000, 250, 1_030), Ok(225));
+    }
+
+    #[test]
+    fn test_validate() {
+        let vesting = linear(1_000, 0, 3_600);
+        assert_eq!(vesting.validate(), Ok(()));
+        assert_eq!(
+            linear(0, -1, 0).validate(),
+            Err(ScheduleError::NegativeTime)
+        );
+
+        let with = |cliff: i64, schedule: VestingSchedule| Vesting {
+            cliff,
+            schedule,
+            ..vesting
+        };
+        assert_eq!(
+            with(0, VestingSchedule::Step { period: 600 }).validate(),
+            Ok(())
+        );
+        for period in [0, -600, 700] {
+            assert_eq!(
+                with(0, VestingSchedule::Step { period }).validate(),
+                Err(ScheduleError::InvalidPeriod)
+            );
+        }
+        for (duration, period) in [(0, 600), ((MAX_STEPS + 1) * 60, 60)] {
+            assert_eq!(
+                Vesting {
+                    duration,
+                    ..with(0, VestingSchedule::Step { period })
+                }
+                .validate(),
+                Err(ScheduleError::InvalidPeriod)
+            );
+        }
+
+        assert_eq!(
+            with(60, VestingSchedule::TgeLinear { tge_bps: BPS }).validate(),
+            Ok(())
+        );
+        assert_eq!(
+            with(0, VestingSchedule::TgeLinear { tge_bps: BPS + 1 }).validate(),
+            Err(ScheduleError::InvalidTge)
+        );
+
+        assert_eq!(
+            with(0, tranches(&[(0, 1_000), (3_600, BPS)])).validate(),
+            Ok(())
+        );
+        for (cliff, schedule) in [
+            (0, tranches(&[])),
+            (60, tranches(&[(0, 1_000), (3_600, BPS)])),
+            (0, tranches(&[(0, 1_000), (3_000, BPS)])),
+            (0, tranches(&[(0, 1_000), (3_600, 9_999)])),
+            (0, tranches(&[(600, 1_000), (600, 2_000), (3_600, BPS)])),
+            (0, tranches(&[(600, 1_000), (700, 1_000), (3_600, BPS)])),
+            (0, tranches(&[(-1, 1_000), (3_600, BPS)])),
+            (0, tranches(&[(0, 0), (3_600, BPS)])),
+        ] {
+            assert_eq!(
+                with(cliff, schedule).validate(),
+                Err(ScheduleError::InvalidTranches),
+                "{:?}",
+                schedule
+            );
+        }
+        assert_eq!(
+            with(
+                0,
+                VestingSchedule::Tranches {
+                    len: 9,
+                    tranches: [Tranche::default(); 8]
+                }
+            )
+            .validate(),
+            Err(ScheduleError::InvalidTranches)
+        );
+        assert!(VestingSchedule::tranches(&[Tranche::default(); MAX_TRANCHES + 1]).is_none());
+    }
+
+    #[test]
+    fn test_schedule_encoding() {
+        for schedule in [
+            VestingSchedule::Linear,
+            VestingSchedule::Step { period: 2_592_000 },
+            VestingSchedule::TgeLinear { tge_bps: 1_000 },
+            tranches(&[(0, 1_000), (86_400, 5_000), (172_800, BPS)]),
+        ] {
+            let bytes = schedule.to_bytes();
+            assert_eq!(VestingSchedule::from_bytes(&bytes), Ok(schedule));
+        }
+
+        // Zeroed account space is linear
+        assert_eq!(
+            VestingSchedule::from_bytes(&[0u8; SCHEDULE_SIZE]),
+            Ok(VestingSchedule::Linear)
+        );
+
+        let mut bytes = VestingSchedule::TgeLinear { tge_bps: 1_000 }.to_bytes();
+        bytes[SCHEDULE_SIZE - 1] = 1;
+        assert_eq!(
+            VestingSchedule::from_bytes(&bytes),
+            Err(ScheduleError::Malformed)
+        );
+        let mut bytes = [0u8; SCHEDULE_SIZE];
+        bytes[0] = 4;
+        assert_eq!(
+            VestingSchedule::from_bytes(&bytes),
+            Err(ScheduleError::Malformed)
+        );
+        bytes[0] = 3;
+        bytes[1] = MAX_TRANCHES as u8 + 1;
+        assert_eq!(
+            VestingSchedule::from_bytes(&bytes),
+            Err(ScheduleError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_schedule() {
+        let linear_schedule = linear(100, 25, 100);
+        assert_eq!(
+            linear_schedule.schedule(1_000),
+            [
+                Unlock {
+                    at: 125,
+                    vested: 250,
+                    linear: true
+                },
+                Unlock {
+                    at: 200,
+                    vested: 1_000,
+                    linear: false
+                },
+            ]
+        );
+
+        let cliff_only = linear(100, 200, 100);
+        assert_eq!(
+            cliff_only.schedule(7),
+            [Unlock {
+                at: 300,
+                vested: 7,
+                linear: false
+            }]
+        );
+
+        let instant = linear(100, 50, 0);
+        assert_eq!(
+            instant.schedule(7),
+            [Unlock {
+                at: 100,
+                vested: 7,
+                linear: false
+            }]
+        );
+        assert!(!instant.before_cliff(0));
+
+        // Monthly steps over a quarter, after a 45-day cliff
+        let month = 2_592_000;
+        let steps = Vesting {
+            start: 0,
+            cliff: 3_888_000,
+            duration: 3 * month,
+            schedule: VestingSchedule::Step { period: month },
+        };
+        let points: Vec<(i64, u64, bool)> = steps
+            .schedule(900)
+            .iter()
+            .map(|u| (u.at, u.vested, u.linear))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                (3_888_000, 300, false),
+                (2 * month, 600, false),
+                (3 * month, 900, false)
+            ]
+        );
+
+        // 20% at TGE, then linear after a cliff
+        let tge = Vesting {
+            start: 100,
+            cliff: 50,
+            duration: 100,
+            schedule: VestingSchedule::TgeLinear { tge_bps: 2_000 },
+        };
+        let points: Vec<(i64, u64, bool)> = tge
+            .schedule(1_000)
+            .iter()
+            .map(|u| (u.at, u.vested, u.linear))
+            .collect();
+        assert_eq!(
+            points,
+            [(100, 200, false), (150, 600, true), (200, 1_000, false)]
+        );
+
+        let table = Vesting {
+            start: 100,
+            cliff: 0,
+            duration: 60,
+            schedule: tranches(&[(0, 1_000), (30, 4_000), (60, BPS)]),
+        };
+        let points: Vec<(i64, u64, bool)> = table
+            .schedule(10)
+            .iter()
+            .map(|u| (u.at, u.vested, u.linear))
+            .collect();
+        assert_eq!(points, [(100, 1, false), (130, 4, false), (160, 10, false)]);
+        // Shares too small to show up are skipped, the end is kept
+        assert_eq!(table.schedule(0).len(), 1);
+    }
+}